@@ -1,86 +1,1772 @@
-use std::time::Instant;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, Instant};
 
 use crossterm::terminal;
-use rand::rngs::ThreadRng;
-use rand::thread_rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::io;
 
+use crate::background::Starfield;
+use crate::beginner::{self, Stage};
+use crate::bld::{self, Memo};
+use crate::capture::{self, FrameDump, Turntable};
+use crate::cli::Options;
 use crate::config;
-use crate::cube::Cube;
-use crate::geometry::{Camera, Viewport};
+use crate::cube::{self, Cube, FACELET_COUNT, Move, ScrambleStyle};
+use crate::cube_explorer;
+use crate::effects::{CameraShake, Celebration, Explosion};
+use crate::export;
+use crate::geometry::{self, Camera, Viewport};
+use crate::history::{MoveCounts, MoveHistory, ScrambleLog};
 use crate::input::{Action, InputHandler};
-use crate::raster::Renderer;
-use crate::terminal::FrameWriter;
+use crate::last_layer;
+use crate::leaderboard::Leaderboard;
+use crate::nxn::{self, BigCube, LayerMove};
+use crate::optimal;
+use crate::raster::{self, FaceletLabel, Frame, NetLayout, RenderBackend};
+use crate::reconstruction::{self, Reconstruction};
+use crate::recording::{RecordFormat, Recorder};
+use crate::sixel::SixelRenderer;
+use crate::solver;
+use crate::stages::{self, Method};
+use crate::terminal::{FrameWriter, cell_pixel_size};
+use crate::theme;
+use crate::watch::StateWatch;
 
 type TermResult<T> = io::Result<T>;
 
+/// An optimal solve running on a background thread.
+struct OptimalJob {
+    /// The state being solved; the search is stopped if the cube moves on.
+    start: Cube,
+    status: String,
+    updates: Receiver<optimal::Update>,
+    stop: Arc<AtomicBool>,
+}
+
+impl OptimalJob {
+    /// Tells the search thread to give up; its result is never read.
+    fn cancel(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// A solution being played back on the cube one move at a time.
+struct AutoSolve {
+    moves: VecDeque<Move>,
+    /// Seconds until the next move.
+    wait: f32,
+    /// Where the last move left the cube; playback stops if it moves on.
+    expected: Cube,
+}
+
+impl AutoSolve {
+    fn new(cube: Cube, moves: Vec<Move>) -> Self {
+        Self {
+            moves: moves.into(),
+            wait: config::AUTOSOLVE_MOVE_SECONDS,
+            expected: cube,
+        }
+    }
+
+    /// The next move once `dt` more seconds have passed, if one is due.
+    fn update(&mut self, dt: f32) -> Option<Move> {
+        self.wait -= dt;
+        if self.wait > 0.0 {
+            return None;
+        }
+        self.wait = config::AUTOSOLVE_MOVE_SECONDS;
+        let mv = self.moves.pop_front()?;
+        self.expected.apply_move(mv);
+        Some(mv)
+    }
+}
+
+/// Walking through a solution with `n` and `p`, one move at a time.
+struct SolutionWalk {
+    /// Each stage's name and moves.
+    stages: Vec<(String, Vec<Move>)>,
+    /// Moves applied so far, counted across all stages.
+    position: usize,
+    /// Where the walk has left the cube; it ends if the cube moves on.
+    expected: Cube,
+}
+
+impl SolutionWalk {
+    fn len(&self) -> usize {
+        self.stages.iter().map(|(_, moves)| moves.len()).sum()
+    }
+
+    /// The `index`th move of the whole solution and the stage it is in.
+    fn step(&self, index: usize) -> Option<(&str, Move)> {
+        self.stages
+            .iter()
+            .flat_map(|(name, moves)| moves.iter().map(|&mv| (name.as_str(), mv)))
+            .nth(index)
+    }
+
+    /// The move `n` plays next, if any are left.
+    fn upcoming(&self) -> Option<(&str, Move)> {
+        self.step(self.position)
+    }
+}
+
+struct EoLineTrainer {
+    moves: usize,
+    done: bool,
+}
+
+/// Blindfold practice: memorize the Old Pochmann letters, recite them, then
+/// solve with every sticker the same color.
+struct BldTrainer {
+    memo: Memo,
+    phase: BldPhase,
+}
+
+/// What is shown next to the main view: the opposite side of the cube,
+/// or the target state to compare against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DualView {
+    Off,
+    SideBySide,
+    /// A smaller view in the top-right corner of the main one.
+    Inset,
+    /// The ghost target, or the solved cube, from the same camera, with the
+    /// stickers that differ highlighted on both.
+    Compare,
+}
+
+impl DualView {
+    fn next(self) -> Self {
+        match self {
+            DualView::Off => DualView::SideBySide,
+            DualView::SideBySide => DualView::Inset,
+            DualView::Inset => DualView::Compare,
+            DualView::Compare => DualView::Off,
+        }
+    }
+}
+
+enum BldPhase {
+    Memo,
+    /// Letters typed so far.
+    Quiz(String),
+    /// Moves made since the stickers were hidden.
+    Execute(usize),
+}
+
 pub struct App {
     cube: Cube,
+    /// Set when running a cube other than 3x3, which replaces `cube` for
+    /// twisting, scrambling and rendering.
+    big_cube: Option<BigCube>,
+    big_scramble: Vec<LayerMove>,
+    /// Size given on the command line, restored when leaving 4x4 mode.
+    start_size: usize,
     camera: Camera,
-    renderer: Renderer,
+    renderer: Box<dyn RenderBackend>,
     input: InputHandler,
     frame_writer: FrameWriter,
-    rng: ThreadRng,
+    frame_dump: Option<FrameDump>,
+    export_pending: bool,
+    turntable: Option<Turntable>,
+    turntable_seconds: f32,
+    record_format: RecordFormat,
+    /// Session RNG, seeded by `--seed` so a whole session replays.
+    rng: StdRng,
+    /// Seed the next scramble uses; each scramble gets its own so it can be
+    /// shared and replayed.
+    next_seed: u64,
+    /// Seed of the latest scramble, replayed by `Ctrl+R`.
+    scramble_seed: Option<u64>,
+    scramble_log: ScrambleLog,
+    /// Highlighted row of the recent scrambles list while it is open.
+    scramble_list: Option<usize>,
+    scramble_length: usize,
+    scramble_style: ScrambleStyle,
+    /// The last 3x3 scramble, shown above the status line until a reset.
+    last_scramble: Vec<Move>,
+    /// Index into `cube::PATTERNS` of the pattern `J` shows next.
+    next_pattern: usize,
+    history: MoveHistory,
+    move_counts: MoveCounts,
+    /// Solver output for the current state, dropped once the cube changes.
+    solution: Option<Vec<Stage>>,
+    optimal_job: Option<OptimalJob>,
+    autosolve: Option<AutoSolve>,
+    walk: Option<SolutionWalk>,
+    watch: Option<StateWatch>,
+    attempt_moves: Option<usize>,
+    leaderboard: Leaderboard,
+    show_stats: bool,
+    show_hud: bool,
+    /// Method whose stage the status line follows.
+    method: Method,
+    eoline_trainer: Option<EoLineTrainer>,
+    bld_trainer: Option<BldTrainer>,
+    show_letters: bool,
+    ghost_target: Option<Cube>,
+    show_f2l_pairs: bool,
+    starfield: Option<Starfield>,
+    dual_view: DualView,
+    /// Show the 3x3 as a flat net instead of in 3D.
+    net_view: bool,
+    /// Overlay a small net in the top-left corner of the 3D view.
+    mini_net: bool,
+    show_history: bool,
+    hover: Option<(u16, u16)>,
+    /// One-line message shown above the status line, with seconds left.
+    notice: Option<(String, f32)>,
+    shake: Option<CameraShake>,
+    celebration: Option<Celebration>,
+    explosion: Option<Explosion>,
+    frame_budget: Duration,
     running: bool,
 }
 
 impl App {
-    pub fn new(frame_writer: FrameWriter) -> Self {
-        Self {
-            cube: Cube::new(),
+    pub fn new(frame_writer: FrameWriter, options: Options) -> TermResult<Self> {
+        let frame_dump = match options.dump_dir {
+            Some(dir) => Some(FrameDump::create(dir, options.dump_format)?),
+            None => None,
+        };
+        let leaderboard_path = config::data_dir().map(|dir| dir.join(config::LEADERBOARD_FILE));
+        // An unreadable leaderboard is left alone on disk; this session keeps
+        // its solves in memory only.
+        let (leaderboard, notice) = match Leaderboard::load(leaderboard_path) {
+            Ok(board) => (
+                board,
+                options
+                    .pattern
+                    .map(|name| (format!("Pattern: {name}"), config::NOTICE_SECONDS)),
+            ),
+            Err(err) => (
+                Leaderboard::in_memory(),
+                Some((
+                    format!("Could not read the leaderboard: {err}"),
+                    config::NOTICE_SECONDS,
+                )),
+            ),
+        };
+        let mut rng = match options.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let next_seed = options
+            .seed
+            .unwrap_or_else(|| rng.gen_range(0..config::SCRAMBLE_SEEDS));
+        let mut cube = options.state.unwrap_or_else(Cube::new);
+        cube.apply_sequence(&options.scramble);
+        let big_cube = (options.size != 3).then(|| {
+            let mut big = BigCube::new(options.size);
+            for mv in &options.big_scramble {
+                big.apply(*mv);
+            }
+            big
+        });
+        // Monochrome output asks for text even where sixels would work.
+        let renderer: Box<dyn RenderBackend> =
+            if options.graphics.use_sixel() && !options.render_settings.monochrome {
+                let cell = cell_pixel_size().unwrap_or(config::SIXEL_CELL_PIXELS);
+                Box::new(SixelRenderer::new(options.render_settings, cell))
+            } else {
+                raster::with_cell_mode(
+                    raster::backend(options.renderer, options.render_settings),
+                    options.cell_mode,
+                )
+            };
+        let mut app = Self {
+            cube,
+            big_cube,
+            big_scramble: options.big_scramble,
+            start_size: options.size,
             camera: Camera::new(),
-            renderer: Renderer::new(),
-            input: InputHandler::new(),
+            renderer,
+            input: InputHandler::new(options.macros.unwrap_or_default()),
             frame_writer,
-            rng: thread_rng(),
+            frame_dump,
+            export_pending: false,
+            turntable: None,
+            turntable_seconds: options.turntable_seconds,
+            record_format: options.record_format,
+            rng,
+            next_seed,
+            scramble_seed: None,
+            scramble_log: ScrambleLog::new(),
+            scramble_list: None,
+            scramble_length: config::SCRAMBLE_LENGTH,
+            scramble_style: ScrambleStyle::Standard,
+            last_scramble: options.scramble,
+            next_pattern: 0,
+            history: MoveHistory::new(),
+            move_counts: MoveCounts::default(),
+            solution: None,
+            optimal_job: None,
+            autosolve: None,
+            walk: None,
+            watch: options.watch.map(StateWatch::new),
+            attempt_moves: None,
+            leaderboard,
+            show_stats: false,
+            show_hud: true,
+            method: options.method,
+            eoline_trainer: None,
+            bld_trainer: None,
+            show_letters: false,
+            ghost_target: None,
+            show_f2l_pairs: false,
+            starfield: None,
+            dual_view: DualView::Off,
+            net_view: false,
+            mini_net: false,
+            show_history: false,
+            hover: None,
+            notice,
+            shake: None,
+            celebration: None,
+            explosion: None,
+            frame_budget: config::frame_duration(),
             running: true,
+        };
+        if let Some(reconstruction) = options.reconstruction {
+            app.play_reconstruction(reconstruction);
         }
+        Ok(app)
     }
 
     pub fn run(&mut self) -> TermResult<()> {
         let mut viewport = current_viewport()?;
+        self.fit_camera(viewport);
+        let mut last_frame = Instant::now();
         while self.running {
             let frame_start = Instant::now();
+            let dt = frame_start.duration_since(last_frame).as_secs_f32();
+            last_frame = frame_start;
             self.process_input()?;
-            let frame = self.renderer.render(&self.cube, &self.camera, viewport);
+            self.poll_optimal();
+            self.play_autosolve(dt);
+            self.check_walk();
+            self.poll_watch();
+            if let Some(starfield) = self.starfield.as_mut() {
+                starfield.update(dt);
+            }
+            if let Some(shake) = self.shake.as_mut()
+                && !shake.update(dt)
+            {
+                self.shake = None;
+            }
+            if let Some((_, remaining)) = self.notice.as_mut() {
+                *remaining -= dt;
+                if *remaining <= 0.0 {
+                    self.notice = None;
+                }
+            }
+            if let Some(celebration) = self.celebration.as_mut()
+                && !celebration.update(dt)
+            {
+                self.celebration = None;
+            }
+            if let Some(explosion) = self.explosion.as_mut()
+                && !explosion.update(dt)
+            {
+                self.explosion = None;
+            }
+            let turntable_done = self
+                .turntable
+                .as_mut()
+                .is_some_and(|turntable| !turntable.update(dt));
+            if turntable_done && let Some(turntable) = self.turntable.take() {
+                let dir = config::TURNTABLE_DIR;
+                let message = match turntable.finish() {
+                    Ok(()) => format!("Saved {dir}"),
+                    Err(err) => format!("Could not save {dir}: {err}"),
+                };
+                self.notice = Some((message, config::NOTICE_SECONDS));
+            }
+            let mut frame = if self.show_stats {
+                self.stats_frame(viewport)
+            } else if let Some(selected) = self.scramble_list {
+                self.scramble_list_frame(viewport, selected)
+            } else {
+                let mut camera = match &self.shake {
+                    Some(shake) => shake.apply(&self.camera),
+                    None => self.camera,
+                };
+                if let Some(celebration) = &self.celebration {
+                    camera = celebration.apply(&camera);
+                }
+                if let Some(turntable) = &self.turntable {
+                    camera = turntable.apply(&camera);
+                }
+                if let Some(explosion) = &self.explosion {
+                    camera = explosion.apply(&camera);
+                }
+                let mut frame = self.render_scene(&camera, viewport);
+                self.draw_tooltip(&mut frame, &camera, viewport);
+                if let Some(starfield) = &self.starfield {
+                    starfield.draw(&mut frame);
+                }
+                if self.show_hud {
+                    self.draw_hud(&mut frame);
+                }
+                if let Some(celebration) = &self.celebration {
+                    celebration.draw(&mut frame);
+                }
+                frame
+            };
+            if let Some(background) = theme::active().background {
+                frame.fill_empty_background(background);
+            }
             self.frame_writer.blit(&frame)?;
-            viewport = current_viewport()?;
+            if let Some(dump) = self.frame_dump.as_mut() {
+                dump.write(&frame)?;
+            }
+            if let Some(turntable) = self.turntable.as_mut()
+                && let Err(err) = turntable.write(&frame)
+            {
+                // A half-written orbit isn't worth finishing.
+                self.turntable = None;
+                self.notice = Some((format!("Turntable stopped: {err}"), config::NOTICE_SECONDS));
+            }
+            if self.export_pending {
+                self.export_pending = false;
+                let stem = config::FRAME_EXPORT_STEM;
+                let message = match capture::export_frame(&frame, Path::new(stem)) {
+                    Ok(()) => format!("Saved {stem}.ans and {stem}.txt"),
+                    Err(err) => format!("Could not save {stem}: {err}"),
+                };
+                self.notice = Some((message, config::NOTICE_SECONDS));
+            }
+            let resized = current_viewport()?;
+            if resized != viewport {
+                self.fit_camera(resized);
+                viewport = resized;
+            }
             self.cap_frame_rate(frame_start);
         }
+        self.frame_writer.stop_recording()?;
         Ok(())
     }
 
     fn process_input(&mut self) -> TermResult<()> {
         let actions = self.input.poll_actions()?;
         for action in actions {
-            self.dispatch(action);
+            self.dispatch(action)?;
         }
         Ok(())
     }
 
-    fn dispatch(&mut self, action: Action) {
+    fn dispatch(&mut self, action: Action) -> TermResult<()> {
         match action {
             Action::RotateCamera { d_theta, d_phi } => {
                 self.camera.orbit(d_theta, d_phi);
             }
             Action::RollCamera(delta) => self.camera.roll(delta),
-            Action::ZoomCamera(delta) => self.camera.zoom(delta),
-            Action::TwistFace(mv) => self.cube.apply_move(mv),
-            Action::Scramble => self.cube.scramble(config::SCRAMBLE_LENGTH, &mut self.rng),
-            Action::Reset => self.cube.reset(),
+            Action::RotateLight {
+                d_azimuth,
+                d_elevation,
+            } => self.camera.rotate_light(d_azimuth, d_elevation),
+            Action::ZoomCamera(delta) => {
+                if !self.camera.zoom(delta) {
+                    self.start_shake(config::SHAKE_BUMP_STRENGTH);
+                }
+            }
+            Action::Hover { x, y } => self.hover = Some((x, y)),
+            Action::ToggleFourByFour => {
+                let size = toggled_size(self.big_cube.as_ref().map(BigCube::size), self.start_size);
+                self.big_cube = (size != 3).then(|| BigCube::new(size));
+                self.big_scramble.clear();
+                self.stop_bld();
+                self.history.clear();
+                self.move_counts = MoveCounts::default();
+                self.solution = None;
+            }
+            Action::FitCamera => self.fit_camera(current_viewport()?),
+            Action::ToggleDualView => {
+                self.dual_view = self.dual_view.next();
+                self.fit_camera(current_viewport()?);
+            }
+            Action::ToggleNetView => self.net_view = !self.net_view,
+            Action::ToggleMiniNet => self.mini_net = !self.mini_net,
+            Action::ToggleExplode => match self.explosion.as_mut() {
+                Some(explosion) => explosion.toggle(),
+                None => self.explosion = Some(Explosion::new()),
+            },
+            Action::ToggleHistory => {
+                self.show_history = !self.show_history;
+                self.fit_camera(current_viewport()?);
+            }
+            Action::TwistFace(mv) => {
+                self.history.push(mv);
+                self.twist(mv);
+            }
+            Action::ApplySequence(moves) => {
+                for mv in cube::simplify(&moves) {
+                    self.history.push(mv);
+                    self.twist(mv);
+                }
+            }
+            Action::Undo => {
+                if let Some(mv) = self.history.undo() {
+                    self.twist(mv);
+                }
+            }
+            Action::Redo => {
+                if let Some(mv) = self.history.redo() {
+                    self.twist(mv);
+                }
+            }
+            Action::Scramble => self.scramble_active(),
+            Action::RepeatScramble => {
+                let Some(seed) = self.scramble_seed else {
+                    return Ok(());
+                };
+                match self.big_cube.as_mut() {
+                    Some(big) => big.reset(),
+                    None => self.cube.reset(),
+                }
+                self.next_seed = seed;
+                self.scramble_active();
+            }
+            Action::ToggleScrambleList => {
+                self.scramble_list = match self.scramble_list {
+                    Some(_) => None,
+                    None => Some(0),
+                };
+                self.input.set_list_open(self.scramble_list.is_some());
+            }
+            Action::ListUp => {
+                if let Some(selected) = self.scramble_list.as_mut() {
+                    *selected = selected.saturating_sub(1);
+                }
+            }
+            Action::ListDown => {
+                if let Some(selected) = self.scramble_list.as_mut() {
+                    *selected = (*selected + 1).min(self.scramble_log.len().saturating_sub(1));
+                }
+            }
+            Action::ListSelect => {
+                let entry = self
+                    .scramble_list
+                    .take()
+                    .and_then(|selected| self.scramble_log.get(selected))
+                    .cloned();
+                self.input.set_list_open(false);
+                if let Some(entry) = entry {
+                    self.reapply_scramble(entry.moves, entry.seed);
+                }
+            }
+            Action::AdjustScrambleLength(delta) => {
+                self.scramble_length = self
+                    .scramble_length
+                    .saturating_add_signed(delta)
+                    .clamp(config::SCRAMBLE_MIN_LENGTH, config::SCRAMBLE_MAX_LENGTH);
+            }
+            Action::CycleScrambleStyle => self.scramble_style = self.scramble_style.next(),
+            Action::TrainEoLine if self.big_cube.is_some() => {}
+            Action::TrainEoLine => {
+                self.history.clear();
+                self.move_counts = MoveCounts::default();
+                self.cube.reset();
+                self.scramble();
+                self.attempt_moves = None;
+                self.eoline_trainer = Some(EoLineTrainer {
+                    moves: 0,
+                    done: false,
+                });
+            }
+            Action::TrainBld if self.big_cube.is_some() => {}
+            Action::TrainBld => match self.bld_trainer.as_mut() {
+                None => {
+                    self.history.clear();
+                    self.move_counts = MoveCounts::default();
+                    self.cube.reset();
+                    self.scramble();
+                    self.attempt_moves = None;
+                    self.eoline_trainer = None;
+                    self.bld_trainer = Some(BldTrainer {
+                        memo: bld::memo(&self.cube),
+                        phase: BldPhase::Memo,
+                    });
+                }
+                Some(trainer) if matches!(trainer.phase, BldPhase::Memo) => {
+                    trainer.phase = BldPhase::Quiz(String::new());
+                    self.input.set_text_entry(true);
+                }
+                Some(_) => self.stop_bld(),
+            },
+            Action::TypeChar(ch) => {
+                if let Some(BldTrainer {
+                    phase: BldPhase::Quiz(typed),
+                    ..
+                }) = self.bld_trainer.as_mut()
+                    && ch.is_alphabetic()
+                {
+                    typed.push(ch);
+                }
+            }
+            Action::TypeBackspace => {
+                if let Some(BldTrainer {
+                    phase: BldPhase::Quiz(typed),
+                    ..
+                }) = self.bld_trainer.as_mut()
+                {
+                    typed.pop();
+                }
+            }
+            Action::TypeSubmit => {
+                let Some(trainer) = self.bld_trainer.as_mut() else {
+                    return Ok(());
+                };
+                let BldPhase::Quiz(typed) = &trainer.phase else {
+                    return Ok(());
+                };
+                let message = if typed.eq_ignore_ascii_case(&trainer.memo.letters()) {
+                    "Memo correct, now solve it blind".to_string()
+                } else {
+                    format!("Memo was {}", trainer.memo.describe())
+                };
+                trainer.phase = BldPhase::Execute(0);
+                self.input.set_text_entry(false);
+                self.notice = Some((message, config::NOTICE_SECONDS));
+            }
+            Action::ExportScrambleCard => {
+                let path = config::SCRAMBLE_CARD_PATH;
+                let message =
+                    match export::write_scramble_card(Path::new(path), &self.last_scramble) {
+                        Ok(()) => format!("Saved {path}"),
+                        Err(err) => format!("Could not save {path}: {err}"),
+                    };
+                self.notice = Some((message, config::NOTICE_SECONDS));
+            }
+            Action::ExportFrame => self.export_pending = true,
+            Action::ExportCubeSvg => {
+                let colors = match &self.big_cube {
+                    Some(big) => big.face_colors(),
+                    None => self.cube.face_colors(),
+                };
+                let camera = match &self.explosion {
+                    Some(explosion) => explosion.apply(&self.camera),
+                    None => self.camera,
+                };
+                let path = config::CUBE_SVG_PATH;
+                let message = match export::write_cube_svg(Path::new(path), colors, &camera) {
+                    Ok(()) => format!("Saved {path}"),
+                    Err(err) => format!("Could not save {path}: {err}"),
+                };
+                self.notice = Some((message, config::NOTICE_SECONDS));
+            }
+            Action::ExportCubeExplorer if self.big_cube.is_some() => {}
+            Action::ExportCubeExplorer => {
+                let path = config::CUBE_EXPLORER_PATH;
+                let message = match cube_explorer::write(Path::new(path), &self.cube) {
+                    Ok(()) => format!("Saved {path}"),
+                    Err(err) => format!("Could not save {path}: {err}"),
+                };
+                self.notice = Some((message, config::NOTICE_SECONDS));
+            }
+            Action::NormalizeOrientation if self.big_cube.is_some() => {}
+            Action::NormalizeOrientation => {
+                // The history's twists were made in the old orientation.
+                if self.cube.normalize_orientation() {
+                    self.history.clear();
+                    self.solution = None;
+                }
+            }
+            Action::Solve | Action::SolveBeginner if self.big_cube.is_some() => {}
+            Action::Solve => {
+                let solution = solver::solve(&self.cube, config::SOLVER_MAX_MOVES).map(|moves| {
+                    vec![Stage {
+                        name: "Solution",
+                        moves,
+                    }]
+                });
+                self.show_solution(solution);
+            }
+            Action::SolveBeginner => self.show_solution(beginner::solve(&self.cube)),
+            Action::SolveCrosses if self.big_cube.is_some() => {}
+            Action::SolveCrosses => self.show_solution(beginner::solve_crosses(&self.cube)),
+            Action::SolveOptimal if self.big_cube.is_some() => {}
+            Action::SolveOptimal => match self.optimal_job.take() {
+                Some(job) => {
+                    job.cancel();
+                    let text = "Optimal solve cancelled".to_string();
+                    self.notice = Some((text, config::NOTICE_SECONDS));
+                }
+                None => {
+                    let (updates, stop) = optimal::spawn(self.cube);
+                    self.optimal_job = Some(OptimalJob {
+                        start: self.cube,
+                        status: "starting".to_string(),
+                        updates,
+                        stop,
+                    });
+                }
+            },
+            Action::AutoSolve if self.big_cube.is_some() => {}
+            Action::AutoSolve if self.autosolve.is_some() => {
+                self.autosolve = None;
+                self.notice = Some(("Auto-solve stopped".to_string(), config::NOTICE_SECONDS));
+            }
+            Action::AutoSolve if self.cube.is_solved() => {}
+            Action::AutoSolve => match solver::solve(&self.cube, config::SOLVER_MAX_MOVES) {
+                Ok(moves) => {
+                    let moves = cube::simplify(&moves);
+                    // Giving up ends the attempt without a leaderboard entry.
+                    self.attempt_moves = None;
+                    self.notice = Some((
+                        format!("Auto-solving in {} moves", moves.len()),
+                        config::NOTICE_SECONDS,
+                    ));
+                    self.autosolve = Some(AutoSolve::new(self.cube, moves));
+                }
+                Err(err) => {
+                    self.notice = Some((format!("Cannot solve: {err}"), config::NOTICE_SECONDS));
+                }
+            },
+            Action::StepSolution if self.walk.is_some() => self.stop_walk(),
+            Action::StepSolution => match self.solution.take() {
+                Some(stages) if stages.iter().any(|stage| !stage.moves.is_empty()) => {
+                    self.walk = Some(SolutionWalk {
+                        stages: stages
+                            .into_iter()
+                            .map(|stage| (stage.name.to_string(), stage.moves))
+                            .collect(),
+                        position: 0,
+                        expected: self.cube,
+                    });
+                    self.input.set_stepping(true);
+                }
+                solution => {
+                    self.solution = solution;
+                    let text = "Nothing to step through: solve first (Ctrl+S, Ctrl+B or Ctrl+P)";
+                    self.notice = Some((text.to_string(), config::NOTICE_SECONDS));
+                }
+            },
+            Action::StepNext => {
+                let Some(walk) = self.walk.as_mut() else {
+                    return Ok(());
+                };
+                if let Some((_, mv)) = walk.upcoming() {
+                    walk.position += 1;
+                    walk.expected.apply_move(mv);
+                    self.history.push(mv);
+                    self.twist(mv);
+                }
+            }
+            Action::StepBack => {
+                let Some(walk) = self.walk.as_mut() else {
+                    return Ok(());
+                };
+                if let Some(position) = walk.position.checked_sub(1)
+                    && let Some((_, mv)) = walk.step(position)
+                {
+                    walk.position = position;
+                    walk.expected.apply_move(mv.inverse());
+                    self.history.undo();
+                    self.twist(mv.inverse());
+                }
+            }
+            Action::PasteClipboard => match self.frame_writer.read_clipboard()? {
+                Some(text) => self.paste(&text),
+                None => {
+                    let text = "The clipboard is empty or can't be read".to_string();
+                    self.notice = Some((text, config::NOTICE_SECONDS));
+                }
+            },
+            Action::CopyFaceletString if self.big_cube.is_some() => {}
+            Action::CopyFaceletString => {
+                let facelets = self.cube.to_facelet_string();
+                self.frame_writer.copy_to_clipboard(&facelets)?;
+                self.notice = Some((format!("Copied: {facelets}"), config::NOTICE_SECONDS));
+            }
+            Action::NextPattern if self.big_cube.is_some() => {}
+            Action::NextPattern => {
+                let (name, _) = cube::PATTERNS[self.next_pattern];
+                self.next_pattern = (self.next_pattern + 1) % cube::PATTERNS.len();
+                self.history.clear();
+                self.move_counts = MoveCounts::default();
+                self.solution = None;
+                self.attempt_moves = None;
+                self.eoline_trainer = None;
+                self.stop_bld();
+                self.last_scramble.clear();
+                self.cube.reset();
+                self.cube
+                    .apply_sequence(&cube::pattern(name).expect("listed in PATTERNS"));
+                self.notice = Some((format!("Pattern: {name}"), config::NOTICE_SECONDS));
+            }
+            Action::CaptureTurntable => {
+                if self.turntable.is_none() {
+                    let dir = config::TURNTABLE_DIR;
+                    match Turntable::create(PathBuf::from(dir), self.turntable_seconds) {
+                        Ok(turntable) => self.turntable = Some(turntable),
+                        Err(err) => {
+                            self.notice = Some((
+                                format!("Could not create {dir}: {err}"),
+                                config::NOTICE_SECONDS,
+                            ));
+                        }
+                    }
+                }
+            }
+            Action::ToggleRecording => {
+                let message = match self.frame_writer.stop_recording() {
+                    Ok(Some(path)) => format!("Saved {}", path.display()),
+                    Err(err) => format!("Could not save the recording: {err}"),
+                    Ok(None) => match Recorder::create(
+                        Path::new(config::RECORDING_STEM),
+                        self.record_format,
+                    ) {
+                        Ok(recorder) => {
+                            let message = format!("Recording to {}", recorder.path().display());
+                            self.frame_writer.start_recording(recorder);
+                            message
+                        }
+                        Err(err) => format!("Could not start recording: {err}"),
+                    },
+                };
+                self.notice = Some((message, config::NOTICE_SECONDS));
+            }
+            Action::ToggleStats => self.show_stats = !self.show_stats,
+            Action::ToggleHud => self.show_hud = !self.show_hud,
+            Action::ToggleGhost => {
+                self.ghost_target = match self.ghost_target {
+                    Some(_) => None,
+                    None => Some(Cube::new()),
+                };
+                self.renderer.set_ghost(self.ghost_target);
+            }
+            Action::ToggleF2lPairs => self.show_f2l_pairs = !self.show_f2l_pairs,
+            Action::CycleShading => {
+                let settings = self.renderer.settings_mut();
+                settings.shading = settings.shading.next();
+            }
+            Action::ToggleBorders => {
+                let settings = self.renderer.settings_mut();
+                settings.borders = !settings.borders;
+            }
+            Action::CycleMethod => {
+                self.method = self.method.next();
+                let text = format!("Stages: {}", self.method.label());
+                self.notice = Some((text, config::NOTICE_SECONDS));
+            }
+            Action::ToggleWireframe => {
+                let settings = self.renderer.settings_mut();
+                settings.wireframe = !settings.wireframe;
+            }
+            Action::ToggleGround => {
+                let settings = self.renderer.settings_mut();
+                settings.ground = !settings.ground;
+            }
+            Action::ToggleStarfield => {
+                self.starfield = match self.starfield {
+                    Some(_) => None,
+                    None => Some(Starfield::new()),
+                };
+            }
+            Action::ToggleLetters => {
+                self.show_letters = !self.show_letters;
+                let labels: Option<FaceletLabel> = if self.show_letters {
+                    Some(bld::letter)
+                } else {
+                    None
+                };
+                self.renderer.set_labels(labels);
+            }
+            Action::Reset => {
+                if let Some(big) = self.big_cube.as_mut() {
+                    big.reset();
+                }
+                self.big_scramble.clear();
+                self.last_scramble.clear();
+                self.history.clear();
+                self.move_counts = MoveCounts::default();
+                self.solution = None;
+                self.cube.reset();
+                self.attempt_moves = None;
+                self.eoline_trainer = None;
+                self.stop_bld();
+            }
             Action::Quit => self.running = false,
         }
+        Ok(())
+    }
+
+    /// Renders the cube, with the move history column on the right when
+    /// it is shown.
+    fn render_scene(&mut self, camera: &Camera, viewport: Viewport) -> Frame {
+        let scene = self.scene_viewport(viewport);
+        let cube = self.render_cube(camera, scene);
+        if !self.show_history {
+            return cube;
+        }
+        let mut frame = Frame::blank(viewport);
+        frame.paste(&cube, 0, 0);
+        frame.paste(
+            &self.history_panel(viewport.height),
+            scene.width as usize,
+            0,
+        );
+        frame
     }
 
-    fn cap_frame_rate(&self, frame_start: Instant) {
-        let frame_time = config::frame_duration();
-        if let Some(remaining) = frame_time.checked_sub(frame_start.elapsed()) {
+    /// Renders the cube from `camera`, or in dual view from `camera` on the
+    /// left and the opposite side on the right.
+    fn render_cube(&mut self, camera: &Camera, viewport: Viewport) -> Frame {
+        let hidden = [config::BLD_HIDDEN_COLOR; FACELET_COUNT];
+        let colors = match &self.big_cube {
+            Some(big) => big.face_colors(),
+            None if self.stickers_hidden() => &hidden,
+            None => self.cube.face_colors(),
+        };
+        // The layer the solution walk turns next outranks the F2L pairs.
+        let highlight = match self.walk.as_ref().and_then(SolutionWalk::upcoming) {
+            Some((_, mv)) => mv.facelets(),
+            None if self.show_f2l_pairs && self.big_cube.is_none() => {
+                stages::connected_f2l_pairs(&self.cube)
+            }
+            None => Vec::new(),
+        };
+        if self.net_view && self.big_cube.is_none() {
+            let labels: Option<FaceletLabel> = self.show_letters.then_some(bld::letter);
+            let settings = *self.renderer.settings_mut();
+            return raster::render_net(colors, &settings, labels, &highlight, viewport);
+        }
+        self.renderer.set_highlight(highlight);
+        let mut frame = match self.dual_view {
+            DualView::Compare if self.big_cube.is_none() => {
+                let target = self.ghost_target.unwrap_or_else(Cube::new);
+                let (left, right) = split_viewport(viewport);
+                self.renderer
+                    .set_highlight(self.cube.differing_facelets(&target));
+                let mut frame = Frame::blank(viewport);
+                let current = self.renderer.render(colors, camera, left);
+                let wanted = self.renderer.render(target.face_colors(), camera, right);
+                frame.paste(&current, 0, 0);
+                frame.paste(&wanted, left.width as usize, 0);
+                frame
+            }
+            DualView::Off | DualView::Compare => self.renderer.render(colors, camera, viewport),
+            DualView::SideBySide => {
+                let (left, right) = split_viewport(viewport);
+                let mut frame = Frame::blank(viewport);
+                let front = self.renderer.render(colors, camera, left);
+                let back = self.renderer.render(colors, &camera.opposite(), right);
+                frame.paste(&front, 0, 0);
+                frame.paste(&back, left.width as usize, 0);
+                frame
+            }
+            DualView::Inset => {
+                let mut frame = self.renderer.render(colors, camera, viewport);
+                let (x, inset) = inset_viewport(viewport);
+                let back = self
+                    .renderer
+                    .render(colors, &inset_camera(camera, inset), inset);
+                frame.paste(&back, x as usize, 0);
+                draw_inset_border(&mut frame, x as usize, inset);
+                frame
+            }
+        };
+        if self.mini_net && self.big_cube.is_none() {
+            let settings = *self.renderer.settings_mut();
+            let net = raster::render_mini_net(colors, &settings);
+            // A blank margin keeps the net clear of the cube behind it.
+            let mut backing = Frame::blank(Viewport {
+                width: net.width() as u16 + 1,
+                height: net.height() as u16 + 1,
+            });
+            backing.paste(&net, 0, 0);
+            frame.paste(&backing, 0, 0);
+        }
+        frame
+    }
+
+    /// Names the piece under the mouse pointer next to the pointer.
+    fn draw_tooltip(&self, frame: &mut Frame, camera: &Camera, viewport: Viewport) {
+        let viewport = self.scene_viewport(viewport);
+        let Some((pointer_x, pointer_y)) = self
+            .hover
+            .filter(|_| self.big_cube.is_none() && !self.stickers_hidden())
+        else {
+            return;
+        };
+        let colors = self.cube.face_colors();
+        let picked = if self.net_view {
+            NetLayout::fit(viewport).facelet_at(pointer_x as usize, pointer_y as usize)
+        } else {
+            self.pick_3d(camera, viewport, pointer_x, pointer_y)
+        };
+        let Some(facelet) = picked else {
+            return;
+        };
+        let Some(piece) = cube::piece_name(facelet) else {
+            return;
+        };
+        let color = colors[facelet];
+        let mut text = format!(" {piece}, {} sticker ", color.name());
+        if let Some(letter) = bld::letter(facelet) {
+            text.push_str(&format!("({letter}) "));
+        }
+        let width = text.chars().count();
+        let column = (pointer_x as usize + 2).min(frame.width().saturating_sub(width));
+        let row = (pointer_y as usize).saturating_sub(1);
+        let theme = theme::active();
+        frame.put_str(column, row, &text, Some(theme.highlight_text));
+        frame.fill_background(column, row, width, theme.highlight);
+    }
+
+    /// The facelet of the 3D view under cell (`x`, `y`), looking through
+    /// whichever half of the dual view the cell is in.
+    fn pick_3d(&self, camera: &Camera, viewport: Viewport, x: u16, y: u16) -> Option<usize> {
+        let (camera, viewport, x) = match self.dual_view {
+            DualView::Off => (*camera, viewport, x),
+            DualView::SideBySide => {
+                let (left, right) = split_viewport(viewport);
+                match x.checked_sub(left.width) {
+                    Some(right_x) => (camera.opposite(), right, right_x),
+                    None => (*camera, left, x),
+                }
+            }
+            DualView::Inset => {
+                let (left, inset) = inset_viewport(viewport);
+                match x.checked_sub(left) {
+                    Some(inset_x) if y < inset.height => {
+                        (inset_camera(camera, inset), inset, inset_x)
+                    }
+                    _ => (*camera, viewport, x),
+                }
+            }
+            // The right half shows the target, not this cube.
+            DualView::Compare => {
+                let (left, _) = split_viewport(viewport);
+                if x >= left.width {
+                    return None;
+                }
+                (*camera, left, x)
+            }
+        };
+        geometry::pick_facelet(self.cube.face_colors(), &camera, viewport, x, y)
+    }
+
+    fn fit_camera(&mut self, viewport: Viewport) {
+        let viewport = self.scene_viewport(viewport);
+        let viewport = match self.dual_view {
+            DualView::SideBySide => split_viewport(viewport).0,
+            DualView::Compare if self.big_cube.is_none() => split_viewport(viewport).0,
+            DualView::Off | DualView::Inset | DualView::Compare => viewport,
+        };
+        self.camera.fit_to(viewport);
+    }
+
+    /// The part of the terminal the cube gets; the history column takes the
+    /// right edge when shown.
+    fn scene_viewport(&self, viewport: Viewport) -> Viewport {
+        if !self.show_history {
+            return viewport;
+        }
+        Viewport {
+            width: viewport.width.saturating_sub(config::HISTORY_PANEL_WIDTH),
+            height: viewport.height,
+        }
+    }
+
+    /// The history column: a header, then the twists since the scramble,
+    /// numbered and scrolled so the newest stays above the status lines.
+    fn history_panel(&self, height: u16) -> Frame {
+        let mut panel = Frame::blank(Viewport {
+            width: config::HISTORY_PANEL_WIDTH,
+            height,
+        });
+        let color = theme::active().hud;
+        for row in 0..panel.height() {
+            panel.put_str(0, row, "│", color);
+        }
+        panel.put_str(2, 0, "History", color);
+        let reserved = if self.show_hud { 3 } else { 1 };
+        let rows = panel.height().saturating_sub(reserved);
+        let moves = self.history.moves();
+        let first = moves.len().saturating_sub(rows);
+        for (row, (index, mv)) in moves.iter().enumerate().skip(first).enumerate() {
+            panel.put_str(2, row + 1, &format!("{:>3}. {mv}", index + 1), color);
+        }
+        panel
+    }
+
+    /// Picks up progress from a running optimal solve and shows its answer,
+    /// or stops the search once the cube has left the state it was asked
+    /// about.
+    fn poll_optimal(&mut self) {
+        let Some(job) = self.optimal_job.as_mut() else {
+            return;
+        };
+        if job.start != self.cube {
+            job.cancel();
+            self.optimal_job = None;
+            let text = "Optimal solve cancelled: the cube changed".to_string();
+            self.notice = Some((text, config::NOTICE_SECONDS));
+            return;
+        }
+        loop {
+            match job.updates.try_recv() {
+                Ok(optimal::Update::BuildingTables) => {
+                    job.status = "building tables (first solve only)".to_string();
+                }
+                Ok(optimal::Update::Searching(length)) => {
+                    job.status = format!("trying {length} moves");
+                }
+                Ok(optimal::Update::Finished(result)) => {
+                    self.optimal_job = None;
+                    self.show_solution(result.map(|moves| {
+                        vec![Stage {
+                            name: "Optimal",
+                            moves,
+                        }]
+                    }));
+                    return;
+                }
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    self.optimal_job = None;
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Plays the next auto-solve move when it is due, as an undoable
+    /// twist, and stops early if anything else has turned the cube.
+    fn play_autosolve(&mut self, dt: f32) {
+        let Some(autosolve) = self.autosolve.as_mut() else {
+            return;
+        };
+        if autosolve.expected != self.cube || self.big_cube.is_some() {
+            self.autosolve = None;
+            let text = "Auto-solve stopped: the cube changed".to_string();
+            self.notice = Some((text, config::NOTICE_SECONDS));
+            return;
+        }
+        if let Some(mv) = autosolve.update(dt) {
+            self.history.push(mv);
+            self.twist(mv);
+        }
+        if self
+            .autosolve
+            .as_ref()
+            .is_some_and(|autosolve| autosolve.moves.is_empty())
+        {
+            self.autosolve = None;
+        }
+    }
+
+    /// Shows the watched file's state whenever it changes, or why it
+    /// can't be shown. Waits while a bigger cube is up.
+    fn poll_watch(&mut self) {
+        if self.big_cube.is_some() {
+            return;
+        }
+        match self.watch.as_mut().and_then(StateWatch::poll) {
+            Some(Ok(cube)) if cube != self.cube => {
+                self.last_scramble.clear();
+                self.history.clear();
+                self.move_counts = MoveCounts::default();
+                self.solution = None;
+                self.attempt_moves = None;
+                self.eoline_trainer = None;
+                self.stop_bld();
+                self.cube = cube;
+            }
+            Some(Ok(_)) | None => {}
+            Some(Err(err)) => {
+                self.notice = Some((format!("--watch: {err}"), config::NOTICE_SECONDS));
+            }
+        }
+    }
+
+    /// Ends the solution walk once anything but `n` or `p` has turned the
+    /// cube.
+    fn check_walk(&mut self) {
+        let Some(walk) = &self.walk else {
+            return;
+        };
+        if walk.expected != self.cube || self.big_cube.is_some() {
+            self.stop_walk();
+            let text = "Stopped stepping: the cube changed".to_string();
+            self.notice = Some((text, config::NOTICE_SECONDS));
+        }
+    }
+
+    /// Starts over from the reconstruction's scramble, stepping through
+    /// its solution.
+    fn play_reconstruction(&mut self, reconstruction: Reconstruction) {
+        self.big_cube = None;
+        self.big_scramble.clear();
+        self.history.clear();
+        self.move_counts = MoveCounts::default();
+        self.solution = None;
+        self.autosolve = None;
+        self.eoline_trainer = None;
+        self.stop_bld();
+        self.cube.reset();
+        self.cube.apply_sequence(&reconstruction.scramble);
+        self.last_scramble = reconstruction.scramble;
+        self.walk = Some(SolutionWalk {
+            stages: reconstruction.stages,
+            position: 0,
+            expected: self.cube,
+        });
+        self.input.set_stepping(true);
+    }
+
+    /// Applies the clipboard: a reconstruction when it runs over several
+    /// lines, or else moves to play on the cube.
+    fn paste(&mut self, text: &str) {
+        let lines: Vec<&str> = text
+            .lines()
+            .map(reconstruction::code)
+            .filter(|code| !code.trim().is_empty())
+            .collect();
+        let result = if lines.len() > 1 {
+            reconstruction::parse(text).map(|reconstruction| {
+                let moves = reconstruction
+                    .stages
+                    .iter()
+                    .map(|(_, moves)| moves.len())
+                    .sum::<usize>();
+                self.play_reconstruction(reconstruction);
+                format!("Pasted a reconstruction: {moves} moves, n to step")
+            })
+        } else {
+            reconstruction::parse_moves(lines.first().copied().unwrap_or_default())
+                .map(|moves| {
+                    let moves = cube::simplify(&moves);
+                    for &mv in &moves {
+                        self.history.push(mv);
+                        self.twist(mv);
+                    }
+                    format!("Pasted {} moves", moves.len())
+                })
+                .map_err(|err| err.to_string())
+        };
+        let text = result.unwrap_or_else(|err| format!("Clipboard: {err}"));
+        self.notice = Some((text, config::NOTICE_SECONDS));
+    }
+
+    fn stop_walk(&mut self) {
+        self.walk = None;
+        self.input.set_stepping(false);
+    }
+
+    fn show_solution(&mut self, solution: Result<Vec<Stage>, String>) {
+        match solution {
+            Ok(mut stages) => {
+                for stage in &mut stages {
+                    stage.moves = cube::simplify(&stage.moves);
+                }
+                self.solution = Some(stages);
+                // The leaderboard only counts solves made without the solver.
+                self.attempt_moves = None;
+            }
+            Err(err) => {
+                self.notice = Some((format!("Cannot solve: {err}"), config::NOTICE_SECONDS));
+            }
+        }
+    }
+
+    /// Applies a twist to whichever cube is active, counting it towards the
+    /// current attempt and trainer.
+    fn twist(&mut self, mv: Move) {
+        self.solution = None;
+        if let Some(big) = self.big_cube.as_mut() {
+            big.apply_move(mv);
+            return;
+        }
+        self.cube.apply_move(mv);
+        self.move_counts.add(mv);
+        self.track_attempt();
+        self.track_eoline();
+        self.track_bld();
+    }
+
+    /// Scrambles whichever cube is active, starting an attempt when the
+    /// 3x3 was solved.
+    fn scramble_active(&mut self) {
+        self.history.clear();
+        self.move_counts = MoveCounts::default();
+        if self.big_cube.is_some() {
+            let mut rng = self.scramble_rng();
+            if let Some(big) = self.big_cube.as_mut() {
+                self.big_scramble = big.scramble(self.scramble_length, &mut rng);
+            }
+            self.start_shake(config::SHAKE_SCRAMBLE_STRENGTH);
+            return;
+        }
+        let from_solved = self.cube.is_solved();
+        self.scramble();
+        self.start_shake(config::SHAKE_SCRAMBLE_STRENGTH);
+        self.attempt_moves = from_solved.then_some(0);
+        self.eoline_trainer = None;
+        self.stop_bld();
+    }
+
+    fn scramble(&mut self) {
+        self.solution = None;
+        let seed = self.next_seed;
+        let mut rng = self.scramble_rng();
+        self.last_scramble =
+            self.cube
+                .scramble_with(self.scramble_style, self.scramble_length, &mut rng);
+        self.scramble_log.push(self.last_scramble.clone(), seed);
+    }
+
+    /// Resets to solved and applies a scramble from the log, starting a
+    /// fresh attempt at it. Leaves 4x4 mode, as logged scrambles are 3x3.
+    fn reapply_scramble(&mut self, moves: Vec<Move>, seed: u64) {
+        self.big_cube = None;
+        self.big_scramble.clear();
+        self.history.clear();
+        self.move_counts = MoveCounts::default();
+        self.solution = None;
+        self.eoline_trainer = None;
+        self.stop_bld();
+        self.cube.reset();
+        self.cube.apply_sequence(&moves);
+        self.last_scramble = moves;
+        self.scramble_seed = Some(seed);
+        self.attempt_moves = Some(0);
+        self.start_shake(config::SHAKE_SCRAMBLE_STRENGTH);
+    }
+
+    /// An RNG for one scramble, seeded from `next_seed`, which then moves
+    /// on to a fresh seed from the session RNG.
+    fn scramble_rng(&mut self) -> StdRng {
+        let seed = self.next_seed;
+        self.scramble_seed = Some(seed);
+        self.next_seed = self.rng.gen_range(0..config::SCRAMBLE_SEEDS);
+        StdRng::seed_from_u64(seed)
+    }
+
+    fn start_shake(&mut self, strength: f32) {
+        if config::CAMERA_SHAKE {
+            self.shake = Some(CameraShake::new(strength));
+        }
+    }
+
+    /// Counts moves made since a scramble from solved and records the
+    /// attempt on the leaderboard once the cube is solved again.
+    fn track_attempt(&mut self) {
+        let Some(moves) = self.attempt_moves.as_mut() else {
+            return;
+        };
+        *moves += 1;
+        if self.cube.is_solved() {
+            let scramble = cube::format_sequence(&self.last_scramble);
+            // The solve stays on the board in memory if it can't be saved.
+            if let Err(err) = self.leaderboard.record(&scramble, *moves) {
+                let text = format!("Could not save the leaderboard: {err}");
+                self.notice = Some((text, config::NOTICE_SECONDS));
+            }
+            if config::CELEBRATION {
+                self.celebration = Some(Celebration::new(*moves, &mut self.rng));
+            }
+            self.attempt_moves = None;
+        }
+    }
+
+    fn track_eoline(&mut self) {
+        let Some(trainer) = self.eoline_trainer.as_mut() else {
+            return;
+        };
+        if trainer.done {
+            return;
+        }
+        trainer.moves += 1;
+        trainer.done = stages::eoline_solved(&self.cube);
+    }
+
+    fn track_bld(&mut self) {
+        let Some(trainer) = self.bld_trainer.as_mut() else {
+            return;
+        };
+        match &mut trainer.phase {
+            // Turning while memorizing changes what there is to memorize.
+            BldPhase::Memo => trainer.memo = bld::memo(&self.cube),
+            BldPhase::Quiz(_) => {}
+            BldPhase::Execute(moves) => {
+                *moves += 1;
+                if self.cube.is_solved() {
+                    let message = format!("Solved blind in {moves} moves");
+                    self.notice = Some((message, config::NOTICE_SECONDS));
+                    self.bld_trainer = None;
+                }
+            }
+        }
+    }
+
+    fn stop_bld(&mut self) {
+        self.bld_trainer = None;
+        self.input.set_text_entry(false);
+    }
+
+    /// Whether the BLD trainer has hidden the stickers.
+    fn stickers_hidden(&self) -> bool {
+        self.bld_trainer
+            .as_ref()
+            .is_some_and(|trainer| !matches!(trainer.phase, BldPhase::Memo))
+    }
+
+    fn draw_hud(&self, frame: &mut Frame) {
+        let Some(bottom) = frame.height().checked_sub(1) else {
+            return;
+        };
+        let status = if let Some(big) = &self.big_cube {
+            let n = big.size();
+            let state = if big.is_solved() {
+                "solved"
+            } else {
+                "scrambled"
+            };
+            let oll = if big.has_oll_parity() {
+                ", OLL parity"
+            } else {
+                ""
+            };
+            let pll = if big.has_pll_parity() {
+                ", PLL parity"
+            } else {
+                ""
+            };
+            format!("{n}x{n}x{n}: {state}{oll}{pll}")
+        } else {
+            match &self.eoline_trainer {
+                Some(trainer) if trainer.done => {
+                    format!("EOLine done in {} moves", trainer.moves)
+                }
+                Some(trainer) => format!(
+                    "EOLine trainer: {} bad edges, {} moves",
+                    stages::bad_edge_count(&self.cube),
+                    trainer.moves
+                ),
+                None => {
+                    let stage = match self.method {
+                        Method::Roux => stages::roux_stage(&self.cube).label().to_string(),
+                        // Once F2L is done, name the last-layer case instead.
+                        Method::Cfop => match last_layer::case_name(&self.cube) {
+                            Some(case) => case.to_string(),
+                            None => stages::cfop_stage(&self.cube).label(),
+                        },
+                    };
+                    format!("{}: {stage}", self.method.label())
+                }
+            }
+        };
+        let mut segments = vec![status];
+        if let Some(trainer) = &self.bld_trainer {
+            segments.push(match &trainer.phase {
+                BldPhase::Memo => format!(
+                    "BLD memo: {}; Ctrl+T when memorized",
+                    trainer.memo.describe()
+                ),
+                BldPhase::Quiz(typed) => {
+                    format!("BLD quiz: type the memo, Enter to check: {typed}_")
+                }
+                BldPhase::Execute(moves) => {
+                    format!("BLD: solving blind, {moves} moves; Ctrl+T to give up")
+                }
+            });
+        }
+        if let Some(job) = &self.optimal_job {
+            segments.push(format!("Optimal: {}, Ctrl+P to cancel", job.status));
+        }
+        if let Some(walk) = &self.walk {
+            let step = format!("Step {}/{}", walk.position, walk.len());
+            segments.push(match walk.upcoming() {
+                Some((stage, mv)) => {
+                    format!("{step}, {stage}: next {mv}; n/p to step, Esc to stop")
+                }
+                None => format!("{step}: done; p to rewind, Esc to stop"),
+            });
+        }
+        if let Some(autosolve) = &self.autosolve {
+            segments.push(format!(
+                "Auto-solve: {} moves left, : to stop",
+                autosolve.moves.len()
+            ));
+        }
+        if self.frame_writer.is_recording() {
+            segments.push("REC, Ctrl+K to stop".to_string());
+        }
+        let counts = self.move_counts;
+        if self.big_cube.is_none() && counts.stm > 0 {
+            segments.push(format!(
+                "Moves: {} HTM, {} QTM, {} STM",
+                counts.htm, counts.qtm, counts.stm
+            ));
+        }
+        let style = self.scramble_style;
+        segments.push(match style.length(self.scramble_length) {
+            Some(length) => format!("Scramble: {length} moves, {}", style.label()),
+            None => format!("Scramble: {}", style.label()),
+        });
+        if let Some(target) = self
+            .ghost_target
+            .as_ref()
+            .filter(|_| self.big_cube.is_none())
+        {
+            let remaining = self.cube.differing_facelets(target).len();
+            segments.push(format!("Ghost: {remaining} stickers to go"));
+        }
+        if self.show_f2l_pairs && self.big_cube.is_none() {
+            // Five stickers to a pair.
+            let pairs = stages::connected_f2l_pairs(&self.cube).len() / 5;
+            segments.push(format!("F2L pairs: {pairs}"));
+        }
+        frame.put_str(0, bottom, &segments.join("  |  "), theme::active().hud);
+        let scramble = match &self.big_cube {
+            Some(_) => {
+                let moves: Vec<String> = self
+                    .big_scramble
+                    .iter()
+                    .map(|mv| config::BIG_CUBE_NOTATION.format(*mv))
+                    .collect();
+                moves.join(" ")
+            }
+            None => cube::format_sequence(&self.last_scramble),
+        };
+        if !scramble.is_empty()
+            && let Some(above) = bottom.checked_sub(1)
+        {
+            let line = match self.scramble_seed {
+                Some(seed) => format!("Scramble (seed {seed}): {scramble}"),
+                None => format!("Scramble: {scramble}"),
+            };
+            frame.put_str(0, above, &line, theme::active().hud);
+        }
+        // Once a 4x4 is reduced, offer the fix for whichever parity it has.
+        if let Some(big) = self.big_cube.as_ref().filter(|big| big.is_reduced()) {
+            let mut lines = Vec::new();
+            if big.has_oll_parity() {
+                lines.push(format!("OLL parity: {}", nxn::OLL_PARITY_ALG));
+            }
+            if big.has_pll_parity() {
+                lines.push(format!("PLL parity: {}", nxn::PLL_PARITY_ALG));
+            }
+            for (offset, line) in lines.iter().rev().enumerate() {
+                if let Some(row) = bottom.checked_sub(offset + 1) {
+                    frame.put_str(0, row, line, theme::active().hud);
+                }
+            }
+        }
+        if let Some(stages) = &self.solution {
+            let lines: Vec<String> = if stages.iter().all(|stage| stage.moves.is_empty()) {
+                vec!["Already solved".to_string()]
+            } else {
+                stages
+                    .iter()
+                    .map(|stage| {
+                        format!(
+                            "{} ({}): {}",
+                            stage.name,
+                            stage.moves.len(),
+                            cube::format_sequence(&stage.moves)
+                        )
+                    })
+                    .collect()
+            };
+            for (offset, line) in lines.iter().rev().enumerate() {
+                if let Some(row) = bottom.checked_sub(offset + 1) {
+                    frame.put_str(0, row, line, theme::active().hud);
+                }
+            }
+        }
+        if let Some((text, _)) = &self.notice
+            && let Some(above) = bottom.checked_sub(1)
+        {
+            frame.put_str(0, above, text, theme::active().hud);
+        }
+    }
+
+    fn stats_frame(&self, viewport: Viewport) -> Frame {
+        let mut frame = Frame::blank(viewport);
+        let hud = theme::active().hud;
+        frame.put_str(0, 0, "Fewest moves per scramble (Tab to close)", hud);
+        let entries = self.leaderboard.entries();
+        if entries.is_empty() {
+            frame.put_str(0, 2, "No solves recorded yet.", hud);
+        }
+        for (rank, entry) in entries.iter().enumerate() {
+            let line = format!("{:>3}. {:>4}  {}", rank + 1, entry.moves, entry.scramble);
+            frame.put_str(0, rank + 2, &line, hud);
+        }
+        frame
+    }
+
+    /// Recent scrambles, newest first, with the selected one highlighted.
+    fn scramble_list_frame(&self, viewport: Viewport, selected: usize) -> Frame {
+        let mut frame = Frame::blank(viewport);
+        let theme = theme::active();
+        frame.put_str(
+            0,
+            0,
+            "Recent scrambles (Up/Down to pick, Enter to retry, Ctrl+L to close)",
+            theme.hud,
+        );
+        if self.scramble_log.len() == 0 {
+            frame.put_str(0, 2, "No scrambles yet.", theme.hud);
+        }
+        for (index, entry) in self.scramble_log.iter().enumerate() {
+            let line = format!(
+                "{:>3}. seed {:<6}  {}",
+                index + 1,
+                entry.seed,
+                cube::format_sequence(&entry.moves)
+            );
+            if index == selected {
+                frame.put_str(0, index + 2, &line, Some(theme.highlight_text));
+                frame.fill_background(0, index + 2, line.chars().count(), theme.highlight);
+            } else {
+                frame.put_str(0, index + 2, &line, theme.hud);
+            }
+        }
+        frame
+    }
+
+    /// Sleeps out the rest of the frame budget. Everything animated is
+    /// driven by wall-clock `dt`, so when the budget stretches on a slow
+    /// terminal motion keeps its speed and just takes coarser steps.
+    fn cap_frame_rate(&mut self, frame_start: Instant) {
+        let elapsed = frame_start.elapsed();
+        self.frame_budget = adapt_frame_budget(self.frame_budget, elapsed);
+        if let Some(remaining) = self.frame_budget.checked_sub(elapsed) {
             std::thread::sleep(remaining);
         }
     }
 }
 
+/// Backs the frame rate off when a frame overruns its budget and creeps
+/// back towards `TARGET_FPS` once frames finish with room to spare.
+fn adapt_frame_budget(budget: Duration, elapsed: Duration) -> Duration {
+    let next = if elapsed > budget {
+        elapsed.mul_f32(1.25)
+    } else if elapsed < budget / 2 {
+        budget.mul_f32(0.9)
+    } else {
+        budget
+    };
+    next.clamp(config::frame_duration(), config::slowest_frame_duration())
+}
+
+fn split_viewport(viewport: Viewport) -> (Viewport, Viewport) {
+    let left = viewport.width / 2;
+    (
+        Viewport {
+            width: left,
+            height: viewport.height,
+        },
+        Viewport {
+            width: viewport.width - left,
+            height: viewport.height,
+        },
+    )
+}
+
+/// The corner view's left column and size: the top-right corner of
+/// `viewport`, `config::INSET_SCALE` of it on each side.
+fn inset_viewport(viewport: Viewport) -> (u16, Viewport) {
+    let scale = |len: u16| (len as f32 * config::INSET_SCALE).round() as u16;
+    let inset = Viewport {
+        width: scale(viewport.width),
+        height: scale(viewport.height),
+    };
+    (viewport.width - inset.width, inset)
+}
+
+/// The opposite camera pulled back so the whole cube fits the corner view.
+fn inset_camera(camera: &Camera, inset: Viewport) -> Camera {
+    let mut opposite = camera.opposite();
+    opposite.fit_to(inset);
+    opposite
+}
+
+/// Rules off the corner view from the main one along its left and bottom
+/// edges.
+fn draw_inset_border(frame: &mut Frame, x: usize, inset: Viewport) {
+    let color = Some(theme::active().outline);
+    let Some(left) = x.checked_sub(1) else {
+        return;
+    };
+    let height = inset.height as usize;
+    for y in 0..height {
+        frame.put_str(left, y, "│", color);
+    }
+    let rule: String = std::iter::once('└')
+        .chain(std::iter::repeat_n('─', inset.width as usize))
+        .collect();
+    frame.put_str(left, height, &rule, color);
+}
+
 fn current_viewport() -> TermResult<Viewport> {
     let (width, height) = terminal::size()?;
     Ok(Viewport { width, height })
 }
+
+/// The cube size `4` switches to from a cube of `current` size (`None` for
+/// the 3x3): 4x4 from anything else, and back to the size the app started
+/// with from 4x4, or to 3x3 when it started as a 4x4.
+fn toggled_size(current: Option<usize>, start_size: usize) -> usize {
+    match current {
+        Some(4) if start_size == 4 => 3,
+        Some(4) => start_size,
+        _ => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn four_by_four_toggle_returns_to_another_size() {
+        assert_eq!(toggled_size(None, 3), 4);
+        assert_eq!(toggled_size(Some(4), 3), 3);
+        assert_eq!(toggled_size(Some(5), 5), 4);
+        assert_eq!(toggled_size(Some(4), 5), 5);
+        assert_eq!(toggled_size(Some(4), 4), 3);
+        assert_eq!(toggled_size(None, 4), 4);
+    }
+
+    #[test]
+    fn autosolve_plays_one_move_per_interval() {
+        let mut cube = Cube::new();
+        cube.apply_sequence(&[Move::R, Move::U]);
+        let mut autosolve = AutoSolve::new(cube, vec![Move::UPrime, Move::RPrime]);
+        let step = config::AUTOSOLVE_MOVE_SECONDS;
+        assert_eq!(autosolve.update(step / 2.0), None);
+        assert_eq!(autosolve.update(step / 2.0), Some(Move::UPrime));
+        assert_eq!(autosolve.update(step), Some(Move::RPrime));
+        assert!(autosolve.moves.is_empty());
+        assert!(autosolve.expected.is_solved());
+        assert_eq!(autosolve.update(step), None);
+    }
+
+    #[test]
+    fn solution_walk_steps_across_stages() {
+        let walk = SolutionWalk {
+            stages: vec![
+                ("Cross".to_string(), vec![Move::F, Move::R]),
+                ("F2L".to_string(), Vec::new()),
+                ("LL".to_string(), vec![Move::U2]),
+            ],
+            position: 2,
+            expected: Cube::new(),
+        };
+        assert_eq!(walk.len(), 3);
+        assert_eq!(walk.step(1), Some(("Cross", Move::R)));
+        assert_eq!(walk.upcoming(), Some(("LL", Move::U2)));
+        assert_eq!(walk.step(3), None);
+    }
+}
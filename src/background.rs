@@ -0,0 +1,55 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::config;
+use crate::raster::Frame;
+use crate::theme;
+
+const STAR_GLYPHS: [char; 3] = ['.', '·', '*'];
+
+struct Star {
+    x: f32,
+    y: f32,
+    speed: f32,
+    glyph: char,
+}
+
+/// Slowly drifting stars drawn only into cells the cube left empty.
+pub struct Starfield {
+    stars: Vec<Star>,
+}
+
+impl Starfield {
+    pub fn new() -> Self {
+        let mut rng = StdRng::seed_from_u64(config::STARFIELD_SEED);
+        let stars = (0..config::STARFIELD_STARS)
+            .map(|_| {
+                let layer = rng.gen_range(0..STAR_GLYPHS.len());
+                Star {
+                    x: rng.gen_range(0.0..1.0),
+                    y: rng.gen_range(0.0..1.0),
+                    speed: config::STARFIELD_SPEED * (layer + 1) as f32,
+                    glyph: STAR_GLYPHS[layer],
+                }
+            })
+            .collect();
+        Self { stars }
+    }
+
+    /// Advances the drift by `dt` seconds; positions wrap around the screen.
+    pub fn update(&mut self, dt: f32) {
+        for star in &mut self.stars {
+            star.x = (star.x - star.speed * dt).rem_euclid(1.0);
+        }
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let width = frame.width() as f32;
+        let height = frame.height() as f32;
+        for star in &self.stars {
+            let x = (star.x * width) as usize;
+            let y = (star.y * height) as usize;
+            frame.put_background(x, y, star.glyph, Some(theme::active().stars));
+        }
+    }
+}
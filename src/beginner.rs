@@ -0,0 +1,443 @@
+use std::collections::VecDeque;
+
+use once_cell::sync::Lazy;
+
+use crate::cube::{self, Axis, Cube, Face, FaceColor, Move, RotationDir};
+use crate::solver::{self, CubieCube, MOVE_FACES, SOLVED};
+
+/// One named step of a solution.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Stage {
+    pub name: &'static str,
+    pub moves: Vec<Move>,
+}
+
+/// Physical (front, right) faces for each way of holding the cube white
+/// side down: the algorithms below are written for that grip, so U means
+/// the yellow D face and F one of the four side faces.
+const GRIPS: [(char, char); 4] = [('B', 'R'), ('R', 'F'), ('F', 'L'), ('L', 'B')];
+/// First-layer corner slot and middle-layer edge slot that each grip
+/// works on (its front-right corner and edge).
+const GRIP_CORNER_SLOT: [usize; 4] = [3, 0, 1, 2];
+const GRIP_EDGE_SLOT: [usize; 4] = [11, 8, 9, 10];
+
+const CORNER_TRIGGER: &str = "R U R' U'";
+const RIGHT_INSERT: &str = "U R U' R' U' F' U F";
+const LEFT_INSERT: &str = "U' L' U L U F U' F'";
+const EDGE_FLIP: &str = "F R U R' U' F'";
+const SUNE: &str = "R U R' U R U2 R'";
+const CORNER_CYCLE: &str = "R' F R' B2 R F' R' B2 R2";
+const EDGE_CYCLE: &str = "R U' R U R U R U' R' U' R2";
+
+/// Solves `cube` the way the layer-by-layer beginner method teaches:
+/// white cross, white corners, middle layer, yellow cross, then the last
+/// layer's corners oriented and everything permuted. Far longer than
+/// `solver::solve`, but every stage is one a learner can follow.
+pub fn solve(cube: &Cube) -> Result<Vec<Stage>, String> {
+    let mut state = solver::cubie_state(cube)?;
+    let mut stages = Vec::new();
+
+    let cross = solve_cross(&state);
+    finish_stage(&mut stages, &mut state, "White cross", &cross);
+
+    let mut corners = Vec::new();
+    for slot in 0..4 {
+        let mut path = Vec::new();
+        let mut here = state;
+        let current = position(&here.cp, slot);
+        if current < 4 && current != slot {
+            let grip = GRIP_CORNER_SLOT.iter().position(|s| *s == current).unwrap();
+            path.extend(alg(CORNER_TRIGGER, grip));
+            here = apply(&here, &path);
+        }
+        let goal = |c: &CubieCube| cross_done(c) && (0..=slot).all(|s| corner_solved(c, s));
+        let candidates = (0..4).flat_map(|auf| {
+            (0..4).flat_map(move |grip| {
+                (0..6).map(move |reps| {
+                    let mut moves = d_turns(auf);
+                    for _ in 0..reps {
+                        moves.extend(alg(CORNER_TRIGGER, grip));
+                    }
+                    moves
+                })
+            })
+        });
+        path.extend(first_reaching(&here, candidates, goal, "white corners")?);
+        state = apply(&state, &path);
+        corners.extend(path);
+    }
+    stages.push(stage("White corners", &corners));
+
+    let mut middle = Vec::new();
+    for slot in 8..12 {
+        let mut path = Vec::new();
+        let mut here = state;
+        let current = position(&here.ep, slot);
+        if current >= 8 && !edge_solved(&here, slot) {
+            let grip = GRIP_EDGE_SLOT.iter().position(|s| *s == current).unwrap();
+            path.extend(alg(RIGHT_INSERT, grip));
+            here = apply(&here, &path);
+        }
+        let goal = |c: &CubieCube| first_layer_done(c) && (8..=slot).all(|s| edge_solved(c, s));
+        let inserts = (0..4).flat_map(|auf| {
+            (0..4).flat_map(move |grip| {
+                [RIGHT_INSERT, LEFT_INSERT].map(move |insert| {
+                    let mut moves = d_turns(auf);
+                    moves.extend(alg(insert, grip));
+                    moves
+                })
+            })
+        });
+        let candidates = std::iter::once(Vec::new()).chain(inserts);
+        path.extend(first_reaching(&here, candidates, goal, "second layer")?);
+        state = apply(&state, &path);
+        middle.extend(path);
+    }
+    stages.push(stage("Second layer", &middle));
+
+    let yellow_cross = |c: &CubieCube| (4..8).all(|s| c.eo[s] == 0);
+    let path = repeat_until(&state, EDGE_FLIP, 3, yellow_cross)?;
+    finish_stage(&mut stages, &mut state, "Yellow cross", &path);
+
+    let oriented = |c: &CubieCube| yellow_cross(c) && (4..8).all(|s| c.co[s] == 0);
+    let path = repeat_until(&state, SUNE, 4, oriented)?;
+    finish_stage(&mut stages, &mut state, "Yellow corners", &path);
+
+    let corners_placed = |c: &CubieCube| {
+        (0..4).any(|auf| {
+            let turned = apply(c, &d_turns(auf));
+            (4..8).all(|s| corner_solved(&turned, s))
+        })
+    };
+    let mut path = repeat_until(&state, CORNER_CYCLE, 3, corners_placed)?;
+    let after_corners = apply(&state, &path);
+    let solved_after_auf = |c: &CubieCube| (0..4).any(|auf| apply(c, &d_turns(auf)) == SOLVED);
+    path.extend(repeat_until(
+        &after_corners,
+        EDGE_CYCLE,
+        3,
+        solved_after_auf,
+    )?);
+    let almost = apply(&state, &path);
+    let auf = (0..4)
+        .find(|auf| apply(&almost, &d_turns(*auf)) == SOLVED)
+        .expect("last layer solved up to a D turn");
+    path.extend(d_turns(auf));
+    finish_stage(&mut stages, &mut state, "Last layer", &path);
+
+    debug_assert_eq!(state, SOLVED);
+    Ok(stages)
+}
+
+fn stage(name: &'static str, path: &[usize]) -> Stage {
+    Stage {
+        name,
+        moves: cube::simplify(&solver::to_moves(path)),
+    }
+}
+
+/// Records `path` as stage `name` and advances `state` past it.
+fn finish_stage(
+    stages: &mut Vec<Stage>,
+    state: &mut CubieCube,
+    name: &'static str,
+    path: &[usize],
+) {
+    *state = apply(state, path);
+    stages.push(stage(name, path));
+}
+
+/// Applies `path` to a copy of `state`.
+fn apply(state: &CubieCube, path: &[usize]) -> CubieCube {
+    path.iter().fold(*state, |s, &m| s.apply(m))
+}
+
+/// `count` clockwise quarter turns of D, the last layer in the white-down
+/// grip.
+fn d_turns(count: usize) -> Vec<usize> {
+    match count % 4 {
+        0 => Vec::new(),
+        power => vec![9 + power - 1],
+    }
+}
+
+/// `text` written for `grip`, as move indices on the actual cube.
+fn alg(text: &str, grip: usize) -> Vec<usize> {
+    let (front, right) = GRIPS[grip];
+    let opposite = |face| match face {
+        'F' => 'B',
+        'B' => 'F',
+        'R' => 'L',
+        _ => 'R',
+    };
+    cube::parse_algorithm(text)
+        .expect("built-in algorithms parse")
+        .into_iter()
+        .map(|mv| {
+            let index = solver::move_index(mv).expect("built-in algorithms use face turns");
+            let physical = match MOVE_FACES[index / 3] {
+                'U' => 'D',
+                'D' => 'U',
+                'F' => front,
+                'R' => right,
+                'B' => opposite(front),
+                _ => opposite(right),
+            };
+            let face = MOVE_FACES.iter().position(|f| *f == physical).unwrap();
+            face * 3 + index % 3
+        })
+        .collect()
+}
+
+/// Tries `algorithm` from the front grip after each D turn, up to `limit`
+/// times in a row, and returns the shortest run that reaches `goal`.
+fn repeat_until(
+    state: &CubieCube,
+    algorithm: &str,
+    limit: usize,
+    goal: impl Fn(&CubieCube) -> bool,
+) -> Result<Vec<usize>, String> {
+    let ops: Vec<Vec<usize>> = (0..4)
+        .map(|auf| {
+            let mut moves = d_turns(auf);
+            moves.extend(alg(algorithm, 0));
+            moves
+        })
+        .collect();
+    let mut queue = VecDeque::from([(*state, Vec::new(), 0)]);
+    while let Some((here, path, runs)) = queue.pop_front() {
+        if goal(&here) {
+            return Ok(path);
+        }
+        if runs == limit {
+            continue;
+        }
+        for op in &ops {
+            let mut next = path.clone();
+            next.extend(op);
+            queue.push_back((apply(&here, op), next, runs + 1));
+        }
+    }
+    Err(format!("'{algorithm}' did not finish the stage"))
+}
+
+fn first_reaching(
+    state: &CubieCube,
+    candidates: impl Iterator<Item = Vec<usize>>,
+    goal: impl Fn(&CubieCube) -> bool,
+    stage: &str,
+) -> Result<Vec<usize>, String> {
+    let mut candidates: Vec<Vec<usize>> = candidates.collect();
+    candidates.sort_by_key(Vec::len);
+    candidates
+        .into_iter()
+        .find(|path| goal(&apply(state, path)))
+        .ok_or_else(|| format!("no algorithm finished the {stage}"))
+}
+
+fn position(pieces: &[u8], piece: usize) -> usize {
+    pieces
+        .iter()
+        .position(|p| *p as usize == piece)
+        .expect("every piece has a slot")
+}
+
+fn corner_solved(c: &CubieCube, slot: usize) -> bool {
+    c.cp[slot] as usize == slot && c.co[slot] == 0
+}
+
+fn edge_solved(c: &CubieCube, slot: usize) -> bool {
+    c.ep[slot] as usize == slot && c.eo[slot] == 0
+}
+
+fn cross_done(c: &CubieCube) -> bool {
+    (0..4).all(|s| edge_solved(c, s))
+}
+
+fn first_layer_done(c: &CubieCube) -> bool {
+    cross_done(c) && (0..4).all(|s| corner_solved(c, s))
+}
+
+/// Each white edge's slot and flip, packed as `slot * 2 + flip`, gives a
+/// cross state in base 24.
+const CROSS_STATES: usize = 24 * 24 * 24 * 24;
+
+/// Where an edge at `slot * 2 + flip` ends up after each move.
+static EDGE_MOVES: Lazy<Vec<[u8; 24]>> = Lazy::new(|| {
+    (0..18)
+        .map(|m| {
+            let turned = SOLVED.apply(m);
+            let mut table = [0; 24];
+            for (slot, (&from, &flipped)) in turned.ep.iter().zip(&turned.eo).enumerate() {
+                for flip in 0..2 {
+                    table[from as usize * 2 + flip] = (slot * 2 + (flip ^ flipped as usize)) as u8;
+                }
+            }
+            table
+        })
+        .collect()
+});
+
+fn cross_move(state: usize, m: usize) -> usize {
+    (0..4).fold(0, |next, piece| {
+        let edge = state / 24usize.pow(piece) % 24;
+        next + EDGE_MOVES[m][edge] as usize * 24usize.pow(piece)
+    })
+}
+
+/// Moves needed to solve the cross from every cross state.
+static CROSS_DEPTHS: Lazy<Vec<u8>> = Lazy::new(|| {
+    let solved = (0..4)
+        .map(|piece| piece * 2 * 24usize.pow(piece as u32))
+        .sum();
+    let mut depths = vec![u8::MAX; CROSS_STATES];
+    depths[solved] = 0;
+    let mut queue = VecDeque::from([solved]);
+    while let Some(state) = queue.pop_front() {
+        for m in 0..18 {
+            let next = cross_move(state, m);
+            if depths[next] == u8::MAX {
+                depths[next] = depths[state] + 1;
+                queue.push_back(next);
+            }
+        }
+    }
+    depths
+});
+
+/// An optimal white cross, found by walking down the cross distance table.
+fn solve_cross(state: &CubieCube) -> Vec<usize> {
+    let mut cross = (0..4)
+        .map(|piece| {
+            let slot = position(&state.ep, piece);
+            (slot * 2 + state.eo[slot] as usize) * 24usize.pow(piece as u32)
+        })
+        .sum();
+    let mut path = Vec::new();
+    while CROSS_DEPTHS[cross] > 0 {
+        let m = (0..18)
+            .find(|&m| CROSS_DEPTHS[cross_move(cross, m)] < CROSS_DEPTHS[cross])
+            .expect("some move gets closer");
+        path.push(m);
+        cross = cross_move(cross, m);
+    }
+    path
+}
+
+/// Every face's shortest cross, named by color, shortest first.
+pub fn solve_crosses(cube: &Cube) -> Result<Vec<Stage>, String> {
+    let mut stages = Face::all()
+        .iter()
+        .map(|&face| {
+            let name = match face.default_color() {
+                FaceColor::White => "White cross",
+                FaceColor::Yellow => "Yellow cross",
+                FaceColor::Red => "Red cross",
+                FaceColor::Orange => "Orange cross",
+                FaceColor::Blue => "Blue cross",
+                FaceColor::Green => "Green cross",
+            };
+            Ok(Stage {
+                name,
+                moves: solve_cross_on(cube, face)?,
+            })
+        })
+        .collect::<Result<Vec<Stage>, String>>()?;
+    stages.sort_by_key(|stage| stage.moves.len());
+    Ok(stages)
+}
+
+/// The shortest cross on `face`, e.g. to compare with the cross actually
+/// done. The cube is turned so `face` is on top, the U cross solved there,
+/// and the moves renamed back to the faces they turn on the real cube.
+pub fn solve_cross_on(cube: &Cube, face: Face) -> Result<Vec<Move>, String> {
+    solver::cubie_state(cube)?;
+    // x brings F up, z' brings R up.
+    let (axis, dir, turns) = match face {
+        Face::Up => (Axis::X, RotationDir::Clockwise, 0),
+        Face::Down => (Axis::X, RotationDir::Clockwise, 2),
+        Face::Front => (Axis::X, RotationDir::Clockwise, 1),
+        Face::Back => (Axis::X, RotationDir::CounterClockwise, 1),
+        Face::Right => (Axis::Z, RotationDir::CounterClockwise, 1),
+        Face::Left => (Axis::Z, RotationDir::Clockwise, 1),
+    };
+    let turn = |face: Face| (0..turns).fold(face, |f, _| cube::rotate_face(f, axis, dir));
+    let mut turned = *cube;
+    for _ in 0..turns {
+        turned.rotate(axis, dir);
+    }
+    let state = CubieCube::from_facelets(&turned.to_facelet_string())?;
+    Ok(solver::to_moves(&solve_cross(&state))
+        .into_iter()
+        .map(|mv| {
+            let notation = mv.notation();
+            let shown = Face::from_letter(notation.chars().next().unwrap_or('U'))
+                .expect("solver moves are face turns");
+            let real = Face::all()
+                .iter()
+                .find(|face| turn(**face) == shown)
+                .expect("rotations permute the faces");
+            cube::move_for(
+                real.letter(),
+                notation.ends_with('\''),
+                notation.ends_with('2'),
+            )
+            .expect("face letters map to moves")
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn stages_solve_random_scrambles_in_order() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        for _ in 0..10 {
+            let mut cube = Cube::new();
+            cube.scramble_with(cube::ScrambleStyle::Standard, 25, &mut rng);
+            let stages = solve(&cube).unwrap();
+            let names: Vec<_> = stages.iter().map(|stage| stage.name).collect();
+            assert_eq!(
+                names,
+                [
+                    "White cross",
+                    "White corners",
+                    "Second layer",
+                    "Yellow cross",
+                    "Yellow corners",
+                    "Last layer"
+                ]
+            );
+            assert!(stages[0].moves.len() <= 8);
+            for stage in &stages {
+                cube.apply_sequence(&stage.moves);
+            }
+            assert!(cube.is_solved());
+        }
+    }
+
+    #[test]
+    fn crosses_solve_on_every_face() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(8);
+        let mut cube = Cube::new();
+        cube.scramble_with(cube::ScrambleStyle::Standard, 25, &mut rng);
+        for &face in Face::all() {
+            let moves = solve_cross_on(&cube, face).unwrap();
+            assert!(moves.len() <= 8, "{face:?}");
+            let mut solved = cube;
+            solved.apply_sequence(&moves);
+            let normal = face.spec().normal;
+            assert!(
+                solved.facelets_match_centers(|desc| {
+                    let c = desc.coord;
+                    c.component(normal.axis) == normal.dir
+                        && [c.x, c.y, c.z].iter().filter(|v| **v != 0).count() == 2
+                }),
+                "{face:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use crate::config;
+use crate::cube::{Cube, Face, FaceColor, FaceletDescriptor, facelet_descriptors};
+
+/// Letter for the sticker slot at facelet `index` in `config::LETTER_SCHEME`
+/// (Speffz by default). Faces are lettered U, L, F, R, B, D in blocks of
+/// four, clockwise from the top-left corner (or top edge) as the face is
+/// seen in the standard net. Corners are returned uppercase and edges
+/// lowercase; centers have no letter.
+pub fn letter(index: usize) -> Option<char> {
+    let desc = facelet_descriptors().get(index)?;
+    let letter = config::LETTER_SCHEME[letter_slot(desc)?];
+    if is_corner(desc) {
+        Some(letter.to_ascii_uppercase())
+    } else {
+        Some(letter.to_ascii_lowercase())
+    }
+}
+
+/// Old Pochmann memo for a cube: the letters of the targets the buffer is
+/// shot to, corners from the UBL buffer and edges from the UR buffer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Memo {
+    pub corners: Vec<char>,
+    pub edges: Vec<char>,
+}
+
+impl Memo {
+    /// An odd number of corner targets (and so of edge targets) needs the
+    /// parity algorithm between edges and corners.
+    pub fn parity(&self) -> bool {
+        self.corners.len() % 2 == 1
+    }
+
+    /// Every letter, corners first, as the quiz expects them.
+    pub fn letters(&self) -> String {
+        self.corners.iter().chain(&self.edges).collect()
+    }
+
+    /// The letters in pairs, e.g. "corners AB C, edges ab (parity)".
+    pub fn describe(&self) -> String {
+        let pairs = |letters: &[char]| -> String {
+            if letters.is_empty() {
+                return "-".to_string();
+            }
+            let pairs: Vec<String> = letters
+                .chunks(2)
+                .map(|pair| pair.iter().collect())
+                .collect();
+            pairs.join(" ")
+        };
+        let parity = if self.parity() { " (parity)" } else { "" };
+        format!(
+            "corners {}, edges {}{parity}",
+            pairs(&self.corners),
+            pairs(&self.edges)
+        )
+    }
+}
+
+/// Traces the Old Pochmann memo for `cube` by playing out each swap: the
+/// sticker in the buffer names the next target, and once the buffer holds
+/// its own piece the memo breaks into the first unsolved piece. Stickers
+/// are judged against the centers, so any orientation works.
+pub fn memo(cube: &Cube) -> Memo {
+    let homes = sticker_homes(cube);
+    Memo {
+        corners: trace(homes.clone(), &pieces(3), buffer(0, 0)),
+        edges: trace(homes, &pieces(2), buffer(1, 2)),
+    }
+}
+
+/// Old Pochmann never needs more targets than this on a legal cube; the
+/// cap keeps an impossible state from looping.
+const MAX_TARGETS: usize = 24;
+
+fn trace(mut homes: Vec<usize>, pieces: &[Vec<usize>], buffer: usize) -> Vec<char> {
+    let buffer_piece = pieces
+        .iter()
+        .find(|piece| piece.contains(&buffer))
+        .expect("the buffer is on a piece");
+    let mut targets = Vec::new();
+    while targets.len() < MAX_TARGETS {
+        let home = homes[buffer];
+        let target = if buffer_piece.contains(&home) {
+            let unsolved = pieces.iter().find(|piece| {
+                !piece.contains(&buffer) && piece.iter().any(|idx| homes[*idx] != *idx)
+            });
+            match unsolved {
+                Some(piece) => piece[0],
+                None => break,
+            }
+        } else {
+            home
+        };
+        targets.push(letter(target).expect("pieces have letters"));
+        // The swap carries each buffer sticker to the matching target one.
+        let from = starting_at(buffer_piece, buffer);
+        let piece = pieces
+            .iter()
+            .find(|piece| piece.contains(&target))
+            .expect("targets are on pieces");
+        let to = starting_at(piece, target);
+        for (a, b) in from.iter().zip(&to) {
+            homes.swap(*a, *b);
+        }
+    }
+    targets
+}
+
+/// `piece` turned cyclically so it starts at `facelet`.
+fn starting_at(piece: &[usize], facelet: usize) -> Vec<usize> {
+    let start = piece.iter().position(|idx| *idx == facelet).unwrap_or(0);
+    let mut turned = piece.to_vec();
+    turned.rotate_left(start);
+    turned
+}
+
+/// The U-face facelet at `row`, `col`; both buffers sit on U.
+fn buffer(row: u8, col: u8) -> usize {
+    facelet_descriptors()
+        .iter()
+        .position(|desc| desc.face == Face::Up && desc.row == row && desc.col == col)
+        .expect("every U slot has a facelet")
+}
+
+/// The facelets of every piece with `stickers` stickers, in letter order.
+/// Each corner lists its stickers the same way round, so matching them up
+/// position by position follows a real swap.
+fn pieces(stickers: usize) -> Vec<Vec<usize>> {
+    let descs = facelet_descriptors();
+    let mut pieces: Vec<Vec<usize>> = Vec::new();
+    for desc in descs {
+        if pieces
+            .iter()
+            .any(|piece| descs[piece[0]].coord == desc.coord)
+        {
+            continue;
+        }
+        let mut piece: Vec<usize> = (0..descs.len())
+            .filter(|other| descs[*other].coord == desc.coord)
+            .collect();
+        if piece.len() != stickers {
+            continue;
+        }
+        if stickers == 3 {
+            let [a, b, c] = [0, 1, 2].map(|i| normal(descs[piece[i]].face));
+            let det = a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0])
+                + a[2] * (b[0] * c[1] - b[1] * c[0]);
+            if det < 0 {
+                piece.swap(1, 2);
+            }
+        }
+        let first = (0..piece.len())
+            .min_by_key(|i| letter_slot(&descs[piece[*i]]))
+            .unwrap_or(0);
+        piece.rotate_left(first);
+        pieces.push(piece);
+    }
+    pieces.sort_by_key(|piece| letter_slot(&descs[piece[0]]));
+    pieces
+}
+
+fn normal(face: Face) -> [i32; 3] {
+    match face {
+        Face::Up => [0, 1, 0],
+        Face::Down => [0, -1, 0],
+        Face::Right => [1, 0, 0],
+        Face::Left => [-1, 0, 0],
+        Face::Front => [0, 0, 1],
+        Face::Back => [0, 0, -1],
+    }
+}
+
+/// For each facelet, the facelet its current sticker belongs on when solved.
+fn sticker_homes(cube: &Cube) -> Vec<usize> {
+    let descs = facelet_descriptors();
+    let colors = cube.face_colors();
+    let solved: Vec<FaceColor> = descs
+        .iter()
+        .map(|desc| cube.center_color(desc.face))
+        .collect();
+    let piece_colors = |idx: usize, colors: &[FaceColor]| -> Vec<FaceColor> {
+        let mut piece: Vec<FaceColor> = (0..descs.len())
+            .filter(|other| descs[*other].coord == descs[idx].coord)
+            .map(|other| colors[other])
+            .collect();
+        piece.sort_by_key(|color| color.index());
+        piece
+    };
+    (0..descs.len())
+        .map(|idx| {
+            let here = piece_colors(idx, colors);
+            (0..descs.len())
+                .find(|home| solved[*home] == colors[idx] && piece_colors(*home, &solved) == here)
+                .unwrap_or(idx)
+        })
+        .collect()
+}
+
+/// Position in the lettering scheme: four slots per face, faces in U, L,
+/// F, R, B, D order.
+fn letter_slot(desc: &FaceletDescriptor) -> Option<usize> {
+    Some(face_block(desc.face) * 4 + slot_in_face(desc)?)
+}
+
+fn face_block(face: Face) -> usize {
+    match face {
+        Face::Up => 0,
+        Face::Left => 1,
+        Face::Front => 2,
+        Face::Right => 3,
+        Face::Back => 4,
+        Face::Down => 5,
+    }
+}
+
+fn slot_in_face(desc: &FaceletDescriptor) -> Option<usize> {
+    match (desc.row, desc.col) {
+        (0, 0) | (0, 1) => Some(0),
+        (0, 2) | (1, 2) => Some(1),
+        (2, 2) | (2, 1) => Some(2),
+        (2, 0) | (1, 0) => Some(3),
+        _ => None,
+    }
+}
+
+fn is_corner(desc: &FaceletDescriptor) -> bool {
+    desc.row != 1 && desc.col != 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::{self, Move};
+
+    #[test]
+    fn speffz_assigns_each_letter_once_per_piece_type() {
+        let letters: Vec<char> = (0..facelet_descriptors().len())
+            .filter_map(letter)
+            .collect();
+        for base in ['A', 'a'] {
+            for offset in 0..24u8 {
+                let letter = (base as u8 + offset) as char;
+                assert_eq!(letters.iter().filter(|c| **c == letter).count(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn memo_follows_old_pochmann() {
+        assert_eq!(memo(&Cube::new()).letters(), "");
+
+        // The T perm swaps the UL and UR edges and the UFR and UBR corners,
+        // which leaves the corner buffer solved and needs a cycle break.
+        let mut cube = Cube::new();
+        cube.apply_sequence(
+            &cube::parse_algorithm("R U R' U' R' F R2 U' R' U' R U R' F'").unwrap(),
+        );
+        let memo = memo(&cube);
+        assert_eq!(memo.edges, ['d']);
+        assert_eq!(memo.corners.len(), 3);
+        assert!(memo.parity());
+
+        // Tracing only stops short of the cap once every piece is home.
+        let mut cube = Cube::new();
+        cube.apply_sequence(&[Move::R, Move::U, Move::F2, Move::D, Move::LPrime]);
+        let memo = super::memo(&cube);
+        assert!(memo.corners.len() < MAX_TARGETS && memo.edges.len() < MAX_TARGETS);
+        assert_eq!(memo.corners.len() % 2, memo.edges.len() % 2);
+    }
+}
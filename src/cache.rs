@@ -0,0 +1,79 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+
+const MAGIC: &[u8; 8] = b"CUBEXTBL";
+/// Magic, then version (u32), payload length (u64) and payload checksum
+/// (u64), all little-endian.
+const HEADER_LEN: usize = 8 + 4 + 8 + 8;
+
+/// Where a cached table named `name` lives, if there is a data directory.
+pub fn path(name: &str) -> Option<PathBuf> {
+    config::data_dir().map(|dir| dir.join(config::TABLE_CACHE_DIR).join(name))
+}
+
+/// Reads a table written by `write`, or `None` if the file is missing,
+/// from another version, the wrong size or corrupted.
+pub fn read(path: &Path, version: u32, len: usize) -> Option<Vec<u8>> {
+    let mut bytes = fs::read(path).ok()?;
+    if bytes.len() != HEADER_LEN + len || &bytes[..8] != MAGIC {
+        return None;
+    }
+    let stored_version = u32::from_le_bytes(bytes[8..12].try_into().ok()?);
+    let stored_len = u64::from_le_bytes(bytes[12..20].try_into().ok()?);
+    let stored_sum = u64::from_le_bytes(bytes[20..28].try_into().ok()?);
+    let payload = &bytes[HEADER_LEN..];
+    if stored_version != version || stored_len != len as u64 || stored_sum != checksum(payload) {
+        return None;
+    }
+    bytes.drain(..HEADER_LEN);
+    Some(bytes)
+}
+
+pub fn write(path: &Path, version: u32, payload: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&version.to_le_bytes());
+    bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(&checksum(payload).to_le_bytes());
+    bytes.extend_from_slice(payload);
+    // Write beside the target and rename, so an interrupted write never
+    // leaves a half-written table behind.
+    let partial = path.with_extension("partial");
+    fs::write(&partial, bytes)?;
+    fs::rename(partial, path)
+}
+
+/// 64-bit FNV-1a.
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_round_trip_and_reject_stale_or_damaged_files() {
+        let dir = std::env::temp_dir().join(format!("cubex-cache-test-{}", std::process::id()));
+        let path = dir.join("table.bin");
+        let payload: Vec<u8> = (0..=255).collect();
+        write(&path, 3, &payload).unwrap();
+        assert_eq!(read(&path, 3, payload.len()), Some(payload.clone()));
+        assert_eq!(read(&path, 4, payload.len()), None);
+        assert_eq!(read(&path, 3, payload.len() + 1), None);
+
+        let mut damaged = fs::read(&path).unwrap();
+        *damaged.last_mut().unwrap() ^= 1;
+        fs::write(&path, damaged).unwrap();
+        assert_eq!(read(&path, 3, payload.len()), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,159 @@
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::geometry::Camera;
+use crate::raster::Frame;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DumpFormat {
+    Text,
+    Ansi,
+    Both,
+}
+
+impl std::str::FromStr for DumpFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(DumpFormat::Text),
+            "ansi" => Ok(DumpFormat::Ansi),
+            "both" => Ok(DumpFormat::Both),
+            other => Err(format!(
+                "unknown dump format '{other}' (expected text, ansi or both)"
+            )),
+        }
+    }
+}
+
+/// Writes every blitted frame to `dir` as `frame-000001.txt` / `.ans`.
+pub struct FrameDump {
+    dir: PathBuf,
+    format: DumpFormat,
+    next_index: u64,
+}
+
+impl FrameDump {
+    pub fn create(dir: PathBuf, format: DumpFormat) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            format,
+            next_index: 1,
+        })
+    }
+
+    pub fn write(&mut self, frame: &Frame) -> io::Result<()> {
+        let stem = format!("frame-{:06}", self.next_index);
+        if matches!(self.format, DumpFormat::Text | DumpFormat::Both) {
+            fs::write(
+                self.dir.join(format!("{stem}.txt")),
+                frame.as_plain_string(),
+            )?;
+        }
+        if matches!(self.format, DumpFormat::Ansi | DumpFormat::Both) {
+            fs::write(self.dir.join(format!("{stem}.ans")), frame.as_ansi_string())?;
+        }
+        self.next_index += 1;
+        Ok(())
+    }
+}
+
+/// Saves `frame` next to `stem` as `<stem>.ans` (with color escapes, for
+/// `cat`) and `<stem>.txt` (plain text).
+pub fn export_frame(frame: &Frame, stem: &Path) -> io::Result<()> {
+    fs::write(stem.with_extension("ans"), frame.as_ansi_string())?;
+    fs::write(stem.with_extension("txt"), frame.as_plain_string())
+}
+
+/// Records one full camera orbit into `dir`: numbered `.txt` / `.ans`
+/// frames plus `turntable.cast`, an asciinema recording of the same frames.
+pub struct Turntable {
+    dump: FrameDump,
+    cast: BufWriter<File>,
+    duration: f32,
+    elapsed: f32,
+    wrote_header: bool,
+}
+
+impl Turntable {
+    pub fn create(dir: PathBuf, duration: f32) -> io::Result<Self> {
+        let dump = FrameDump::create(dir.clone(), DumpFormat::Both)?;
+        let cast = BufWriter::new(File::create(dir.join("turntable.cast"))?);
+        Ok(Self {
+            dump,
+            cast,
+            duration,
+            elapsed: 0.0,
+            wrote_header: false,
+        })
+    }
+
+    /// Advances the orbit by `dt` seconds; returns false once it has come
+    /// full circle.
+    pub fn update(&mut self, dt: f32) -> bool {
+        self.elapsed += dt;
+        self.elapsed < self.duration
+    }
+
+    /// A copy of `camera` carried round by the orbit so far.
+    pub fn apply(&self, camera: &Camera) -> Camera {
+        let mut orbiting = *camera;
+        orbiting.orbit(self.elapsed / self.duration * std::f32::consts::TAU, 0.0);
+        orbiting
+    }
+
+    pub fn write(&mut self, frame: &Frame) -> io::Result<()> {
+        self.dump.write(frame)?;
+        if !self.wrote_header {
+            writeln!(
+                self.cast,
+                r#"{{"version": 2, "width": {}, "height": {}}}"#,
+                frame.width(),
+                frame.height()
+            )?;
+            self.wrote_header = true;
+        }
+        let output = format!("\x1b[H{}", frame.as_ansi_string());
+        writeln!(
+            self.cast,
+            r#"[{:.3}, "o", "{}"]"#,
+            self.elapsed,
+            json_escape(&output)
+        )
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.cast.flush()
+    }
+}
+
+fn json_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            ch if ch.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", ch as u32);
+            }
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_escape_handles_quotes_and_escapes() {
+        assert_eq!(json_escape("a\"b\\c\r\n"), r#"a\"b\\c\r\n"#);
+        assert_eq!(json_escape("\x1b[H"), r"\u001b[H");
+    }
+}
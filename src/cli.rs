@@ -0,0 +1,307 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::capture::DumpFormat;
+use crate::config;
+use crate::cube::{self, Cube, FaceColor, Move};
+use crate::cube_explorer;
+use crate::macros::{self, Macros};
+use crate::nxn::{self, LayerMove};
+use crate::raster::{CellMode, RenderSettings, RendererKind};
+use crate::reconstruction::{self, Reconstruction};
+use crate::recording::RecordFormat;
+use crate::sixel::Graphics;
+use crate::stages::Method;
+use crate::theme::{self, Theme};
+
+pub const USAGE: &str = "usage: cubex [--renderer raster|raycast] [--cells ascii|quadrant|half|braille] \
+[--graphics text|sixel|auto] [--gamma <value>] \
+[--mono] [--solid] [--wireframe] [--no-dither] [--ground] [--glyphs W=#,R=%,...] [--dump-frames <dir>] [--dump-format text|ansi|both] \\
+[--turntable-seconds <n>] [--record-format cast|gif] [--method cfop|roux] [--mouse] \\
+[--theme classic|pastel|solarized|<file>] [--size <n>] [--state <facelets>] [--cube-explorer <file>] [--pattern <name>|today] [--watch <file>] [--reconstruction <file>] \\
+[--macros <file>] [--seed <n>] [--scramble <moves>]";
+
+pub struct Options {
+    pub renderer: RendererKind,
+    pub cell_mode: CellMode,
+    pub graphics: Graphics,
+    pub render_settings: RenderSettings,
+    pub dump_dir: Option<PathBuf>,
+    pub dump_format: DumpFormat,
+    pub turntable_seconds: f32,
+    /// What `Ctrl+K` records to.
+    pub record_format: RecordFormat,
+    /// Method whose stage the status line shows.
+    pub method: Method,
+    pub mouse: bool,
+    pub theme: Option<Theme>,
+    pub size: usize,
+    /// Starting position as a 54-letter facelet string.
+    pub state: Option<Cube>,
+    /// Name of the `--pattern` in `state`, announced at start.
+    pub pattern: Option<&'static str>,
+    /// File whose facelet string replaces the cube whenever it changes.
+    pub watch: Option<PathBuf>,
+    /// Solve from `--reconstruction`, stepped through from its scramble.
+    pub reconstruction: Option<Reconstruction>,
+    /// Key-bound algorithms from `--macros`, or else the data directory.
+    pub macros: Option<Macros>,
+    /// Seeds the first scramble and every one after it.
+    pub seed: Option<u64>,
+    /// Scramble from `--scramble` for the 3x3, applied after `state`.
+    pub scramble: Vec<Move>,
+    /// Scramble from `--scramble` for any other size.
+    pub big_scramble: Vec<LayerMove>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            renderer: RendererKind::Raster,
+            cell_mode: CellMode::Ascii,
+            graphics: Graphics::Auto,
+            render_settings: RenderSettings::default(),
+            dump_dir: None,
+            dump_format: DumpFormat::Both,
+            turntable_seconds: config::TURNTABLE_SECONDS,
+            record_format: RecordFormat::Cast,
+            method: config::STAGE_METHOD,
+            mouse: false,
+            theme: None,
+            size: 3,
+            state: None,
+            pattern: None,
+            watch: None,
+            reconstruction: None,
+            macros: None,
+            seed: None,
+            scramble: Vec::new(),
+            big_scramble: Vec::new(),
+        }
+    }
+}
+
+pub fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut scramble = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--renderer" => {
+                let value = args.next().ok_or("--renderer needs a value")?;
+                options.renderer = value.parse()?;
+            }
+            "--cells" => {
+                let value = args.next().ok_or("--cells needs a value")?;
+                options.cell_mode = value.parse()?;
+            }
+            "--graphics" => {
+                let value = args.next().ok_or("--graphics needs a value")?;
+                options.graphics = value.parse()?;
+            }
+            "--gamma" => {
+                let value = args.next().ok_or("--gamma needs a value")?;
+                let gamma: f32 = value
+                    .parse()
+                    .map_err(|_| format!("invalid gamma '{value}'"))?;
+                if !gamma.is_finite() || gamma <= 0.0 {
+                    return Err("--gamma must be a positive number".to_string());
+                }
+                options.render_settings.gamma = gamma;
+            }
+            "--mono" => options.render_settings.monochrome = true,
+            "--solid" => options.render_settings.solid = true,
+            "--wireframe" => options.render_settings.wireframe = true,
+            "--no-dither" => options.render_settings.dither = false,
+            "--ground" => options.render_settings.ground = true,
+            "--glyphs" => {
+                let value = args.next().ok_or("--glyphs needs a mapping")?;
+                parse_glyphs(&value, &mut options.render_settings.glyphs)?;
+            }
+            "--dump-frames" => {
+                let value = args.next().ok_or("--dump-frames needs a directory")?;
+                options.dump_dir = Some(PathBuf::from(value));
+            }
+            "--dump-format" => {
+                let value = args.next().ok_or("--dump-format needs a value")?;
+                options.dump_format = value.parse()?;
+            }
+            "--turntable-seconds" => {
+                let value = args.next().ok_or("--turntable-seconds needs a value")?;
+                let seconds: f32 = value
+                    .parse()
+                    .map_err(|_| format!("invalid turntable length '{value}'"))?;
+                if !seconds.is_finite() || seconds <= 0.0 {
+                    return Err("--turntable-seconds must be a positive number".to_string());
+                }
+                options.turntable_seconds = seconds;
+            }
+            "--record-format" => {
+                let value = args.next().ok_or("--record-format needs a value")?;
+                options.record_format = value.parse()?;
+            }
+            "--method" => {
+                let value = args.next().ok_or("--method needs a value")?;
+                options.method = value.parse()?;
+            }
+            "--mouse" => options.mouse = true,
+            "--size" => {
+                let value = args.next().ok_or("--size needs a value")?;
+                let size: usize = value
+                    .parse()
+                    .map_err(|_| format!("invalid cube size '{value}'"))?;
+                if !(nxn::MIN_SIZE..=nxn::MAX_SIZE).contains(&size) {
+                    return Err(format!(
+                        "--size must be between {} and {}",
+                        nxn::MIN_SIZE,
+                        nxn::MAX_SIZE
+                    ));
+                }
+                options.size = size;
+            }
+            "--theme" => {
+                let value = args.next().ok_or("--theme needs a file or name")?;
+                options.theme = Some(theme::load(&value)?);
+            }
+            "--state" => {
+                let value = args.next().ok_or("--state needs a facelet string")?;
+                let cube =
+                    Cube::from_facelet_string(&value).map_err(|err| format!("--state: {err}"))?;
+                options.state = Some(cube);
+            }
+            "--cube-explorer" => {
+                let value = args.next().ok_or("--cube-explorer needs a file")?;
+                let text = std::fs::read_to_string(&value)
+                    .map_err(|err| format!("--cube-explorer: {value}: {err}"))?;
+                let cube = cube_explorer::from_text(&text)
+                    .map_err(|err| format!("--cube-explorer: {value}: {err}"))?;
+                options.state = Some(cube);
+            }
+            "--pattern" => {
+                let value = args.next().ok_or("--pattern needs a name")?;
+                let name = match value.as_str() {
+                    "today" => cube::pattern_of_the_day(today()),
+                    _ => cube::PATTERNS
+                        .iter()
+                        .map(|(name, _)| *name)
+                        .find(|name| *name == value)
+                        .ok_or_else(|| {
+                            let names: Vec<&str> =
+                                cube::PATTERNS.iter().map(|(name, _)| *name).collect();
+                            format!(
+                                "unknown pattern '{value}' (expected one of {}, or today)",
+                                names.join(", ")
+                            )
+                        })?,
+                };
+                let mut cube = Cube::new();
+                cube.apply_sequence(&cube::pattern(name).expect("listed in PATTERNS"));
+                options.state = Some(cube);
+                options.pattern = Some(name);
+            }
+            "--watch" => {
+                let value = args.next().ok_or("--watch needs a file")?;
+                options.watch = Some(PathBuf::from(value));
+            }
+            "--reconstruction" => {
+                let value = args.next().ok_or("--reconstruction needs a file")?;
+                let text = std::fs::read_to_string(&value)
+                    .map_err(|err| format!("--reconstruction: {value}: {err}"))?;
+                let reconstruction = reconstruction::parse(&text)
+                    .map_err(|err| format!("--reconstruction: {value}: {err}"))?;
+                options.reconstruction = Some(reconstruction);
+            }
+            "--macros" => {
+                let value = args.next().ok_or("--macros needs a file")?;
+                options.macros = Some(macros::load(Path::new(&value))?);
+            }
+            "--seed" => {
+                let value = args.next().ok_or("--seed needs a number")?;
+                let seed = value
+                    .parse()
+                    .map_err(|_| format!("invalid seed '{value}'"))?;
+                options.seed = Some(seed);
+            }
+            "--scramble" => {
+                scramble = Some(args.next().ok_or("--scramble needs moves")?);
+            }
+            other => return Err(format!("unknown argument '{other}'")),
+        }
+    }
+    if (options.state.is_some() || options.watch.is_some() || options.reconstruction.is_some())
+        && options.size != 3
+    {
+        return Err(
+            "--state, --cube-explorer, --pattern, --watch and --reconstruction only work with a 3x3 cube"
+                .to_string(),
+        );
+    }
+    if options.reconstruction.is_some() && (options.state.is_some() || scramble.is_some()) {
+        return Err("--reconstruction brings its own scramble".to_string());
+    }
+    // The notation depends on the size, which may come after.
+    if let Some(text) = scramble {
+        let error = |err: cube::ParseError| format!("--scramble: {err}");
+        if options.size == 3 {
+            options.scramble = cube::parse_algorithm(&text).map_err(error)?;
+        } else {
+            options.big_scramble = nxn::parse_algorithm(&text, options.size).map_err(error)?;
+        }
+    }
+    if options.macros.is_none()
+        && let Some(path) = config::data_dir().map(|dir| dir.join(config::MACROS_FILE))
+        && path.exists()
+    {
+        options.macros = Some(macros::load(&path)?);
+    }
+    Ok(options)
+}
+
+/// Parses `W=#,R=%` style color-to-glyph pairs into `glyphs`.
+fn parse_glyphs(spec: &str, glyphs: &mut [Option<char>; 6]) -> Result<(), String> {
+    for pair in spec.split(',').filter(|pair| !pair.trim().is_empty()) {
+        let (color, glyph) = pair
+            .split_once('=')
+            .ok_or_else(|| format!("glyph mapping '{pair}' should look like W=#"))?;
+        let mut letters = color.trim().chars();
+        let color = match (letters.next(), letters.next()) {
+            (Some(letter), None) => FaceColor::from_letter(letter),
+            _ => None,
+        }
+        .ok_or_else(|| format!("unknown color '{color}' (expected one of W Y R O B G)"))?;
+        let mut chars = glyph.chars();
+        let glyph = match (chars.next(), chars.next()) {
+            (Some(ch), None) => ch,
+            _ => return Err(format!("glyph for {color:?} must be a single character")),
+        };
+        glyphs[color.index()] = Some(glyph);
+    }
+    Ok(())
+}
+
+/// Days since the Unix epoch, in UTC.
+fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() / 86_400)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn gamma_must_be_a_positive_number() {
+        assert_eq!(
+            parse(&["--gamma", "1.8"]).unwrap().render_settings.gamma,
+            1.8
+        );
+        for value in ["0", "-1", "NaN", "inf", "-inf"] {
+            assert!(parse(&["--gamma", value]).is_err(), "{value}");
+        }
+        assert!(parse(&["--turntable-seconds", "inf"]).is_err());
+    }
+}
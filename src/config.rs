@@ -1,38 +1,193 @@
+use std::path::PathBuf;
 use std::time::Duration;
 
 use crossterm::style::Color;
 
-use crate::cube::FaceColor;
+use crate::cube::{self, ColorScheme, FaceColor};
+use crate::geometry::Vec3;
+use crate::nxn::Notation;
+use crate::palette::ColorDepth;
+use crate::stages::Method;
+use crate::theme;
 
 pub const TARGET_FPS: u64 = 30;
+/// Floor for the adaptive frame rate on terminals that can't keep up.
+pub const MIN_FPS: u64 = 8;
+/// Solved color of each face in U, D, R, L, F, B order; `cube::JAPANESE_SCHEME`
+/// or any other arrangement of the six colors works too.
+pub const COLOR_SCHEME: ColorScheme = cube::WESTERN_SCHEME;
 pub const SCRAMBLE_LENGTH: usize = 25;
+/// Method whose stage the status line shows at start (`--method`, or `/`
+/// to switch).
+pub const STAGE_METHOD: Method = Method::Cfop;
+pub const SCRAMBLE_MIN_LENGTH: usize = 1;
+pub const SCRAMBLE_MAX_LENGTH: usize = 60;
+/// Scramble seeds are drawn below this, short enough to read out to
+/// someone racing the same scramble.
+pub const SCRAMBLE_SEEDS: u64 = 1_000_000;
+/// How big-cube scrambles are written: `Notation::Wca` (`3Rw`) or
+/// `Notation::Sign` (`3r`). Both are read back by `--scramble`.
+pub const BIG_CUBE_NOTATION: Notation = Notation::Wca;
+/// Recent scrambles kept for `Ctrl+L`.
+pub const SCRAMBLE_LOG_SIZE: usize = 10;
 pub const CAMERA_ROTATE_STEP: f32 = 0.14;
 pub const CAMERA_ELEVATION_STEP: f32 = 0.1;
 pub const CAMERA_ROLL_STEP: f32 = 0.06;
 pub const CAMERA_ZOOM_STEP: f32 = 0.45;
 pub const CAMERA_MIN_RADIUS: f32 = 2.8;
 pub const CAMERA_MAX_RADIUS: f32 = 9.5;
+/// Where the light shines from (world space, y up, z towards the default
+/// camera) and how brightness splits between an ambient floor and the lit
+/// part; the two should add up to 1. Shift+arrows swing the light by
+/// `LIGHT_ROTATE_STEP` radians.
+pub const LIGHT_DIR: Vec3 = Vec3::new(0.3, 0.9, 0.6);
+pub const LIGHT_AMBIENT: f32 = 0.2;
+pub const LIGHT_DIFFUSE: f32 = 0.8;
+pub const LIGHT_ROTATE_STEP: f32 = 0.2;
+/// Blinn-Phong highlight strength (0 turns it off) and how tight it is.
+pub const LIGHT_SPECULAR: f32 = 0.35;
+pub const LIGHT_SHININESS: f32 = 24.0;
+/// A second, fixed light with its diffuse strength, e.g.
+/// `Some((Vec3::new(-0.6, -0.2, 0.5), 0.25))` to lift the shadowed faces.
+pub const FILL_LIGHT: Option<(Vec3, f32)> = None;
+/// Depth fog: stickers fade by up to `FOG_DENSITY` (0 turns it off) as
+/// they go from `FOG_START` to `FOG_END` past the middle of the cube, as
+/// seen from the camera, so the far side recedes at any zoom.
+pub const FOG_DENSITY: f32 = 0.35;
+pub const FOG_START: f32 = -0.7;
+pub const FOG_END: f32 = 0.7;
+/// Start with the floor grid and the cube's shadow drawn (toggled with
+/// `Ctrl+V`). The floor hangs `GROUND_GAP` below the cube and reaches
+/// `GROUND_EXTENT` from its middle, in squares of `GROUND_GRID`;
+/// `SHADOW_SOFTNESS` widens the light into a cone to blur the shadow's edge.
+pub const GROUND_PLANE: bool = false;
+pub const GROUND_GAP: f32 = 0.35;
+pub const GROUND_EXTENT: f32 = 2.4;
+pub const GROUND_GRID: f32 = 0.6;
+pub const SHADOW_SOFTNESS: f32 = 0.12;
+pub const GROUND_GLYPH: char = '.';
+pub const GROUND_COLOR: Color = Color::DarkGrey;
+/// Shadow glyphs from the faintest penumbra to full shadow.
+pub const SHADOW_GLYPHS: [char; 3] = ['░', '▒', '▓'];
+/// Wobble the camera briefly on scrambles and when zoom hits its limits.
+pub const CAMERA_SHAKE: bool = true;
+pub const SHAKE_DURATION: f32 = 0.35;
+pub const SHAKE_FREQUENCY: f32 = 9.0;
+pub const SHAKE_SCRAMBLE_STRENGTH: f32 = 0.06;
+pub const SHAKE_BUMP_STRENGTH: f32 = 0.025;
+/// Spin, confetti and a result panel when a scrambled cube is solved.
+pub const CELEBRATION: bool = true;
+pub const CELEBRATION_DURATION: f32 = 3.0;
+pub const CELEBRATION_SPIN_DURATION: f32 = 1.2;
+/// Exploded view: how far each cubie moves out, as a fraction of its
+/// distance from the middle, and how long the move takes.
+pub const EXPLODE_DISTANCE: f32 = 0.6;
+pub const EXPLODE_SECONDS: f32 = 0.5;
+pub const CONFETTI_PIECES: usize = 60;
+/// Fraction of the viewport left empty around the cube by zoom-to-fit.
+pub const FIT_MARGIN: f32 = 0.1;
+pub const HISTORY_PANEL_WIDTH: u16 = 14;
+/// Seconds a notice stays on the status line.
+pub const NOTICE_SECONDS: f32 = 4.0;
+/// Share of the width and height the corner views take up.
+pub const INSET_SCALE: f32 = 0.35;
+pub const SCRAMBLE_CARD_PATH: &str = "cubex-scramble.svg";
+pub const FRAME_EXPORT_STEM: &str = "cubex-frame";
+pub const CUBE_SVG_PATH: &str = "cubex-cube.svg";
+pub const CUBE_EXPLORER_PATH: &str = "cubex-position.txt";
+pub const TURNTABLE_DIR: &str = "cubex-turntable";
+/// Pixels per character cell for sixel graphics when the terminal doesn't
+/// report its size in pixels.
+pub const SIXEL_CELL_PIXELS: (usize, usize) = (10, 20);
+/// `Ctrl+K` records to this stem plus `.cast` or `.gif`.
+pub const RECORDING_STEM: &str = "cubex-session";
+/// Pixels per terminal cell, across and down, in recorded GIFs.
+pub const RECORD_GIF_CELL: (usize, usize) = (4, 8);
+pub const TURNTABLE_SECONDS: f32 = 6.0;
+pub const LEADERBOARD_FILE: &str = "leaderboard.tsv";
+/// Read from the data directory when `--macros` is not given.
+pub const MACROS_FILE: &str = "macros.toml";
+/// Solver tables cached under the data directory after their first build.
+pub const TABLE_CACHE_DIR: &str = "tables";
+/// Longest solution `Ctrl+S` searches for before giving up.
+pub const SOLVER_MAX_MOVES: usize = 22;
+/// Seconds between moves when `:` plays a solution back to solved.
+pub const AUTOSOLVE_MOVE_SECONDS: f32 = 0.25;
+/// How long to wait for the terminal to answer a clipboard read (OSC 52)
+/// before taking it as a refusal.
+pub const CLIPBOARD_REPLY_MILLIS: u64 = 500;
+pub const STARFIELD_STARS: usize = 90;
+pub const STARFIELD_SPEED: f32 = 0.01;
+pub const STARFIELD_SEED: u64 = 0x5747_4152;
+pub const GHOST_GLYPH: char = '•';
+/// Letters for the 24 corner and 24 edge sticker slots, four per face in U,
+/// L, F, R, B, D order, clockwise from the top-left; Speffz by default.
+pub const LETTER_SCHEME: [char; 24] = [
+    'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', 'S',
+    'T', 'U', 'V', 'W', 'X',
+];
+/// The one color every sticker shows while the BLD trainer hides them.
+pub const BLD_HIDDEN_COLOR: FaceColor = FaceColor::White;
+pub const SHADE_GAMMA: f32 = 1.8;
+/// Fixed fill glyph per face color, indexed by `FaceColor::index`
+/// (white, yellow, red, orange, blue, green); `None` keeps the shade ramp.
+pub const FACE_GLYPHS: [Option<char>; 6] = [None; 6];
+/// Dither between neighbouring shades with a Bayer matrix so brightness
+/// gradients don't band (turned off with `--no-dither`).
+pub const DITHER_SHADES: bool = true;
+pub const TOON_BANDS: usize = 3;
+pub const TOON_OUTLINE_GLYPH: char = '#';
+pub const TOON_OUTLINE_COLOR: Color = Color::DarkGrey;
+/// Draw the dark cube body behind the stickers, in the theme's `body`
+/// color, or the faintest shade in monochrome.
+pub const CUBE_BODY: bool = true;
+pub const BODY_GLYPH: char = '█';
+/// Start with sticker borders on (toggled with `Ctrl+E`).
+pub const STICKER_BORDERS: bool = false;
+/// Colors the terminal is assumed to support; `None` detects them from
+/// `COLORTERM` and `TERM`. RGB colors are approximated below truecolor.
+pub const COLOR_DEPTH: Option<ColorDepth> = None;
+/// Default shade ramp; the active theme may replace it.
 pub const ASCII_SHADES: &[char; 10] = &[' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
 
 pub fn frame_duration() -> Duration {
     Duration::from_micros(1_000_000 / TARGET_FPS)
 }
 
+pub fn slowest_frame_duration() -> Duration {
+    Duration::from_micros(1_000_000 / MIN_FPS)
+}
+
 pub fn input_poll_timeout() -> Duration {
     Duration::from_millis(0)
 }
 
+pub fn data_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cubex"))
+}
+
 pub fn face_color_to_ansi(color: FaceColor) -> Color {
+    theme::active().face(color)
+}
+
+/// Uses the active theme's color when it is an exact RGB value, otherwise
+/// a print-friendly default.
+pub fn face_color_to_hex(color: FaceColor) -> String {
+    let [r, g, b] = face_color_to_rgb(color);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Like `face_color_to_hex`, as channel values.
+pub fn face_color_to_rgb(color: FaceColor) -> [u8; 3] {
+    if let Color::Rgb { r, g, b } = theme::active().face(color) {
+        return [r, g, b];
+    }
     match color {
-        FaceColor::White => Color::White,
-        FaceColor::Yellow => Color::Yellow,
-        FaceColor::Red => Color::Red,
-        FaceColor::Orange => Color::Rgb {
-            r: 255,
-            g: 140,
-            b: 0,
-        },
-        FaceColor::Blue => Color::Blue,
-        FaceColor::Green => Color::Green,
+        FaceColor::White => [0xff, 0xff, 0xff],
+        FaceColor::Yellow => [0xff, 0xd5, 0x00],
+        FaceColor::Red => [0xc4, 0x1e, 0x3a],
+        FaceColor::Orange => [0xff, 0x8c, 0x00],
+        FaceColor::Blue => [0x00, 0x51, 0xba],
+        FaceColor::Green => [0x00, 0x9e, 0x60],
     }
 }
@@ -1,8 +1,12 @@
 use once_cell::sync::Lazy;
-use rand::Rng;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 use std::collections::HashMap;
 
+use crate::config;
+use crate::solver;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Axis {
     X,
@@ -71,15 +75,47 @@ pub enum Face {
     Back,
 }
 
+/// Which color each face has when solved, in `Face::all` order (U, D, R,
+/// L, F, B). Opposite faces must get opposite colors.
+pub type ColorScheme = [FaceColor; 6];
+
+/// White top, green front, red right.
+pub const WESTERN_SCHEME: ColorScheme = [
+    FaceColor::White,
+    FaceColor::Yellow,
+    FaceColor::Red,
+    FaceColor::Orange,
+    FaceColor::Green,
+    FaceColor::Blue,
+];
+
+/// The western scheme with blue and yellow swapped: white opposite blue.
+#[cfg_attr(not(test), allow(dead_code))]
+pub const JAPANESE_SCHEME: ColorScheme = [
+    FaceColor::White,
+    FaceColor::Blue,
+    FaceColor::Red,
+    FaceColor::Orange,
+    FaceColor::Green,
+    FaceColor::Yellow,
+];
+
 impl Face {
+    /// The face's color on a solved cube under `config::COLOR_SCHEME`.
     pub fn default_color(self) -> FaceColor {
+        config::COLOR_SCHEME[self as usize]
+    }
+
+    /// Column and row of the face in the cross-shaped net: U above F, with
+    /// L, F, R, B in a row and D below.
+    pub fn net_slot(self) -> (usize, usize) {
         match self {
-            Face::Up => FaceColor::White,
-            Face::Down => FaceColor::Yellow,
-            Face::Right => FaceColor::Red,
-            Face::Left => FaceColor::Orange,
-            Face::Front => FaceColor::Green,
-            Face::Back => FaceColor::Blue,
+            Face::Up => (1, 0),
+            Face::Left => (0, 1),
+            Face::Front => (1, 1),
+            Face::Right => (2, 1),
+            Face::Back => (3, 1),
+            Face::Down => (1, 2),
         }
     }
 
@@ -124,6 +160,24 @@ impl Face {
         }
     }
 
+    pub fn from_letter(letter: char) -> Option<Face> {
+        Face::all()
+            .iter()
+            .copied()
+            .find(|face| face.letter() == letter)
+    }
+
+    pub fn letter(self) -> char {
+        match self {
+            Face::Up => 'U',
+            Face::Down => 'D',
+            Face::Right => 'R',
+            Face::Left => 'L',
+            Face::Front => 'F',
+            Face::Back => 'B',
+        }
+    }
+
     pub fn all() -> &'static [Face] {
         static FACES: [Face; 6] = [
             Face::Up,
@@ -147,6 +201,35 @@ pub enum FaceColor {
     Green,
 }
 
+impl FaceColor {
+    pub fn from_letter(letter: char) -> Option<FaceColor> {
+        match letter.to_ascii_uppercase() {
+            'W' => Some(FaceColor::White),
+            'Y' => Some(FaceColor::Yellow),
+            'R' => Some(FaceColor::Red),
+            'O' => Some(FaceColor::Orange),
+            'B' => Some(FaceColor::Blue),
+            'G' => Some(FaceColor::Green),
+            _ => None,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FaceColor::White => "white",
+            FaceColor::Yellow => "yellow",
+            FaceColor::Red => "red",
+            FaceColor::Orange => "orange",
+            FaceColor::Blue => "blue",
+            FaceColor::Green => "green",
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct FaceSpec {
     pub face: Face,
@@ -169,8 +252,10 @@ pub struct FaceletKey {
     pub face: Face,
 }
 
+pub const FACELET_COUNT: usize = 54;
+
 static FACELETS: Lazy<Vec<FaceletDescriptor>> = Lazy::new(|| {
-    let mut output = Vec::with_capacity(54);
+    let mut output = Vec::with_capacity(FACELET_COUNT);
     for face in Face::all() {
         let spec = face.spec();
         for row in 0..3 {
@@ -219,6 +304,33 @@ pub fn facelet_descriptors() -> &'static [FaceletDescriptor] {
     &FACELETS
 }
 
+/// Names the piece a facelet belongs to in the usual U/D, F/B, R/L order,
+/// e.g. "UFR corner", "FR edge" or "U center".
+pub fn piece_name(index: usize) -> Option<String> {
+    let desc = facelet_descriptors().get(index)?;
+    let faces: String = [
+        Face::Up,
+        Face::Down,
+        Face::Front,
+        Face::Back,
+        Face::Right,
+        Face::Left,
+    ]
+    .iter()
+    .filter(|face| {
+        let normal = face.spec().normal;
+        desc.coord.component(normal.axis) == normal.dir
+    })
+    .map(|face| face.letter())
+    .collect();
+    let kind = match faces.len() {
+        1 => "center",
+        2 => "edge",
+        _ => "corner",
+    };
+    Some(format!("{faces} {kind}"))
+}
+
 pub fn facelet_index(coord: LatticePoint, face: Face) -> usize {
     FACELET_INDEX
         .get(&FaceletKey { coord, face })
@@ -226,18 +338,20 @@ pub fn facelet_index(coord: LatticePoint, face: Face) -> usize {
         .expect("invalid facelet lookup")
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum RotationDir {
     Clockwise,
     CounterClockwise,
 }
 
+/// A move as a quarter-turn count of one layer (-1, 0 or 1 along `axis`),
+/// with `dir` seen from the positive end of the axis.
 #[derive(Clone, Copy, Debug)]
-struct MoveDef {
-    axis: Axis,
-    layer: i8,
-    dir: RotationDir,
-    turns: u8,
+pub struct MoveDef {
+    pub axis: Axis,
+    pub layer: i8,
+    pub dir: RotationDir,
+    pub turns: u8,
 }
 // she clone on my debug till i partialeq
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -260,11 +374,20 @@ pub enum Move {
     B,
     BPrime,
     B2,
+    M,
+    MPrime,
+    M2,
+    E,
+    EPrime,
+    E2,
+    S,
+    SPrime,
+    S2,
 }
 
 impl Move {
     pub fn all() -> &'static [Move] {
-        static MOVES: [Move; 18] = [
+        static MOVES: [Move; 27] = [
             Move::U,
             Move::UPrime,
             Move::U2,
@@ -283,11 +406,117 @@ impl Move {
             Move::B,
             Move::BPrime,
             Move::B2,
+            Move::M,
+            Move::MPrime,
+            Move::M2,
+            Move::E,
+            Move::EPrime,
+            Move::E2,
+            Move::S,
+            Move::SPrime,
+            Move::S2,
         ];
         &MOVES
     }
+
+    /// The move that undoes this one: primes swap with plain turns and
+    /// half turns undo themselves.
+    pub fn inverse(self) -> Move {
+        let index = self as usize;
+        let offset = match index % 3 {
+            0 => 1,
+            1 => 0,
+            _ => 2,
+        };
+        Move::all()[index - index % 3 + offset]
+    }
+
+    /// This move reflected through the middle plane across `axis`: layers
+    /// on `axis` swap ends and keep their turning sense about the axis,
+    /// turns about the other axes reverse. Across X, `R` becomes `L'`.
+    pub fn mirrored(self, axis: Axis) -> Move {
+        let def = self.def();
+        let (layer, dir) = if def.axis == axis {
+            (-def.layer, def.dir)
+        } else {
+            let dir = match def.dir {
+                RotationDir::Clockwise => RotationDir::CounterClockwise,
+                RotationDir::CounterClockwise => RotationDir::Clockwise,
+            };
+            (def.layer, dir)
+        };
+        *Move::all()
+            .iter()
+            .find(|mv| {
+                let other = mv.def();
+                other.axis == def.axis
+                    && other.layer == layer
+                    && other.turns == def.turns
+                    && (def.turns == 2 || other.dir == dir)
+            })
+            .expect("every layer has all three turns")
+    }
+
+    /// Middle-layer turns: M follows L, E follows D and S follows F.
+    /// Clockwise quarter turns about the positive end of the axis, 0 to 3.
+    fn quarter_turns(self) -> u8 {
+        let def = self.def();
+        match def.dir {
+            RotationDir::Clockwise => def.turns % 4,
+            RotationDir::CounterClockwise => (4 - def.turns % 4) % 4,
+        }
+    }
+
+    pub fn is_slice(self) -> bool {
+        self.def().layer == 0
+    }
+
+    /// Every facelet in the layer this move turns, including the face's
+    /// own nine for outer turns.
+    pub fn facelets(self) -> Vec<usize> {
+        let def = self.def();
+        FACELETS
+            .iter()
+            .enumerate()
+            .filter(|(_, desc)| desc.coord.component(def.axis) == def.layer)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    pub fn notation(self) -> &'static str {
+        use Move::*;
+        match self {
+            U => "U",
+            UPrime => "U'",
+            U2 => "U2",
+            D => "D",
+            DPrime => "D'",
+            D2 => "D2",
+            R => "R",
+            RPrime => "R'",
+            R2 => "R2",
+            L => "L",
+            LPrime => "L'",
+            L2 => "L2",
+            F => "F",
+            FPrime => "F'",
+            F2 => "F2",
+            B => "B",
+            BPrime => "B'",
+            B2 => "B2",
+            M => "M",
+            MPrime => "M'",
+            M2 => "M2",
+            E => "E",
+            EPrime => "E'",
+            E2 => "E2",
+            S => "S",
+            SPrime => "S'",
+            S2 => "S2",
+        }
+    }
     // she move on my self till i def
-    fn def(self) -> MoveDef {
+    pub fn def(self) -> MoveDef {
         use Move::*;
         match self {
             U => MoveDef {
@@ -398,31 +627,397 @@ impl Move {
                 dir: RotationDir::CounterClockwise,
                 turns: 2,
             },
+            M => MoveDef {
+                axis: Axis::X,
+                layer: 0,
+                dir: RotationDir::CounterClockwise,
+                turns: 1,
+            },
+            MPrime => MoveDef {
+                axis: Axis::X,
+                layer: 0,
+                dir: RotationDir::Clockwise,
+                turns: 1,
+            },
+            M2 => MoveDef {
+                axis: Axis::X,
+                layer: 0,
+                dir: RotationDir::CounterClockwise,
+                turns: 2,
+            },
+            E => MoveDef {
+                axis: Axis::Y,
+                layer: 0,
+                dir: RotationDir::CounterClockwise,
+                turns: 1,
+            },
+            EPrime => MoveDef {
+                axis: Axis::Y,
+                layer: 0,
+                dir: RotationDir::Clockwise,
+                turns: 1,
+            },
+            E2 => MoveDef {
+                axis: Axis::Y,
+                layer: 0,
+                dir: RotationDir::CounterClockwise,
+                turns: 2,
+            },
+            S => MoveDef {
+                axis: Axis::Z,
+                layer: 0,
+                dir: RotationDir::Clockwise,
+                turns: 1,
+            },
+            SPrime => MoveDef {
+                axis: Axis::Z,
+                layer: 0,
+                dir: RotationDir::CounterClockwise,
+                turns: 1,
+            },
+            S2 => MoveDef {
+                axis: Axis::Z,
+                layer: 0,
+                dir: RotationDir::Clockwise,
+                turns: 2,
+            },
+        }
+    }
+}
+
+/// Looks up a move by face letter (`U`, `D`, `R`, `L`, `F`, `B`, `M`, `E`,
+/// `S`) and modifier; `double` wins over `prime`.
+pub fn move_for(letter: char, prime: bool, double: bool) -> Option<Move> {
+    let base = match letter {
+        'U' => 0,
+        'D' => 1,
+        'R' => 2,
+        'L' => 3,
+        'F' => 4,
+        'B' => 5,
+        'M' => 6,
+        'E' => 7,
+        'S' => 8,
+        _ => return None,
+    };
+    let offset = match (prime, double) {
+        (_, true) => 2,
+        (true, false) => 1,
+        (false, false) => 0,
+    };
+    Move::all().get(base * 3 + offset).copied()
+}
+
+/// Why `parse_algorithm` rejected its input. `column` counts characters
+/// from 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub column: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "column {}: {}", self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Most times a parenthesized group can be repeated.
+const MAX_REPEAT: usize = 99;
+
+/// Parses standard notation such as `R U R' U2 F'`. Moves may be separated
+/// by any whitespace or written back to back (`RUR'`); `2'` is read as `2`
+/// and the typographic `’` as a prime. Parenthesized groups may be followed
+/// by a repeat count, so `(R U R' U')6` is the sexy move six times; groups
+/// nest.
+pub fn parse_algorithm(text: &str) -> Result<Vec<Move>, ParseError> {
+    let mut moves = Vec::new();
+    // Open groups: the column of each `(` and the moves read before it.
+    let mut groups: Vec<(usize, Vec<Move>)> = Vec::new();
+    let mut chars = text.chars().enumerate().peekable();
+    while let Some((index, ch)) = chars.next() {
+        if ch.is_whitespace() {
+            continue;
+        }
+        let error = |message: String| ParseError {
+            column: index + 1,
+            message,
+        };
+        if ch == '(' {
+            groups.push((index + 1, std::mem::take(&mut moves)));
+            continue;
+        }
+        if ch == ')' {
+            let Some((_, before)) = groups.pop() else {
+                return Err(error("')' without a matching '('".to_string()));
+            };
+            let mut count = String::new();
+            while let Some(&(_, digit)) = chars.peek()
+                && digit.is_ascii_digit()
+            {
+                count.push(digit);
+                chars.next();
+            }
+            let times = if count.is_empty() {
+                1
+            } else {
+                count
+                    .parse()
+                    .ok()
+                    .filter(|times| (1..=MAX_REPEAT).contains(times))
+                    .ok_or_else(|| {
+                        error(format!("repeat count must be between 1 and {MAX_REPEAT}"))
+                    })?
+            };
+            let group = std::mem::replace(&mut moves, before);
+            for _ in 0..times {
+                moves.extend_from_slice(&group);
+            }
+            continue;
+        }
+        if !Move::all().iter().any(|mv| mv.notation().starts_with(ch)) {
+            let hint = if ch.is_ascii_lowercase() {
+                " (face letters are uppercase)"
+            } else {
+                ""
+            };
+            return Err(error(format!("expected a move, found '{ch}'{hint}")));
+        }
+        let mut prime = false;
+        let mut double = false;
+        while let Some(&(_, modifier)) = chars.peek() {
+            match modifier {
+                '\'' | '’' if !prime => prime = true,
+                '2' if !double && !prime => double = true,
+                '\'' | '’' | '2' => {
+                    return Err(error(format!("too many modifiers after '{ch}'")));
+                }
+                _ => break,
+            }
+            chars.next();
+        }
+        moves.push(move_for(ch, prime, double).expect("letter checked above"));
+    }
+    if let Some((column, _)) = groups.pop() {
+        return Err(ParseError {
+            column,
+            message: "'(' is never closed".to_string(),
+        });
+    }
+    Ok(moves)
+}
+
+impl std::fmt::Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.notation())
+    }
+}
+
+/// Space-separated standard notation, the inverse of `parse_algorithm`.
+pub fn format_sequence(moves: &[Move]) -> String {
+    moves
+        .iter()
+        .map(|mv| mv.notation())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The sequence that undoes `moves`: each move inverted, in reverse order.
+pub fn invert(moves: &[Move]) -> Vec<Move> {
+    moves.iter().rev().map(|mv| mv.inverse()).collect()
+}
+
+/// `moves` with redundant turns cleaned up: turns of the same layer merge
+/// (`U U` becomes `U2`, `R R'` drops out), including across turns of other
+/// layers on the same axis, which commute (`R L R` becomes `R2 L`).
+pub fn simplify(moves: &[Move]) -> Vec<Move> {
+    let mut simplified: Vec<Move> = Vec::with_capacity(moves.len());
+    for &mv in moves {
+        let def = mv.def();
+        let commuting = simplified
+            .iter()
+            .rev()
+            .take_while(|prev| prev.def().axis == def.axis)
+            .count();
+        let start = simplified.len() - commuting;
+        match (start..simplified.len()).find(|i| simplified[*i].def().layer == def.layer) {
+            Some(i) => {
+                let quarters = (simplified[i].quarter_turns() + mv.quarter_turns()) % 4;
+                match Move::all().iter().find(|other| {
+                    let other_def = other.def();
+                    other_def.axis == def.axis
+                        && other_def.layer == def.layer
+                        && other.quarter_turns() == quarters
+                }) {
+                    Some(merged) => simplified[i] = *merged,
+                    None => {
+                        simplified.remove(i);
+                    }
+                }
+            }
+            None => simplified.push(mv),
+        }
+    }
+    simplified
+}
+
+/// `moves` reflected across `axis`, e.g. a right-hand algorithm turned into
+/// its left-hand version across X.
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn mirror(moves: &[Move], axis: Axis) -> Vec<Move> {
+    moves.iter().map(|mv| mv.mirrored(axis)).collect()
+}
+
+impl std::str::FromStr for Move {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse_algorithm(s)?.as_slice() {
+            [mv] => Ok(*mv),
+            moves => Err(ParseError {
+                column: 1,
+                message: format!("expected a single move, found {}", moves.len()),
+            }),
         }
     }
 }
 
-#[derive(Clone, Debug)]
+/// Named patterns and the algorithms that make them from a solved cube.
+pub const PATTERNS: [(&str, &str); 7] = [
+    ("checkerboard", "R2 L2 U2 D2 F2 B2"),
+    (
+        "superflip",
+        "U R2 F B R B2 R U2 L B2 R U' D' R2 F R' L B2 U2 F2",
+    ),
+    ("cube-in-cube", "F L F U' R U F2 L2 U' L' B D' B' L2 U"),
+    ("six-spot", "U D' R L' F B' U D'"),
+    ("four-spot", "F2 B2 U D' R2 L2 U D'"),
+    ("tetris", "L R F B U' D' L' R'"),
+    ("dots", "E S E' S'"),
+];
+
+/// The algorithm for pattern `name`.
+pub fn pattern(name: &str) -> Option<Vec<Move>> {
+    PATTERNS
+        .iter()
+        .find(|(pattern, _)| *pattern == name)
+        .map(|(_, alg)| parse_algorithm(alg).expect("built-in patterns parse"))
+}
+
+/// Name of the pattern for `day`, counted in days since the Unix epoch:
+/// the same all day, and drawn at random so the days don't just cycle.
+pub fn pattern_of_the_day(day: u64) -> &'static str {
+    let mut rng = StdRng::seed_from_u64(day);
+    PATTERNS.choose(&mut rng).expect("patterns not empty").0
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScrambleStyle {
+    Standard,
+    WithSlices,
+    NoD,
+    Ru,
+    Mu,
+    LastSlot,
+    LastLayer,
+    RandomState,
+}
+
+impl ScrambleStyle {
+    pub fn label(self) -> &'static str {
+        match self {
+            ScrambleStyle::Standard => "standard",
+            ScrambleStyle::WithSlices => "with slices",
+            ScrambleStyle::NoD => "no D",
+            ScrambleStyle::Ru => "<R,U>",
+            ScrambleStyle::Mu => "<M,U>",
+            ScrambleStyle::LastSlot => "last slot + LL",
+            ScrambleStyle::LastLayer => "random LL",
+            ScrambleStyle::RandomState => "random state",
+        }
+    }
+
+    /// How many moves a scramble asked to be `requested` long really
+    /// has: last-slot scrambles come in whole four-move inserts, and the
+    /// solver-built styles pick their own length (`None`).
+    pub fn length(self, requested: usize) -> Option<usize> {
+        match self {
+            ScrambleStyle::LastSlot => Some(requested.div_ceil(4) * 4),
+            ScrambleStyle::LastLayer | ScrambleStyle::RandomState => None,
+            _ => Some(requested),
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            ScrambleStyle::Standard => ScrambleStyle::WithSlices,
+            ScrambleStyle::WithSlices => ScrambleStyle::NoD,
+            ScrambleStyle::NoD => ScrambleStyle::Ru,
+            ScrambleStyle::Ru => ScrambleStyle::Mu,
+            ScrambleStyle::Mu => ScrambleStyle::LastSlot,
+            ScrambleStyle::LastSlot => ScrambleStyle::LastLayer,
+            ScrambleStyle::LastLayer => ScrambleStyle::RandomState,
+            ScrambleStyle::RandomState => ScrambleStyle::Standard,
+        }
+    }
+
+    fn moves(self) -> Vec<Move> {
+        use Move::*;
+        Move::all()
+            .iter()
+            .copied()
+            .filter(|mv| match self {
+                ScrambleStyle::WithSlices => true,
+                ScrambleStyle::Standard
+                | ScrambleStyle::LastSlot
+                | ScrambleStyle::LastLayer
+                | ScrambleStyle::RandomState => !mv.is_slice(),
+                ScrambleStyle::NoD => !mv.is_slice() && !matches!(mv, D | DPrime | D2),
+                ScrambleStyle::Ru => matches!(mv, R | RPrime | R2 | U | UPrime | U2),
+                ScrambleStyle::Mu => matches!(mv, M | MPrime | M2 | U | UPrime | U2),
+            })
+            .collect()
+    }
+}
+
+/// Face order of the facelet string format. Each face's `spec` already
+/// matches that format's reading direction.
+const KOCIEMBA_ORDER: [Face; 6] = [
+    Face::Up,
+    Face::Right,
+    Face::Front,
+    Face::Down,
+    Face::Left,
+    Face::Back,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Cube {
-    stickers: Vec<FaceColor>,
+    stickers: [FaceColor; FACELET_COUNT],
 }
 
 impl Cube {
     pub fn new() -> Self {
-        let stickers = FACELETS
-            .iter()
-            .map(|desc| desc.face.default_color())
-            .collect();
+        let mut stickers = [FaceColor::White; FACELET_COUNT];
+        for (idx, desc) in FACELETS.iter().enumerate() {
+            stickers[idx] = desc.face.default_color();
+        }
         Self { stickers }
     }
 
+    /// A cube showing `colors`, in `face_colors` order. Nothing is checked.
+    pub fn from_colors(colors: [FaceColor; FACELET_COUNT]) -> Self {
+        Self { stickers: colors }
+    }
+
     pub fn reset(&mut self) {
         for (idx, desc) in FACELETS.iter().enumerate() {
             self.stickers[idx] = desc.face.default_color();
         }
     }
 
-    #[cfg_attr(not(test), allow(dead_code))]
     pub fn is_solved(&self) -> bool {
         for face in Face::all() {
             let reference = face.default_color();
@@ -443,11 +1038,66 @@ impl Cube {
         }
     }
 
-    pub fn scramble<R: Rng + ?Sized>(&mut self, len: usize, rng: &mut R) {
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn scramble<R: Rng + ?Sized>(&mut self, len: usize, rng: &mut R) -> Vec<Move> {
+        self.scramble_from(Move::all(), len, rng)
+    }
+
+    /// Scrambles in `style`, returning the sequence applied. Random-state
+    /// scrambles ignore `len` and are as long as the solver makes them.
+    pub fn scramble_with<R: Rng + ?Sized>(
+        &mut self,
+        style: ScrambleStyle,
+        len: usize,
+        rng: &mut R,
+    ) -> Vec<Move> {
+        match style {
+            ScrambleStyle::LastSlot => self.scramble_last_slot(len, rng),
+            ScrambleStyle::RandomState => {
+                let sequence = solver::random_state_scramble(rng, config::SOLVER_MAX_MOVES);
+                self.apply_sequence(&sequence);
+                sequence
+            }
+            ScrambleStyle::LastLayer => {
+                let sequence = solver::random_last_layer_scramble(rng, config::SOLVER_MAX_MOVES);
+                self.apply_sequence(&sequence);
+                sequence
+            }
+            _ => self.scramble_from(&style.moves(), len, rng),
+        }
+    }
+
+    /// Scrambles only the FR slot and the last layer by chaining random AUFs
+    /// with `R U* R'` / `F' U* F` inserts, each of which keeps the cross and
+    /// the other three slots intact. `len` is rounded up to whole inserts.
+    fn scramble_last_slot<R: Rng + ?Sized>(&mut self, len: usize, rng: &mut R) -> Vec<Move> {
+        const AUFS: [Move; 3] = [Move::U, Move::UPrime, Move::U2];
+        const INSERTS: [(Move, Move); 2] = [(Move::R, Move::RPrime), (Move::FPrime, Move::F)];
+        let mut sequence = Vec::with_capacity(len + 4);
+        while sequence.len() < len {
+            let (open, close) = *INSERTS.choose(rng).expect("inserts not empty");
+            sequence.push(*AUFS.choose(rng).expect("aufs not empty"));
+            sequence.push(open);
+            sequence.push(*AUFS.choose(rng).expect("aufs not empty"));
+            sequence.push(close);
+        }
+        self.apply_sequence(&sequence);
+        sequence
+    }
+
+    /// Random-move scramble drawn from `moves`, never turning the same axis
+    /// twice in a row. `moves` must span at least two axes.
+    pub fn scramble_from<R: Rng + ?Sized>(
+        &mut self,
+        moves: &[Move],
+        len: usize,
+        rng: &mut R,
+    ) -> Vec<Move> {
         let mut last_axis: Option<Axis> = None;
+        let mut sequence = Vec::with_capacity(len);
         for _ in 0..len {
             let choice = loop {
-                let mv = *Move::all().choose(rng).expect("moves list not empty");
+                let mv = *moves.choose(rng).expect("moves list not empty");
                 let axis = mv.def().axis;
                 if Some(axis) != last_axis {
                     break mv;
@@ -455,16 +1105,187 @@ impl Cube {
             };
             last_axis = Some(choice.def().axis);
             self.apply_move(choice);
+            sequence.push(choice);
+        }
+        sequence
+    }
+
+    /// Turns the whole cube a quarter turn about `axis`, as if all three
+    /// layers turned together (`x` is `Axis::X` clockwise).
+    pub fn rotate(&mut self, axis: Axis, dir: RotationDir) {
+        for layer in -1..=1 {
+            self.rotate_layer(axis, layer, dir);
+        }
+    }
+
+    /// Rotates the whole cube so each center is back on the face whose
+    /// solved color it has, e.g. white up and green front under the
+    /// western scheme. Returns whether anything moved.
+    pub fn normalize_orientation(&mut self) -> bool {
+        let upright = |cube: &Cube| {
+            [Face::Up, Face::Front]
+                .iter()
+                .all(|face| cube.center_color(*face) == face.default_color())
+        };
+        if upright(self) {
+            return false;
+        }
+        for x in 0..4 {
+            for y in 0..4 {
+                for z in 0..4 {
+                    let mut turned = *self;
+                    for (axis, turns) in [(Axis::X, x), (Axis::Y, y), (Axis::Z, z)] {
+                        for _ in 0..turns {
+                            turned.rotate(axis, RotationDir::Clockwise);
+                        }
+                    }
+                    if upright(&turned) {
+                        *self = turned;
+                        return true;
+                    }
+                }
+            }
+        }
+        unreachable!("some rotation brings every center home")
+    }
+
+    pub fn apply_sequence(&mut self, moves: &[Move]) {
+        for mv in moves {
+            self.apply_move(*mv);
         }
     }
 
     pub fn face_colors(&self) -> &[FaceColor] {
         &self.stickers
     }
+
+    /// Indices of the facelets whose color differs from `other`'s.
+    pub fn differing_facelets(&self, other: &Cube) -> Vec<usize> {
+        (0..FACELET_COUNT)
+            .filter(|&index| self.stickers[index] != other.stickers[index])
+            .collect()
+    }
+
+    pub fn center_color(&self, face: Face) -> FaceColor {
+        let coord = coord_for(&face.spec(), 1, 1);
+        self.stickers[facelet_index(coord, face)]
+    }
+
+    /// The 54-character facelet string used by Kociemba-style solvers:
+    /// faces in URFDLB order, each read row by row as seen from outside
+    /// (U with B at the top, D with F at the top), every sticker named by
+    /// the face whose center shares its color.
+    pub fn to_facelet_string(self) -> String {
+        let letter_for = |color: FaceColor| {
+            Face::all()
+                .iter()
+                .find(|face| self.center_color(**face) == color)
+                .map_or('?', |face| face.letter())
+        };
+        KOCIEMBA_ORDER
+            .iter()
+            .flat_map(|face| {
+                FACELETS
+                    .iter()
+                    .enumerate()
+                    .filter(move |(_, desc)| desc.face == *face)
+            })
+            .map(|(idx, _)| letter_for(self.stickers[idx]))
+            .collect()
+    }
+
+    /// Reads a facelet string as written by `to_facelet_string`. Checks the
+    /// length, that every face letter appears nine times, that the centers
+    /// are in URFDLB order and that every corner and edge shows colors one
+    /// real piece could have; twist, flip and parity are not checked.
+    pub fn from_facelet_string(text: &str) -> Result<Cube, String> {
+        let faces = text
+            .trim()
+            .chars()
+            .enumerate()
+            .map(|(i, letter)| {
+                Face::from_letter(letter).ok_or_else(|| {
+                    format!("facelet {}: '{letter}' is not one of U R F D L B", i + 1)
+                })
+            })
+            .collect::<Result<Vec<Face>, String>>()?;
+        if faces.len() != FACELET_COUNT {
+            return Err(format!(
+                "expected {FACELET_COUNT} facelets, found {}",
+                faces.len()
+            ));
+        }
+        for face in Face::all() {
+            let count = faces.iter().filter(|f| *f == face).count();
+            if count != 9 {
+                return Err(format!(
+                    "expected 9 {} facelets, found {count}",
+                    face.letter()
+                ));
+            }
+        }
+        let mut cube = Cube::new();
+        for (n, face) in KOCIEMBA_ORDER.iter().enumerate() {
+            let center = faces[n * 9 + 4];
+            if center != *face {
+                return Err(format!(
+                    "the {} center is {}, but centers must be in URFDLB order",
+                    face.letter(),
+                    center.letter()
+                ));
+            }
+            let indices = FACELETS
+                .iter()
+                .enumerate()
+                .filter(|(_, desc)| desc.face == *face)
+                .map(|(idx, _)| idx);
+            for (idx, sticker) in indices.zip(&faces[n * 9..]) {
+                cube.stickers[idx] = sticker.default_color();
+            }
+        }
+        for (idx, desc) in FACELETS.iter().enumerate() {
+            let clash = FACELETS.iter().enumerate().any(|(other, other_desc)| {
+                other != idx
+                    && other_desc.coord == desc.coord
+                    && color_axis(cube.stickers[other]) == color_axis(cube.stickers[idx])
+            });
+            if clash {
+                let piece = piece_name(idx).unwrap_or_default();
+                return Err(format!("the {piece} has an impossible color combination"));
+            }
+        }
+        Ok(cube)
+    }
+
+    /// Whether every facelet selected by `filter` matches its face's center.
+    pub fn facelets_match_centers(&self, filter: impl Fn(&FaceletDescriptor) -> bool) -> bool {
+        FACELETS
+            .iter()
+            .enumerate()
+            .filter(|(_, desc)| filter(desc))
+            .all(|(idx, desc)| self.stickers[idx] == self.center_color(desc.face))
+    }
+}
+
+/// Axis of the face a color belongs to; one piece never shows two colors
+/// from the same axis.
+fn color_axis(color: FaceColor) -> Axis {
+    Face::all()
+        .iter()
+        .find(|face| face.default_color() == color)
+        .expect("every color has a face")
+        .spec()
+        .normal
+        .axis
 }
 
-fn rotate_layer(stickers: &mut [FaceColor], axis: Axis, layer: i8, dir: RotationDir) {
-    let mut updated = stickers.to_vec();
+fn rotate_layer(
+    stickers: &mut [FaceColor; FACELET_COUNT],
+    axis: Axis,
+    layer: i8,
+    dir: RotationDir,
+) {
+    let mut updated = *stickers;
     for (idx, desc) in FACELETS.iter().enumerate() {
         if desc.coord.component(axis) == layer {
             let new_coord = rotate_coord(desc.coord, axis, dir);
@@ -473,7 +1294,7 @@ fn rotate_layer(stickers: &mut [FaceColor], axis: Axis, layer: i8, dir: Rotation
             updated[target] = stickers[idx];
         }
     }
-    stickers.copy_from_slice(&updated);
+    *stickers = updated;
 }
 
 impl Cube {
@@ -482,7 +1303,7 @@ impl Cube {
     }
 }
 
-fn rotate_face(face: Face, axis: Axis, dir: RotationDir) -> Face {
+pub fn rotate_face(face: Face, axis: Axis, dir: RotationDir) -> Face {
     let normal = face_to_normal(face);
     let rotated_normal = rotate_vector(normal, axis, dir);
     face_from_normal(rotated_normal)
@@ -511,14 +1332,14 @@ fn face_from_normal(normal: LatticePoint) -> Face {
     }
 }
 
-fn rotate_coord(point: LatticePoint, axis: Axis, dir: RotationDir) -> LatticePoint {
+pub fn rotate_coord(point: LatticePoint, axis: Axis, dir: RotationDir) -> LatticePoint {
     match axis {
         Axis::X => {
             let (new_y, new_z) = rotate_pair(point.y, point.z, dir);
             LatticePoint::new(point.x, new_y, new_z)
         }
         Axis::Y => {
-            let (new_x, new_z) = rotate_pair(point.x, point.z, dir);
+            let (new_z, new_x) = rotate_pair(point.z, point.x, dir);
             LatticePoint::new(new_x, point.y, new_z)
         }
         Axis::Z => {
@@ -552,6 +1373,20 @@ mod tests {
         assert!(cube.is_solved());
     }
 
+    #[test]
+    fn every_inverse_undoes_its_move() {
+        for &mv in Move::all() {
+            let mut cube = Cube::new();
+            cube.apply_sequence(&[Move::R, Move::U, Move::F2]);
+            let before = cube;
+            cube.apply_move(mv);
+            cube.apply_move(mv.inverse());
+            assert_eq!(cube, before, "{mv}");
+        }
+        assert_eq!(Move::UPrime.inverse(), Move::U);
+        assert_eq!(Move::S2.inverse(), Move::S2);
+    }
+
     #[test]
     fn double_turn_equivalence() {
         let mut cube = Cube::new();
@@ -562,6 +1397,18 @@ mod tests {
         assert_eq!(cube.face_colors(), other.face_colors());
     }
 
+    #[test]
+    fn last_slot_scramble_keeps_first_three_slots() {
+        let mut cube = Cube::new();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let sequence = cube.scramble_with(ScrambleStyle::LastSlot, 30, &mut rng);
+        assert_eq!(Some(sequence.len()), ScrambleStyle::LastSlot.length(30));
+        assert!(!cube.is_solved());
+        assert!(cube.facelets_match_centers(|desc| {
+            desc.coord.y <= 0 && !(desc.coord.x == 1 && desc.coord.z == 1)
+        }));
+    }
+
     #[test]
     fn scramble_changes_state() {
         let mut cube = Cube::new();
@@ -569,4 +1416,258 @@ mod tests {
         cube.scramble(20, &mut rng);
         assert!(!cube.is_solved());
     }
+
+    #[test]
+    fn piece_names_follow_standard_order() {
+        let corner = facelet_descriptors()
+            .iter()
+            .position(|d| d.face == Face::Right && d.row == 0 && d.col == 0)
+            .unwrap();
+        assert_eq!(piece_name(corner).as_deref(), Some("UFR corner"));
+        let center = facelet_descriptors()
+            .iter()
+            .position(|d| d.face == Face::Down && d.row == 1 && d.col == 1)
+            .unwrap();
+        assert_eq!(piece_name(center).as_deref(), Some("D center"));
+        assert_eq!(piece_name(FACELET_COUNT), None);
+    }
+
+    #[test]
+    fn slice_moves_turn_the_middle_layer() {
+        for mv in [Move::M, Move::E, Move::S] {
+            let mut cube = Cube::new();
+            cube.apply_sequence(&[mv, mv, mv, mv]);
+            assert!(cube.is_solved(), "{}", mv.notation());
+        }
+        let mut cube = Cube::new();
+        cube.apply_sequence(&[Move::M, Move::MPrime]);
+        assert!(cube.is_solved());
+
+        // M turns the front layer's middle column down like L does.
+        let mut cube = Cube::new();
+        cube.apply_move(Move::M);
+        let up_center = facelet_index(LatticePoint::new(0, 1, 0), Face::Up);
+        assert_eq!(cube.stickers[up_center], Face::Back.default_color());
+    }
+
+    #[test]
+    fn parse_algorithm_reads_standard_notation() {
+        use Move::*;
+        assert_eq!(
+            parse_algorithm("  R U R'\tU2 F’ M2'").unwrap(),
+            vec![R, U, RPrime, U2, FPrime, M2]
+        );
+        assert_eq!(parse_algorithm("RUR'").unwrap(), vec![R, U, RPrime]);
+        assert_eq!(parse_algorithm("").unwrap(), Vec::new());
+        assert_eq!("S'".parse::<Move>(), Ok(SPrime));
+
+        let err = parse_algorithm("R U x").unwrap_err();
+        assert_eq!(err.column, 5);
+        assert!(err.to_string().contains("'x'"), "{err}");
+        assert_eq!(parse_algorithm("R''").unwrap_err().column, 1);
+    }
+
+    #[test]
+    fn simplify_merges_and_cancels_turns() {
+        use Move::*;
+        assert_eq!(simplify(&[U, U]), vec![U2]);
+        assert_eq!(simplify(&[U2, U]), vec![UPrime]);
+        assert_eq!(simplify(&[D, DPrime]), Vec::new());
+        assert_eq!(simplify(&[D, D2, D]), Vec::new());
+        assert_eq!(simplify(&[U, D2, DPrime]), vec![U, D]);
+        assert_eq!(simplify(&[R, L, R]), vec![R2, L]);
+        assert_eq!(simplify(&[R, U, UPrime, RPrime]), Vec::new());
+        assert_eq!(simplify(&[R, MPrime, RPrime]), vec![MPrime]);
+        assert_eq!(simplify(&[R, U, D, UPrime, R]), vec![R, D, R]);
+
+        let moves = parse_algorithm("R L' R2 U D U' M2 R' L F2 F2").unwrap();
+        let (mut long, mut short) = (Cube::new(), Cube::new());
+        long.apply_sequence(&moves);
+        short.apply_sequence(&simplify(&moves));
+        assert_eq!(long, short);
+    }
+
+    #[test]
+    fn parse_algorithm_repeats_groups() {
+        use Move::*;
+        assert_eq!(
+            parse_algorithm("(R U)3 F").unwrap(),
+            vec![R, U, R, U, R, U, F]
+        );
+        assert_eq!(
+            parse_algorithm("((R U)2 D')2").unwrap(),
+            vec![R, U, R, U, DPrime, R, U, R, U, DPrime]
+        );
+        let mut cube = Cube::new();
+        cube.apply_sequence(&parse_algorithm("(R U R' U')6").unwrap());
+        assert!(cube.is_solved());
+        assert_eq!(parse_algorithm("(R U").unwrap_err().column, 1);
+        assert_eq!(parse_algorithm("R U)").unwrap_err().column, 4);
+        assert_eq!(parse_algorithm("(R)0").unwrap_err().column, 3);
+    }
+
+    #[test]
+    fn format_sequence_round_trips_through_the_parser() {
+        let moves = parse_algorithm("R U' F2 M E' S2").unwrap();
+        assert_eq!(format_sequence(&moves), "R U' F2 M E' S2");
+        assert_eq!(Move::BPrime.to_string(), "B'");
+        assert_eq!(format_sequence(&[]), "");
+    }
+
+    #[test]
+    fn facelet_string_follows_urfdlb_order() {
+        let mut cube = Cube::new();
+        assert_eq!(
+            cube.to_facelet_string(),
+            format!(
+                "{}{}{}{}{}{}",
+                "U".repeat(9),
+                "R".repeat(9),
+                "F".repeat(9),
+                "D".repeat(9),
+                "L".repeat(9),
+                "B".repeat(9)
+            )
+        );
+        cube.apply_move(Move::R);
+        assert_eq!(
+            cube.to_facelet_string(),
+            "UUFUUFUUFRRRRRRRRRFFDFFDFFDDDBDDBDDBLLLLLLLLLUBBUBBUBB"
+        );
+    }
+
+    #[test]
+    fn pattern_of_the_day_is_fixed_per_day() {
+        assert_eq!(pattern_of_the_day(20_000), pattern_of_the_day(20_000));
+        let week: Vec<&str> = (20_000..20_007).map(pattern_of_the_day).collect();
+        assert!(week.iter().all(|name| pattern(name).is_some()));
+        assert!(week.iter().any(|name| *name != week[0]));
+    }
+
+    #[test]
+    fn differing_facelets_are_the_moved_stickers() {
+        let mut cube = Cube::new();
+        assert!(cube.differing_facelets(&Cube::new()).is_empty());
+        cube.apply_move(Move::R);
+        let differing = cube.differing_facelets(&Cube::new());
+        // R moves twelve side stickers; the R face itself stays red.
+        assert_eq!(differing.len(), 12);
+        assert!(
+            differing
+                .iter()
+                .all(|index| Move::R.facelets().contains(index))
+        );
+    }
+
+    #[test]
+    fn move_facelets_cover_the_turning_layer() {
+        assert_eq!(Move::U.facelets().len(), 21);
+        assert_eq!(Move::M2.facelets().len(), 12);
+        let up: Vec<usize> = (0..FACELET_COUNT)
+            .filter(|&index| FACELETS[index].face == Face::Up)
+            .collect();
+        assert!(
+            up.iter()
+                .all(|index| Move::UPrime.facelets().contains(index))
+        );
+        assert!(!up.iter().any(|index| Move::D.facelets().contains(index)));
+    }
+
+    #[test]
+    fn u_turns_clockwise_seen_from_above() {
+        let mut cube = Cube::new();
+        cube.apply_move(Move::U);
+        assert_eq!(
+            cube.to_facelet_string(),
+            "UUUUUUUUUBBBRRRRRRRRRFFFFFFDDDDDDDDDFFFLLLLLLLLLBBBBBB"
+        );
+    }
+
+    #[test]
+    fn facelet_string_round_trips_and_rejects_bad_input() {
+        let mut cube = Cube::new();
+        cube.apply_sequence(&parse_algorithm("R U2 F' L D B2").unwrap());
+        let facelets = cube.to_facelet_string();
+        assert_eq!(Cube::from_facelet_string(&facelets), Ok(cube));
+
+        let err = |text: &str| Cube::from_facelet_string(text).unwrap_err();
+        assert!(err("UUU").contains("54"));
+        assert!(err(&facelets.replacen('U', "X", 1)).contains("'X'"));
+        assert!(err(&facelets.replacen('U', "R", 1)).contains("9 U"));
+        let swapped = |a: usize, b: usize| {
+            let mut letters: Vec<char> = Cube::new().to_facelet_string().chars().collect();
+            letters.swap(a, b);
+            letters.into_iter().collect::<String>()
+        };
+        assert!(err(&swapped(4, 13)).contains("center"));
+        // Trading the U sticker of UF for the B sticker of UB leaves two U
+        // colors on UB and F with B on UF.
+        assert_eq!(
+            err(&swapped(7, 46)),
+            "the UB edge has an impossible color combination"
+        );
+    }
+
+    #[test]
+    fn invert_and_mirror_transform_sequences() {
+        let sexy = parse_algorithm("R U R' U' M2 E").unwrap();
+        assert_eq!(format_sequence(&invert(&sexy)), "E' M2 U R U' R'");
+        let mut cube = Cube::new();
+        cube.apply_sequence(&sexy);
+        cube.apply_sequence(&invert(&sexy));
+        assert!(cube.is_solved());
+
+        assert_eq!(format_sequence(&mirror(&sexy, Axis::X)), "L' U' L U M2 E'");
+        assert_eq!(format_sequence(&mirror(&sexy, Axis::Y)), "R' D' R D M2 E");
+        assert_eq!(format_sequence(&mirror(&sexy, Axis::Z)), "R' U' R U M2 E'");
+        for &mv in Move::all() {
+            for axis in [Axis::X, Axis::Y, Axis::Z] {
+                assert_eq!(mv.mirrored(axis).mirrored(axis), mv, "{mv} {axis:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn color_schemes_use_every_color_once() {
+        for scheme in [WESTERN_SCHEME, JAPANESE_SCHEME, config::COLOR_SCHEME] {
+            for color in scheme {
+                assert_eq!(scheme.iter().filter(|c| **c == color).count(), 1);
+            }
+        }
+        for face in Face::all() {
+            assert_eq!(Cube::new().center_color(*face), face.default_color());
+        }
+    }
+
+    #[test]
+    fn normalize_orientation_undoes_whole_cube_turns() {
+        let mut cube = Cube::new();
+        cube.apply_move(Move::M);
+        assert!(cube.normalize_orientation());
+        let mut expected = Cube::new();
+        expected.apply_sequence(&[Move::R, Move::LPrime]);
+        assert_eq!(cube, expected);
+
+        let mut cube = Cube::new();
+        cube.apply_sequence(&[Move::R, Move::U]);
+        let before = cube;
+        cube.rotate(Axis::Z, RotationDir::CounterClockwise);
+        cube.rotate(Axis::Y, RotationDir::Clockwise);
+        assert!(cube.normalize_orientation());
+        assert_eq!(cube, before);
+        assert!(!cube.normalize_orientation());
+    }
+
+    #[test]
+    fn patterns_parse_and_leave_the_cube_scrambled() {
+        for (name, _) in PATTERNS {
+            let mut cube = Cube::new();
+            cube.apply_sequence(&pattern(name).unwrap());
+            assert!(!cube.is_solved(), "{name}");
+        }
+        let mut cube = Cube::new();
+        cube.apply_sequence(&pattern("checkerboard").unwrap());
+        assert_eq!(&cube.to_facelet_string()[..9], "UDUDUDUDU");
+        assert!(pattern("nonsense").is_none());
+    }
 }
@@ -0,0 +1,83 @@
+use std::fs;
+use std::path::Path;
+
+use crate::config;
+use crate::cube::{self, Cube, format_sequence};
+use crate::solver;
+
+/// A position as Cube Explorer saves it: a maneuver that makes it from a
+/// solved cube, followed by its length in face turns, e.g.
+/// `R U R' U' (4f)`. Found with the two-phase solver, so it is short but
+/// not always optimal, which Cube Explorer marks with a `*`.
+pub fn to_line(cube: &Cube) -> Result<String, String> {
+    let solution = solver::solve(cube, config::SOLVER_MAX_MOVES)?;
+    let generator = cube::simplify(&cube::invert(&solution));
+    Ok(format!(
+        "{} ({}f)",
+        format_sequence(&generator),
+        generator.len()
+    ))
+}
+
+pub fn write(path: &Path, cube: &Cube) -> Result<(), String> {
+    let line = to_line(cube)?;
+    fs::write(path, format!("{line}\n")).map_err(|err| err.to_string())
+}
+
+/// Reads the first maneuver in a Cube Explorer text file and applies it to
+/// a solved cube. Blank lines, `//` comments and the `(20f*)` style length
+/// at the end of a line are skipped.
+pub fn from_text(text: &str) -> Result<Cube, String> {
+    let line = text
+        .lines()
+        .map(|line| line.split("//").next().unwrap_or_default())
+        .map(strip_length)
+        .find(|line| !line.trim().is_empty())
+        .ok_or("no maneuver found")?;
+    let moves = cube::parse_algorithm(line).map_err(|err| err.to_string())?;
+    let mut cube = Cube::new();
+    cube.apply_sequence(&moves);
+    Ok(cube)
+}
+
+/// `line` without a trailing length such as `(18f)`, `(20q*)` or `(7s)`.
+fn strip_length(line: &str) -> &str {
+    let trimmed = line.trim_end();
+    let Some(open) = trimmed.rfind('(') else {
+        return line;
+    };
+    let Some(inner) = trimmed[open + 1..].strip_suffix(')') else {
+        return line;
+    };
+    let inner = inner.strip_suffix('*').unwrap_or(inner);
+    let is_length = inner
+        .strip_suffix(['f', 'q', 's'])
+        .is_some_and(|count| !count.is_empty() && count.chars().all(|ch| ch.is_ascii_digit()));
+    if is_length { &trimmed[..open] } else { line }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::Move;
+
+    #[test]
+    fn lines_round_trip() {
+        let mut cube = Cube::new();
+        cube.apply_sequence(&cube::parse_algorithm("R U R' U' F2 D").unwrap());
+        let line = to_line(&cube).unwrap();
+        assert!(line.ends_with("f)"), "{line}");
+        assert_eq!(from_text(&line), Ok(cube));
+    }
+
+    #[test]
+    fn reads_comments_lengths_and_groups() {
+        let text = "// saved by Cube Explorer\n\n(R U R' U')2 (8f*)  // twice\nU\n";
+        let mut cube = Cube::new();
+        cube.apply_sequence(&[Move::R, Move::U, Move::RPrime, Move::UPrime]);
+        cube.apply_sequence(&[Move::R, Move::U, Move::RPrime, Move::UPrime]);
+        assert_eq!(from_text(text), Ok(cube));
+        assert!(from_text("// nothing here\n").is_err());
+        assert!(from_text("R Q").unwrap_err().contains("'Q'"));
+    }
+}
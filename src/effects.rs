@@ -0,0 +1,177 @@
+use std::f32::consts::TAU;
+
+use crossterm::style::Color;
+use rand::Rng;
+
+use crate::config;
+use crate::cube::FaceColor;
+use crate::geometry::Camera;
+use crate::raster::Frame;
+use crate::theme;
+
+const CONFETTI_GLYPHS: [char; 5] = ['*', '+', 'o', '~', '°'];
+const CONFETTI_COLORS: [FaceColor; 6] = [
+    FaceColor::White,
+    FaceColor::Yellow,
+    FaceColor::Red,
+    FaceColor::Orange,
+    FaceColor::Blue,
+    FaceColor::Green,
+];
+
+/// A short decaying wobble of the camera, used as feedback for scrambles
+/// and for bumping into the zoom limits.
+pub struct CameraShake {
+    elapsed: f32,
+    strength: f32,
+}
+
+impl CameraShake {
+    pub fn new(strength: f32) -> Self {
+        Self {
+            elapsed: 0.0,
+            strength,
+        }
+    }
+
+    /// Advances the shake by `dt` seconds; returns false once it has died out.
+    pub fn update(&mut self, dt: f32) -> bool {
+        self.elapsed += dt;
+        self.elapsed < config::SHAKE_DURATION
+    }
+
+    /// A copy of `camera` nudged by the current wobble.
+    pub fn apply(&self, camera: &Camera) -> Camera {
+        let decay = 1.0 - (self.elapsed / config::SHAKE_DURATION).clamp(0.0, 1.0);
+        let amplitude = self.strength * decay * decay;
+        let phase = self.elapsed * config::SHAKE_FREQUENCY * TAU;
+        let mut shaken = *camera;
+        shaken.orbit(amplitude * phase.sin(), amplitude * (phase * 1.3).cos());
+        shaken
+    }
+}
+
+/// Pushes the cubies apart from the middle of the cube to show the pieces
+/// inside, and brings them back together when toggled again.
+pub struct Explosion {
+    /// 0 assembled, 1 fully apart.
+    progress: f32,
+    apart: bool,
+}
+
+impl Explosion {
+    pub fn new() -> Self {
+        Self {
+            progress: 0.0,
+            apart: true,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.apart = !self.apart;
+    }
+
+    /// Moves the cubies by `dt` seconds; returns false once they are back
+    /// together.
+    pub fn update(&mut self, dt: f32) -> bool {
+        let step = dt / config::EXPLODE_SECONDS;
+        self.progress = if self.apart {
+            (self.progress + step).min(1.0)
+        } else {
+            (self.progress - step).max(0.0)
+        };
+        self.apart || self.progress > 0.0
+    }
+
+    /// A copy of `camera` that sees the cubies as far apart as they are now,
+    /// eased in and out.
+    pub fn apply(&self, camera: &Camera) -> Camera {
+        let t = self.progress;
+        camera.exploded(t * t * (3.0 - 2.0 * t) * config::EXPLODE_DISTANCE)
+    }
+}
+
+struct Confetti {
+    x: f32,
+    y: f32,
+    speed: f32,
+    glyph: char,
+    color: Color,
+}
+
+/// Played when a tracked solve finishes: the cube spins once about the
+/// vertical axis while confetti falls over the frame, then a result panel
+/// stays up until the celebration ends.
+pub struct Celebration {
+    elapsed: f32,
+    moves: usize,
+    confetti: Vec<Confetti>,
+}
+
+impl Celebration {
+    pub fn new(moves: usize, rng: &mut impl Rng) -> Self {
+        let confetti = (0..config::CONFETTI_PIECES)
+            .map(|_| Confetti {
+                x: rng.gen_range(0.0..1.0),
+                y: rng.gen_range(-1.0..0.0),
+                speed: rng.gen_range(0.4..0.9),
+                glyph: CONFETTI_GLYPHS[rng.gen_range(0..CONFETTI_GLYPHS.len())],
+                color: config::face_color_to_ansi(
+                    CONFETTI_COLORS[rng.gen_range(0..CONFETTI_COLORS.len())],
+                ),
+            })
+            .collect();
+        Self {
+            elapsed: 0.0,
+            moves,
+            confetti,
+        }
+    }
+
+    /// Advances the celebration by `dt` seconds; returns false once it is over.
+    pub fn update(&mut self, dt: f32) -> bool {
+        self.elapsed += dt;
+        for piece in &mut self.confetti {
+            piece.y += piece.speed * dt;
+        }
+        self.elapsed < config::CELEBRATION_DURATION
+    }
+
+    /// A copy of `camera` turned by the spin so far, eased in and out.
+    pub fn apply(&self, camera: &Camera) -> Camera {
+        let t = (self.elapsed / config::CELEBRATION_SPIN_DURATION).clamp(0.0, 1.0);
+        let eased = t * t * (3.0 - 2.0 * t);
+        let mut spun = *camera;
+        spun.orbit(eased * TAU, 0.0);
+        spun
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let width = frame.width() as f32;
+        let height = frame.height() as f32;
+        for piece in self.confetti.iter().filter(|p| (0.0..1.0).contains(&p.y)) {
+            let mut glyph = [0; 4];
+            frame.put_str(
+                (piece.x * width) as usize,
+                (piece.y * height) as usize,
+                piece.glyph.encode_utf8(&mut glyph),
+                Some(piece.color),
+            );
+        }
+        if self.elapsed >= config::CELEBRATION_SPIN_DURATION {
+            self.draw_panel(frame);
+        }
+    }
+
+    fn draw_panel(&self, frame: &mut Frame) {
+        let message = format!("  Solved in {} moves!  ", self.moves);
+        let rule = "-".repeat(message.len());
+        let x = frame.width().saturating_sub(rule.len()) / 2;
+        let y = frame.height().saturating_sub(3) / 2;
+        let theme = theme::active();
+        for (row, text) in [&rule, &message, &rule].into_iter().enumerate() {
+            frame.put_str(x, y + row, text, Some(theme.highlight_text));
+            frame.fill_background(x, y + row, text.len(), theme.highlight);
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crossterm::style::Color;
+
+use crate::config;
+use crate::cube::{Cube, FaceColor, Move, facelet_descriptors, format_sequence};
+use crate::geometry::{self, Camera, ProjectedFace, Viewport};
+use crate::theme;
+
+const STICKER_SIZE: f32 = 24.0;
+const STICKER_GAP: f32 = 2.0;
+const FACE_GAP: f32 = 6.0;
+const CARD_PADDING: f32 = 16.0;
+const TEXT_WIDTH: f32 = 360.0;
+const LINE_HEIGHT: f32 = 22.0;
+const MOVES_PER_LINE: usize = 10;
+/// Width and height of the projected cube drawing, in SVG units.
+const VECTOR_SIZE: u16 = 480;
+
+/// Renders a WCA-sheet style card: the scramble text on the left and the
+/// net of the state it produces (from solved, in standard orientation) on
+/// the right.
+pub fn scramble_card_svg(scramble: &[Move]) -> String {
+    let mut cube = Cube::new();
+    cube.apply_sequence(scramble);
+
+    let face_size = 3.0 * STICKER_SIZE;
+    let net_width = 4.0 * face_size + 3.0 * FACE_GAP;
+    let net_height = 3.0 * face_size + 2.0 * FACE_GAP;
+    let width = CARD_PADDING * 3.0 + TEXT_WIDTH + net_width;
+    let height = CARD_PADDING * 2.0 + net_height;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect x="0.5" y="0.5" width="{}" height="{}" fill="white" stroke="black"/>"#,
+        width - 1.0,
+        height - 1.0
+    );
+
+    let lines: Vec<String> = scramble
+        .chunks(MOVES_PER_LINE)
+        .map(format_sequence)
+        .collect();
+    let text_top = CARD_PADDING + (net_height - lines.len() as f32 * LINE_HEIGHT) * 0.5;
+    for (i, line) in lines.iter().enumerate() {
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="{}" font-family="monospace" font-size="16">{}</text>"#,
+            CARD_PADDING,
+            text_top + (i as f32 + 0.75) * LINE_HEIGHT,
+            line
+        );
+    }
+
+    let net_left = CARD_PADDING * 2.0 + TEXT_WIDTH;
+    let colors = cube.face_colors();
+    for (idx, desc) in facelet_descriptors().iter().enumerate() {
+        let (face_col, face_row) = desc.face.net_slot();
+        let (face_col, face_row) = (face_col as f32, face_row as f32);
+        let x = net_left
+            + face_col * (face_size + FACE_GAP)
+            + desc.col as f32 * STICKER_SIZE
+            + STICKER_GAP * 0.5;
+        let y = CARD_PADDING
+            + face_row * (face_size + FACE_GAP)
+            + desc.row as f32 * STICKER_SIZE
+            + STICKER_GAP * 0.5;
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{x}" y="{y}" width="{size}" height="{size}" fill="{fill}" stroke="black"/>"#,
+            size = STICKER_SIZE - STICKER_GAP,
+            fill = config::face_color_to_hex(colors[idx]),
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+pub fn write_scramble_card(path: &Path, scramble: &[Move]) -> io::Result<()> {
+    fs::write(path, scramble_card_svg(scramble))
+}
+
+/// Draws the cube as `camera` sees it as SVG polygons: the body first, then
+/// the stickers farthest to nearest so nearer ones cover farther ones. Each
+/// sticker is filled with its color dimmed by the lighting.
+pub fn cube_svg(colors: &[FaceColor], camera: &Camera) -> String {
+    let viewport = Viewport {
+        width: VECTOR_SIZE,
+        height: VECTOR_SIZE,
+    };
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{VECTOR_SIZE}" height="{VECTOR_SIZE}" viewBox="0 0 {VECTOR_SIZE} {VECTOR_SIZE}">"#
+    );
+
+    let body = match theme::active().body {
+        Color::Rgb { r, g, b } => format!("#{r:02x}{g:02x}{b:02x}"),
+        _ => "black".to_string(),
+    };
+    // The stroke closes the hairline seams anti-aliasing leaves between
+    // neighbouring body patches.
+    for face in depth_sorted(geometry::project_body(colors, camera, viewport)) {
+        let _ = writeln!(
+            svg,
+            r#"<polygon points="{}" fill="{body}" stroke="{body}" stroke-width="1"/>"#,
+            polygon_points(&face)
+        );
+    }
+    for face in depth_sorted(geometry::project_cube(colors, camera, viewport)) {
+        let level = face.brightness.clamp(0.0, 1.0);
+        let [r, g, b] = config::face_color_to_rgb(face.color).map(|c| (c as f32 * level) as u8);
+        let _ = writeln!(
+            svg,
+            r##"<polygon points="{}" fill="#{r:02x}{g:02x}{b:02x}"/>"##,
+            polygon_points(&face)
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+pub fn write_cube_svg(path: &Path, colors: &[FaceColor], camera: &Camera) -> io::Result<()> {
+    fs::write(path, cube_svg(colors, camera))
+}
+
+fn depth_sorted(mut faces: Vec<ProjectedFace>) -> Vec<ProjectedFace> {
+    faces.sort_by(|a, b| b.depth.total_cmp(&a.depth));
+    faces
+}
+
+fn polygon_points(face: &ProjectedFace) -> String {
+    let points: Vec<String> = face
+        .points
+        .iter()
+        .map(|point| format!("{:.1},{:.1}", point.x, point.y))
+        .collect();
+    points.join(" ")
+}
@@ -1,12 +1,13 @@
+use std::borrow::Cow;
+
 use once_cell::sync::Lazy;
 
 use crate::config;
-use crate::cube::{AxisDir, Cube, FaceColor, FaceletDescriptor, LatticePoint, facelet_descriptors};
+use crate::cube::{AxisDir, Face, FaceColor};
 
 const CELL_SPACING: f32 = 0.7;
 const TILE_SIZE: f32 = 0.38;
 const NORMAL_BIAS: f32 = 0.03;
-const LIGHT_DIR: Vec3 = Vec3::new(0.3, 0.9, 0.6);
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Vec3 {
@@ -109,7 +110,7 @@ impl Vec2 {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Viewport {
     pub width: u16,
     pub height: u16,
@@ -127,8 +128,13 @@ impl Viewport {
 
 #[derive(Clone, Copy, Debug)]
 pub struct ProjectedFace {
+    pub facelet: usize,
     pub points: [Vec2; 4],
+    pub center: Vec2,
+    /// Distance along the view direction to the center of the facelet.
     pub depth: f32,
+    /// The same for each of `points`, for interpolating across the face.
+    pub point_depths: [f32; 4],
     pub brightness: f32,
     pub color: FaceColor,
 }
@@ -137,23 +143,78 @@ pub struct ProjectedFace {
 struct FaceletMesh {
     corners: [Vec3; 4],
     center: Vec3,
+    /// Center of the cubie the facelet belongs to; every facelet of a cubie
+    /// shares it.
+    cubie: Vec3,
+    /// The patch of cube body behind the sticker: the cubie's whole share
+    /// of the face, just behind the sticker plane.
+    body: [Vec3; 4],
     normal: Vec3,
+    right: Vec3,
+    up: Vec3,
+    half_tile: f32,
 }
 
-static FACELET_MESHES: Lazy<Vec<FaceletMesh>> = Lazy::new(|| {
-    facelet_descriptors()
-        .iter()
-        .map(|desc| build_mesh(desc))
-        .collect()
-});
+static FACELET_MESHES: Lazy<Vec<FaceletMesh>> = Lazy::new(|| build_meshes(3));
+
+/// Side length of the cube whose facelet colors are `colors`.
+pub fn cube_size(colors: &[FaceColor]) -> usize {
+    ((colors.len() / 6) as f64).sqrt().round() as usize
+}
+
+fn meshes_for(size: usize) -> Cow<'static, [FaceletMesh]> {
+    if size == 3 {
+        Cow::Borrowed(&FACELET_MESHES)
+    } else {
+        Cow::Owned(build_meshes(size))
+    }
+}
 
-fn build_mesh(desc: &FaceletDescriptor) -> FaceletMesh {
-    let spec = desc.face.spec();
-    let center = lattice_to_vec3(desc.coord);
+/// One mesh per facelet in the `Cube` / `BigCube` storage order. Cubes of
+/// every size span the same space; bigger ones just have smaller tiles.
+fn build_meshes(size: usize) -> Vec<FaceletMesh> {
+    let mut meshes = Vec::with_capacity(6 * size * size);
+    for face in Face::all() {
+        for row in 0..size {
+            for col in 0..size {
+                meshes.push(build_mesh(*face, row, col, size));
+            }
+        }
+    }
+    meshes
+}
+
+fn build_mesh(face: Face, row: usize, col: usize, size: usize) -> FaceletMesh {
+    let spec = face.spec();
+    let n = size as i32;
+    // Doubled lattice steps keep even sizes on integer coordinates.
+    let half_step = CELL_SPACING / (n - 1) as f32;
+    let doubled = |axis: AxisDir, steps: i32| axis_dir_to_vec3(axis) * (steps as f32 * half_step);
+    let center = doubled(spec.normal, n - 1)
+        + doubled(spec.right, 2 * col as i32 - (n - 1))
+        + doubled(spec.up, (n - 1) - 2 * row as i32);
     let normal = axis_dir_to_vec3(spec.normal).normalize();
     let right = axis_dir_to_vec3(spec.right).normalize();
     let up = axis_dir_to_vec3(spec.up).normalize();
-    let half_tile = TILE_SIZE * 0.5;
+    let half_tile = TILE_SIZE * (2.0 / (n - 1) as f32) * 0.5;
+
+    // The body patch spans half a step either side of the cubie, cut off
+    // where the cube ends.
+    let span = |steps: i32| {
+        let middle = steps as f32 * half_step;
+        (
+            (middle - half_step).max(-CELL_SPACING) - middle,
+            (middle + half_step).min(CELL_SPACING) - middle,
+        )
+    };
+    let (left, right_edge) = span(2 * col as i32 - (n - 1));
+    let (bottom, top) = span((n - 1) - 2 * row as i32);
+    let body = [
+        center + right * left + up * top,
+        center + right * right_edge + up * top,
+        center + right * right_edge + up * bottom,
+        center + right * left + up * bottom,
+    ];
 
     let offset = normal * NORMAL_BIAS;
     let corners = [
@@ -166,16 +227,46 @@ fn build_mesh(desc: &FaceletDescriptor) -> FaceletMesh {
     FaceletMesh {
         corners,
         center: center + offset,
+        cubie: center,
+        body,
         normal,
+        right,
+        up,
+        half_tile,
+    }
+}
+
+impl FaceletMesh {
+    /// The mesh pushed out with its cubie, which moves `amount` times its
+    /// own distance from the middle of the cube.
+    fn exploded(&self, amount: f32) -> FaceletMesh {
+        let shift = self.cubie * amount;
+        FaceletMesh {
+            corners: self.corners.map(|corner| corner + shift),
+            center: self.center + shift,
+            cubie: self.cubie + shift,
+            body: self.body.map(|corner| corner + shift),
+            ..*self
+        }
+    }
+
+    /// The body patch as a mesh of its own.
+    fn body_mesh(&self) -> FaceletMesh {
+        FaceletMesh {
+            corners: self.body,
+            center: self.cubie,
+            ..*self
+        }
     }
 }
 
-fn lattice_to_vec3(point: LatticePoint) -> Vec3 {
-    Vec3::new(
-        point.x as f32 * CELL_SPACING,
-        point.y as f32 * CELL_SPACING,
-        point.z as f32 * CELL_SPACING,
-    )
+/// Radius of the sphere around the origin that contains every sticker.
+fn cube_bounding_radius() -> f32 {
+    FACELET_MESHES
+        .iter()
+        .flat_map(|mesh| mesh.corners)
+        .map(Vec3::length)
+        .fold(0.0, f32::max)
 }
 
 fn axis_dir_to_vec3(axis: AxisDir) -> Vec3 {
@@ -195,6 +286,10 @@ pub struct Camera {
     radius: f32,
     target: Vec3,
     fov_y: f32,
+    /// How far the cubies are pushed apart, as a fraction of their distance
+    /// from the middle; 0 keeps the cube whole.
+    explode: f32,
+    light: Light,
 }
 
 impl Camera {
@@ -206,6 +301,16 @@ impl Camera {
             radius: 3.5,
             target: Vec3::zero(),
             fov_y: 1.0,
+            explode: 0.0,
+            light: Light::new(),
+        }
+    }
+
+    /// A copy of the camera that sees the cubies pushed apart by `amount`.
+    pub fn exploded(&self, amount: f32) -> Camera {
+        Camera {
+            explode: amount.max(0.0),
+            ..*self
         }
     }
 
@@ -218,9 +323,40 @@ impl Camera {
         self.roll = (self.roll + delta).clamp(-std::f32::consts::PI, std::f32::consts::PI);
     }
 
-    pub fn zoom(&mut self, delta: f32) {
-        self.radius =
-            (self.radius + delta).clamp(config::CAMERA_MIN_RADIUS, config::CAMERA_MAX_RADIUS);
+    /// Swings the light around the cube; it stays put as the camera moves.
+    pub fn rotate_light(&mut self, d_azimuth: f32, d_elevation: f32) {
+        self.light.rotate(d_azimuth, d_elevation);
+    }
+
+    /// Returns false when the zoom was stopped by the radius limits.
+    pub fn zoom(&mut self, delta: f32) -> bool {
+        let wanted = self.radius + delta;
+        self.radius = wanted.clamp(config::CAMERA_MIN_RADIUS, config::CAMERA_MAX_RADIUS);
+        self.radius == wanted
+    }
+
+    /// The same camera moved to the diametrically opposite side of the
+    /// target, so together the two views show every sticker.
+    pub fn opposite(&self) -> Camera {
+        Camera {
+            theta: (self.theta + std::f32::consts::PI) % std::f32::consts::TAU,
+            phi: -self.phi,
+            ..*self
+        }
+    }
+
+    /// Moves the camera in or out so the whole cube fits `viewport` with
+    /// `config::FIT_MARGIN` to spare, whichever way it is currently facing.
+    pub fn fit_to(&mut self, viewport: Viewport) {
+        let bound = cube_bounding_radius();
+        let f = 1.0 / (0.5 * self.fov_y).tan();
+        // Horizontal NDC is divided by the aspect, so narrow viewports are
+        // limited by width instead of height.
+        let squeeze = (1.0 / viewport.aspect().max(0.5)).max(1.0);
+        let lateral = bound * f * squeeze / (1.0 - config::FIT_MARGIN);
+        self.radius = (bound * bound + lateral * lateral)
+            .sqrt()
+            .clamp(config::CAMERA_MIN_RADIUS, config::CAMERA_MAX_RADIUS);
     }
 
     pub fn basis(&self) -> CameraBasis {
@@ -254,6 +390,9 @@ impl Camera {
             right,
             up,
             fov_y: self.fov_y,
+            explode: self.explode,
+            light: self.light,
+            target: self.target,
         }
     }
 }
@@ -264,19 +403,102 @@ pub struct CameraBasis {
     pub right: Vec3,
     pub up: Vec3,
     pub fov_y: f32,
+    pub explode: f32,
+    pub light: Light,
+    pub target: Vec3,
+}
+
+impl CameraBasis {
+    /// How much of its brightness `point` keeps through the depth fog.
+    fn fog(&self, point: Vec3) -> f32 {
+        let past_middle = (point - self.eye).length() - (self.target - self.eye).length();
+        let t = (past_middle - config::FOG_START) / (config::FOG_END - config::FOG_START);
+        1.0 - config::FOG_DENSITY * t.clamp(0.0, 1.0)
+    }
+}
+
+/// A directional light in world space, given by the angles it shines from
+/// (measured like the camera's), and how a sticker's brightness splits into
+/// an ambient floor, the diffuse part that follows the light and a
+/// Blinn-Phong specular highlight. An optional fixed fill light adds its own
+/// diffuse term to lift the faces the main light misses.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Light {
+    azimuth: f32,
+    elevation: f32,
+    ambient: f32,
+    diffuse: f32,
+    specular: f32,
+    shininess: f32,
+    fill: Option<(Vec3, f32)>,
+}
+
+impl Light {
+    pub fn new() -> Self {
+        let dir = config::LIGHT_DIR.normalize();
+        Self {
+            azimuth: dir.x.atan2(dir.z),
+            elevation: dir.y.clamp(-1.0, 1.0).asin(),
+            ambient: config::LIGHT_AMBIENT,
+            diffuse: config::LIGHT_DIFFUSE,
+            specular: config::LIGHT_SPECULAR,
+            shininess: config::LIGHT_SHININESS,
+            fill: config::FILL_LIGHT.map(|(dir, strength)| (dir.normalize(), strength)),
+        }
+    }
+
+    pub fn rotate(&mut self, d_azimuth: f32, d_elevation: f32) {
+        use std::f32::consts::{FRAC_PI_2, TAU};
+        self.azimuth = (self.azimuth + d_azimuth) % TAU;
+        self.elevation = (self.elevation + d_elevation).clamp(-FRAC_PI_2, FRAC_PI_2);
+    }
+
+    /// Unit vector pointing from the cube towards the light.
+    fn direction(&self) -> Vec3 {
+        let cos_elevation = self.elevation.cos();
+        Vec3::new(
+            cos_elevation * self.azimuth.sin(),
+            self.elevation.sin(),
+            cos_elevation * self.azimuth.cos(),
+        )
+    }
+
+    /// Brightness of a surface with `normal` seen from `to_eye`; glossy
+    /// spots can go past 1 and are clamped by the shading.
+    fn shade(&self, normal: Vec3, to_eye: Vec3) -> f32 {
+        let normal = normal.normalize();
+        let light = self.direction();
+        let diffuse = normal.dot(light).max(0.0);
+        let specular = if diffuse > 0.0 {
+            let halfway = (light + to_eye.normalize()).normalize();
+            normal.dot(halfway).max(0.0).powf(self.shininess)
+        } else {
+            0.0
+        };
+        let fill = self
+            .fill
+            .map_or(0.0, |(dir, strength)| strength * normal.dot(dir).max(0.0));
+        self.ambient + self.diffuse * diffuse + self.specular * specular + fill
+    }
 }
 
-pub fn project_cube(cube: &Cube, camera: &Camera, viewport: Viewport) -> Vec<ProjectedFace> {
-    let colors = cube.face_colors();
+/// Projects the visible facelets of a cube of any size, given its colors.
+pub fn project_cube(
+    colors: &[FaceColor],
+    camera: &Camera,
+    viewport: Viewport,
+) -> Vec<ProjectedFace> {
     let basis = camera.basis();
-    let mut faces = Vec::with_capacity(64);
+    let meshes = meshes_for(cube_size(colors));
+    let mut faces = Vec::with_capacity(colors.len() / 2);
 
-    for (idx, mesh) in FACELET_MESHES.iter().enumerate() {
+    for (idx, mesh) in meshes.iter().enumerate() {
+        let mesh = &mesh.exploded(basis.explode);
         if !is_face_visible(mesh, &basis) {
             continue;
         }
 
-        if let Some(projected) = project_mesh(mesh, colors[idx], &basis, viewport) {
+        if let Some(projected) = project_mesh(idx, mesh, colors[idx], &basis, viewport) {
             faces.push(projected);
         }
     }
@@ -284,29 +506,209 @@ pub fn project_cube(cube: &Cube, camera: &Camera, viewport: Viewport) -> Vec<Pro
     faces
 }
 
+/// Projects the visible patches of cube body, one behind each facelet
+/// (whose index and color they carry), for a cube of any size.
+pub fn project_body(
+    colors: &[FaceColor],
+    camera: &Camera,
+    viewport: Viewport,
+) -> Vec<ProjectedFace> {
+    let basis = camera.basis();
+    let meshes = meshes_for(cube_size(colors));
+    let mut faces = Vec::with_capacity(colors.len() / 2);
+    for (idx, mesh) in meshes.iter().enumerate() {
+        let mesh = &mesh.exploded(basis.explode).body_mesh();
+        if !is_face_visible(mesh, &basis) {
+            continue;
+        }
+        if let Some(projected) = project_mesh(idx, mesh, colors[idx], &basis, viewport) {
+            faces.push(projected);
+        }
+    }
+    faces
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RayHit {
+    pub facelet: usize,
+    pub depth: f32,
+    pub brightness: f32,
+    pub color: FaceColor,
+}
+
+/// Casts a ray through screen position (`x`, `y`), the inverse of
+/// `project_point`, and returns the nearest front-facing sticker it hits.
+pub fn cast_screen_ray(
+    colors: &[FaceColor],
+    basis: &CameraBasis,
+    viewport: Viewport,
+    x: f32,
+    y: f32,
+) -> Option<RayHit> {
+    let dir = screen_ray(basis, viewport, x, y);
+    let meshes = meshes_for(cube_size(colors));
+    let mut nearest: Option<RayHit> = None;
+    for (idx, (mesh, color)) in meshes.iter().zip(colors).enumerate() {
+        let mesh = &mesh.exploded(basis.explode);
+        let facing = dir.dot(mesh.normal);
+        if facing >= 0.0 {
+            continue;
+        }
+        let t = (mesh.center - basis.eye).dot(mesh.normal) / facing;
+        if t <= 0.0 || nearest.is_some_and(|hit| hit.depth <= t) {
+            continue;
+        }
+        let local = basis.eye + dir * t - mesh.center;
+        if local.dot(mesh.right).abs() > mesh.half_tile || local.dot(mesh.up).abs() > mesh.half_tile
+        {
+            continue;
+        }
+        nearest = Some(RayHit {
+            facelet: idx,
+            depth: t,
+            brightness: basis.light.shade(mesh.normal, -dir) * basis.fog(basis.eye + dir * t),
+            color: *color,
+        });
+    }
+    nearest
+}
+
+/// Unit direction of the ray from the eye through screen position (`x`, `y`).
+fn screen_ray(basis: &CameraBasis, viewport: Viewport, x: f32, y: f32) -> Vec3 {
+    let f = 1.0 / (0.5 * basis.fov_y).tan();
+    let aspect = viewport.aspect().max(0.5);
+    let ndc_x = x / viewport.width.saturating_sub(1).max(1) as f32 * 2.0 - 1.0;
+    let ndc_y = 1.0 - y / viewport.height.saturating_sub(1).max(1) as f32 * 2.0;
+    (basis.forward + basis.right * (ndc_x * aspect / f) + basis.up * (ndc_y / f)).normalize()
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct GroundHit {
+    /// Distance along the view direction, comparable with projected depths.
+    pub depth: f32,
+    /// The grid square the point falls in, counted from the middle.
+    pub tile: (i32, i32),
+    /// Fraction of the light the cube blocks here, from 0 to 1.
+    pub shadow: f32,
+}
+
+/// Where the ray through screen position (`x`, `y`) meets the floor under
+/// the cube, if it does within the floor's extent and from above. The floor
+/// sits `GROUND_GAP` below the cube, however far it is exploded.
+pub fn cast_ground_ray(
+    basis: &CameraBasis,
+    viewport: Viewport,
+    x: f32,
+    y: f32,
+) -> Option<GroundHit> {
+    let half = CELL_SPACING * (1.0 + basis.explode);
+    let floor = basis.target.y - half - config::GROUND_GAP;
+    let dir = screen_ray(basis, viewport, x, y);
+    if basis.eye.y <= floor || dir.y >= 0.0 {
+        return None;
+    }
+    let point = basis.eye + dir * ((floor - basis.eye.y) / dir.y);
+    let offset = point - basis.target;
+    if offset.x.abs() > config::GROUND_EXTENT || offset.z.abs() > config::GROUND_EXTENT {
+        return None;
+    }
+    let tile = |coordinate: f32| (coordinate / config::GROUND_GRID).floor() as i32;
+    Some(GroundHit {
+        depth: (point - basis.eye).dot(basis.forward),
+        tile: (tile(offset.x), tile(offset.z)),
+        shadow: shadow(point, basis, half),
+    })
+}
+
+/// How much of the light reaching `point` the cube's bounding box blocks.
+/// The light is sampled over a small cone so the shadow's edge is soft.
+fn shadow(point: Vec3, basis: &CameraBasis, half: f32) -> f32 {
+    let light = basis.light.direction();
+    if light.y <= 0.0 {
+        return 0.0;
+    }
+    let mut side = light.cross(Vec3::new(0.0, 1.0, 0.0));
+    if side.length() < 0.001 {
+        side = Vec3::new(1.0, 0.0, 0.0);
+    }
+    let side = side.normalize();
+    let lift = side.cross(light);
+    let spread = config::SHADOW_SOFTNESS;
+    let mut blocked = 0;
+    for du in [-spread, 0.0, spread] {
+        for dv in [-spread, 0.0, spread] {
+            let dir = (light + side * du + lift * dv).normalize();
+            if ray_hits_box(point - basis.target, dir, half) {
+                blocked += 1;
+            }
+        }
+    }
+    blocked as f32 / 9.0
+}
+
+/// Slab test of a ray against the cube `[-half, half]` on every axis.
+fn ray_hits_box(origin: Vec3, dir: Vec3, half: f32) -> bool {
+    let (mut near, mut far) = (0.0f32, f32::INFINITY);
+    for (origin, dir) in [(origin.x, dir.x), (origin.y, dir.y), (origin.z, dir.z)] {
+        if dir.abs() < 1e-6 {
+            if origin.abs() > half {
+                return false;
+            }
+            continue;
+        }
+        let (a, b) = ((-half - origin) / dir, (half - origin) / dir);
+        near = near.max(a.min(b));
+        far = far.min(a.max(b));
+    }
+    near <= far
+}
+
+/// The facelet drawn in terminal cell (`x`, `y`), if any.
+pub fn pick_facelet(
+    colors: &[FaceColor],
+    camera: &Camera,
+    viewport: Viewport,
+    x: u16,
+    y: u16,
+) -> Option<usize> {
+    let hit = cast_screen_ray(
+        colors,
+        &camera.basis(),
+        viewport,
+        x as f32 + 0.5,
+        y as f32 + 0.5,
+    )?;
+    Some(hit.facelet)
+}
+
 fn is_face_visible(mesh: &FaceletMesh, basis: &CameraBasis) -> bool {
     let to_camera = (basis.eye - mesh.center).normalize();
     mesh.normal.dot(to_camera) > 0.0
 }
 
 fn project_mesh(
+    facelet: usize,
     mesh: &FaceletMesh,
     color: FaceColor,
     basis: &CameraBasis,
     viewport: Viewport,
 ) -> Option<ProjectedFace> {
     let mut projected = [Vec2::new(0.0, 0.0); 4];
-    let mut total_depth = 0.0;
+    let mut point_depths = [0.0; 4];
     for (i, corner) in mesh.corners.iter().enumerate() {
         let (pt, depth) = project_point(*corner, basis, viewport)?;
         projected[i] = pt;
-        total_depth += depth;
+        point_depths[i] = depth;
     }
-    let depth = total_depth / 4.0;
-    let brightness = shade_face(mesh.normal);
+    let (center, depth) = project_point(mesh.center, basis, viewport)?;
+    let brightness =
+        basis.light.shade(mesh.normal, basis.eye - mesh.center) * basis.fog(mesh.center);
     Some(ProjectedFace {
+        facelet,
         points: projected,
+        center,
         depth,
+        point_depths,
         brightness,
         color,
     })
@@ -334,8 +736,71 @@ fn project_point(point: Vec3, basis: &CameraBasis, viewport: Viewport) -> Option
     Some((Vec2::new(screen_x, screen_y), z))
 }
 
-fn shade_face(normal: Vec3) -> f32 {
-    let light = LIGHT_DIR.normalize();
-    let intensity = normal.normalize().dot(light).max(0.0);
-    0.2 + 0.8 * intensity
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn specular_peaks_where_the_view_mirrors_the_light() {
+        let light = Light {
+            azimuth: 0.0,
+            elevation: std::f32::consts::FRAC_PI_4,
+            ambient: 0.2,
+            diffuse: 0.5,
+            specular: 0.3,
+            shininess: 16.0,
+            fill: None,
+        };
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let mirrored = light.shade(up, Vec3::new(0.0, 1.0, -1.0));
+        let off_axis = light.shade(up, Vec3::new(1.0, 1.0, 0.0));
+        assert!((mirrored - (0.2 + 0.5 * 0.5f32.sqrt() + 0.3)).abs() < 1e-4);
+        assert!(off_axis < mirrored - 0.2);
+
+        let filled = Light {
+            fill: Some((Vec3::new(0.0, -1.0, 0.0), 0.25)),
+            ..light
+        };
+        let down = Vec3::new(0.0, -1.0, 0.0);
+        assert_eq!(light.shade(down, down), 0.2);
+        assert_eq!(filled.shade(down, down), 0.45);
+    }
+
+    #[test]
+    fn cube_shadows_the_floor_below_the_light() {
+        let mut camera = Camera::new();
+        camera.rotate_light(0.0, std::f32::consts::FRAC_PI_2);
+        let basis = camera.basis();
+        let viewport = Viewport {
+            width: 80,
+            height: 40,
+        };
+        let hits: Vec<GroundHit> = (0..40)
+            .flat_map(|y| (0..80).map(move |x| (x, y)))
+            .filter_map(|(x, y)| cast_ground_ray(&basis, viewport, x as f32 + 0.5, y as f32 + 0.5))
+            .collect();
+        let under = |hit: &&GroundHit| {
+            [-2, -1, 0, 1].contains(&hit.tile.0) && [-2, -1, 0, 1].contains(&hit.tile.1)
+        };
+        assert!(hits.iter().filter(under).any(|hit| hit.shadow == 1.0));
+        assert!(
+            hits.iter()
+                .filter(|hit| hit.tile.0.abs() > 3)
+                .all(|hit| hit.shadow == 0.0)
+        );
+    }
+
+    #[test]
+    fn fog_dims_the_far_side_of_the_cube() {
+        let basis = Camera::new().basis();
+        let toward_eye = (basis.eye - basis.target).normalize();
+        assert_eq!(basis.fog(basis.eye), 1.0);
+        assert_eq!(
+            basis.fog(basis.target - toward_eye * 5.0),
+            1.0 - config::FOG_DENSITY
+        );
+        let near = basis.fog(basis.target + toward_eye * 0.5);
+        let far = basis.fog(basis.target - toward_eye * 0.5);
+        assert!(far < near && near < 1.0);
+    }
 }
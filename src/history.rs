@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+
+use crate::config;
+use crate::cube::Move;
+
+/// Twists made since the last scramble or reset, with the ones undone kept
+/// for redo until a new twist replaces them.
+#[derive(Clone, Debug, Default)]
+pub struct MoveHistory {
+    done: Vec<Move>,
+    undone: Vec<Move>,
+}
+
+impl MoveHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a fresh twist, which drops anything left to redo.
+    pub fn push(&mut self, mv: Move) {
+        self.done.push(mv);
+        self.undone.clear();
+    }
+
+    /// Pops the latest twist and returns the move that reverses it.
+    pub fn undo(&mut self) -> Option<Move> {
+        let mv = self.done.pop()?;
+        self.undone.push(mv);
+        Some(mv.inverse())
+    }
+
+    /// Returns the most recently undone twist to apply it again.
+    pub fn redo(&mut self) -> Option<Move> {
+        let mv = self.undone.pop()?;
+        self.done.push(mv);
+        Some(mv)
+    }
+
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+
+    /// Twists currently applied, oldest first.
+    pub fn moves(&self) -> &[Move] {
+        &self.done
+    }
+}
+
+/// Moves turned since the last scramble or reset in the half-turn (HTM),
+/// quarter-turn (QTM) and slice-turn (STM) metrics. Undone and redone
+/// twists count like any other turn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MoveCounts {
+    pub htm: usize,
+    pub qtm: usize,
+    pub stm: usize,
+}
+
+impl MoveCounts {
+    /// Counts `mv`: a slice turn is two outer turns in HTM and QTM, and a
+    /// half turn is two quarter turns in QTM.
+    pub fn add(&mut self, mv: Move) {
+        let layers = if mv.is_slice() { 2 } else { 1 };
+        self.htm += layers;
+        self.qtm += layers * mv.def().turns as usize;
+        self.stm += 1;
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoggedScramble {
+    pub moves: Vec<Move>,
+    pub seed: u64,
+}
+
+/// The last `SCRAMBLE_LOG_SIZE` 3x3 scrambles, newest first.
+#[derive(Clone, Debug, Default)]
+pub struct ScrambleLog {
+    entries: VecDeque<LoggedScramble>,
+}
+
+impl ScrambleLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a scramble, dropping the oldest once the log is full.
+    pub fn push(&mut self, moves: Vec<Move>, seed: u64) {
+        self.entries.push_front(LoggedScramble { moves, seed });
+        self.entries.truncate(config::SCRAMBLE_LOG_SIZE);
+    }
+
+    pub fn get(&self, index: usize) -> Option<&LoggedScramble> {
+        self.entries.get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &LoggedScramble> {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_and_redo_walk_the_history() {
+        let mut history = MoveHistory::new();
+        history.push(Move::R);
+        history.push(Move::U2);
+        history.push(Move::FPrime);
+        assert_eq!(history.undo(), Some(Move::F));
+        assert_eq!(history.undo(), Some(Move::U2));
+        assert_eq!(history.redo(), Some(Move::U2));
+        assert_eq!(history.moves(), &[Move::R, Move::U2]);
+
+        history.push(Move::L);
+        assert_eq!(history.redo(), None);
+        history.clear();
+        assert_eq!(history.undo(), None);
+    }
+
+    #[test]
+    fn scramble_log_keeps_the_newest() {
+        let mut log = ScrambleLog::new();
+        for seed in 0..config::SCRAMBLE_LOG_SIZE as u64 + 3 {
+            log.push(vec![Move::R], seed);
+        }
+        assert_eq!(log.len(), config::SCRAMBLE_LOG_SIZE);
+        assert_eq!(log.get(0).map(|entry| entry.seed), Some(12));
+        assert_eq!(log.iter().last().map(|entry| entry.seed), Some(3));
+    }
+
+    #[test]
+    fn move_counts_follow_each_metric() {
+        let mut counts = MoveCounts::default();
+        for mv in [Move::R, Move::U2, Move::M, Move::E2] {
+            counts.add(mv);
+        }
+        assert_eq!(
+            counts,
+            MoveCounts {
+                htm: 6,
+                qtm: 9,
+                stm: 4
+            }
+        );
+    }
+}
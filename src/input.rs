@@ -1,18 +1,85 @@
 use std::time::Duration;
 
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEventKind,
+};
 use std::io;
 
 use crate::config;
-use crate::cube::Move;
+use crate::cube::{self, Move};
+use crate::macros::Macros;
 
 #[derive(Debug)]
 pub enum Action {
-    RotateCamera { d_theta: f32, d_phi: f32 },
+    RotateCamera {
+        d_theta: f32,
+        d_phi: f32,
+    },
     RollCamera(f32),
+    RotateLight {
+        d_azimuth: f32,
+        d_elevation: f32,
+    },
     ZoomCamera(f32),
+    FitCamera,
+    ToggleFourByFour,
+    Hover {
+        x: u16,
+        y: u16,
+    },
+    ToggleDualView,
+    ToggleNetView,
+    ToggleMiniNet,
+    ToggleWireframe,
+    CycleMethod,
+    ToggleGround,
+    ToggleExplode,
+    ToggleHistory,
     TwistFace(Move),
+    ApplySequence(Vec<Move>),
+    Undo,
+    Redo,
     Scramble,
+    RepeatScramble,
+    ToggleScrambleList,
+    ListUp,
+    ListDown,
+    ListSelect,
+    TypeChar(char),
+    TypeBackspace,
+    TypeSubmit,
+    NormalizeOrientation,
+    AdjustScrambleLength(isize),
+    CycleScrambleStyle,
+    ExportScrambleCard,
+    ExportFrame,
+    ExportCubeSvg,
+    ExportCubeExplorer,
+    ToggleRecording,
+    CopyFaceletString,
+    /// Applies moves or a reconstruction from the system clipboard.
+    PasteClipboard,
+    NextPattern,
+    Solve,
+    SolveBeginner,
+    SolveOptimal,
+    SolveCrosses,
+    AutoSolve,
+    /// Starts or leaves stepping through the shown solution.
+    StepSolution,
+    StepNext,
+    StepBack,
+    CaptureTurntable,
+    ToggleStats,
+    ToggleHud,
+    ToggleLetters,
+    ToggleGhost,
+    ToggleF2lPairs,
+    ToggleStarfield,
+    CycleShading,
+    ToggleBorders,
+    TrainEoLine,
+    TrainBld,
     Reset,
     Quit,
 }
@@ -22,25 +89,54 @@ type TermResult<T> = io::Result<T>;
 pub struct InputHandler {
     pending_prime: bool,
     pending_double: bool,
+    macros: Macros,
+    /// While a list is open, arrows and Enter pick from it instead.
+    list_open: bool,
+    /// While text is being typed, keys go into it instead.
+    text_entry: bool,
+    /// While stepping through a solution, `n` and `p` step instead.
+    stepping: bool,
 }
 
 impl InputHandler {
-    pub fn new() -> Self {
+    pub fn new(macros: Macros) -> Self {
         Self {
             pending_prime: false,
             pending_double: false,
+            macros,
+            list_open: false,
+            text_entry: false,
+            stepping: false,
         }
     }
 
+    pub fn set_list_open(&mut self, open: bool) {
+        self.list_open = open;
+    }
+
+    pub fn set_text_entry(&mut self, on: bool) {
+        self.text_entry = on;
+    }
+
+    pub fn set_stepping(&mut self, on: bool) {
+        self.stepping = on;
+    }
+
     pub fn poll_actions(&mut self) -> TermResult<Vec<Action>> {
         let mut actions = Vec::new();
         while event::poll(poll_timeout())? {
             match event::read()? {
                 Event::Key(key) => {
-                    if let Some(action) = self.handle_key_event(key) {
-                        if let Some(action) = action {
-                            actions.push(action);
-                        }
+                    if let Some(Some(action)) = self.handle_key_event(key) {
+                        actions.push(action);
+                    }
+                }
+                Event::Mouse(mouse) => {
+                    if matches!(mouse.kind, MouseEventKind::Moved | MouseEventKind::Drag(_)) {
+                        actions.push(Action::Hover {
+                            x: mouse.column,
+                            y: mouse.row,
+                        });
                     }
                 }
                 Event::Resize(_, _) => {
@@ -56,39 +152,137 @@ impl InputHandler {
         if !matches!(key.kind, KeyEventKind::Press | KeyEventKind::Repeat) {
             return None;
         }
+        if key.modifiers.contains(KeyModifiers::CONTROL)
+            && matches!(key.code, KeyCode::Char('c') | KeyCode::Char('d'))
+        {
+            return Some(Some(Action::Quit));
+        }
         if key.modifiers.contains(KeyModifiers::CONTROL) {
-            if matches!(key.code, KeyCode::Char('c') | KeyCode::Char('d')) {
-                return Some(Some(Action::Quit));
+            return Some(match key.code {
+                KeyCode::Char('z') => Some(Action::Undo),
+                KeyCode::Char('y') => Some(Action::Redo),
+                KeyCode::Char('f') => Some(Action::CopyFaceletString),
+                KeyCode::Char('s') => Some(Action::Solve),
+                KeyCode::Char('b') => Some(Action::SolveBeginner),
+                KeyCode::Char('p') => Some(Action::SolveOptimal),
+                KeyCode::Char('x') => Some(Action::SolveCrosses),
+                KeyCode::Char('r') => Some(Action::RepeatScramble),
+                KeyCode::Char('l') => Some(Action::ToggleScrambleList),
+                KeyCode::Char('o') => Some(Action::NormalizeOrientation),
+                KeyCode::Char('a') => Some(Action::ToggleF2lPairs),
+                KeyCode::Char('t') => Some(Action::TrainBld),
+                KeyCode::Char('e') => Some(Action::ToggleBorders),
+                KeyCode::Char('n') => Some(Action::ToggleNetView),
+                KeyCode::Char('w') => Some(Action::ToggleMiniNet),
+                KeyCode::Char('q') => Some(Action::ToggleWireframe),
+                KeyCode::Char('v') => Some(Action::ToggleGround),
+                KeyCode::Char('u') => Some(Action::ToggleExplode),
+                KeyCode::Char('g') => Some(Action::ExportCubeSvg),
+                KeyCode::Char('k') => Some(Action::ToggleRecording),
+                _ => None,
+            });
+        }
+
+        if self.text_entry {
+            return Some(match key.code {
+                KeyCode::Char(ch) => Some(Action::TypeChar(ch)),
+                KeyCode::Backspace => Some(Action::TypeBackspace),
+                KeyCode::Enter => Some(Action::TypeSubmit),
+                KeyCode::Esc => Some(Action::TrainBld),
+                _ => None,
+            });
+        }
+        if self.list_open {
+            return Some(match key.code {
+                KeyCode::Up => Some(Action::ListUp),
+                KeyCode::Down => Some(Action::ListDown),
+                KeyCode::Enter => Some(Action::ListSelect),
+                KeyCode::Esc => Some(Action::ToggleScrambleList),
+                _ => None,
+            });
+        }
+        if self.stepping {
+            match key.code {
+                KeyCode::Char('n') | KeyCode::Char('N') => return Some(Some(Action::StepNext)),
+                KeyCode::Char('p') | KeyCode::Char('P') => return Some(Some(Action::StepBack)),
+                KeyCode::Esc => return Some(Some(Action::StepSolution)),
+                _ => {}
+            }
+        }
+        if let KeyCode::Char(ch) = key.code
+            && let Some(moves) = self.macros.sequence(ch)
+        {
+            self.pending_prime = false;
+            self.pending_double = false;
+            return Some(Some(Action::ApplySequence(moves.to_vec())));
+        }
+        if key.modifiers.contains(KeyModifiers::SHIFT) {
+            let step = config::LIGHT_ROTATE_STEP;
+            let (d_azimuth, d_elevation) = match key.code {
+                KeyCode::Left => (-step, 0.0),
+                KeyCode::Right => (step, 0.0),
+                KeyCode::Up => (0.0, step),
+                KeyCode::Down => (0.0, -step),
+                _ => (0.0, 0.0),
+            };
+            if d_azimuth != 0.0 || d_elevation != 0.0 {
+                return Some(Some(Action::RotateLight {
+                    d_azimuth,
+                    d_elevation,
+                }));
             }
         }
 
         Some(match key.code {
             KeyCode::Esc => Some(Action::Quit),
+            KeyCode::Enter => Some(Action::StepSolution),
             KeyCode::Char(' ') => Some(Action::Scramble),
+            KeyCode::Char('[') => Some(Action::AdjustScrambleLength(-1)),
+            KeyCode::Char(']') => Some(Action::AdjustScrambleLength(1)),
+            KeyCode::Char('g') | KeyCode::Char('G') => Some(Action::CycleScrambleStyle),
+            KeyCode::Tab => Some(Action::ToggleStats),
+            KeyCode::Char('h') | KeyCode::Char('H') => Some(Action::ToggleHud),
+            KeyCode::Char('o') | KeyCode::Char('O') => Some(Action::TrainEoLine),
+            KeyCode::Char('i') | KeyCode::Char('I') => Some(Action::ToggleLetters),
+            KeyCode::Char('t') | KeyCode::Char('T') => Some(Action::ToggleGhost),
+            KeyCode::Char('*') => Some(Action::ToggleStarfield),
+            KeyCode::Char('/') => Some(Action::CycleMethod),
+            KeyCode::Char(':') => Some(Action::AutoSolve),
+            KeyCode::Char(';') => Some(Action::ExportCubeExplorer),
+            KeyCode::Char('"') => Some(Action::PasteClipboard),
+            KeyCode::Char('v') | KeyCode::Char('V') => Some(Action::CycleShading),
             KeyCode::Char('x') | KeyCode::Char('X') => Some(Action::Reset),
+            KeyCode::Char('j') | KeyCode::Char('J') => Some(Action::NextPattern),
+            KeyCode::Char('p') | KeyCode::Char('P') => Some(Action::ExportScrambleCard),
+            KeyCode::Char('c') | KeyCode::Char('C') => Some(Action::ExportFrame),
+            KeyCode::Char('n') | KeyCode::Char('N') => Some(Action::CaptureTurntable),
             KeyCode::Char('+') | KeyCode::Char('=') => {
                 Some(Action::ZoomCamera(-config::CAMERA_ZOOM_STEP))
             }
             KeyCode::Char('-') | KeyCode::Char('_') => {
                 Some(Action::ZoomCamera(config::CAMERA_ZOOM_STEP))
             }
-            KeyCode::Char('q') => Some(Action::RollCamera(-config::CAMERA_ROLL_STEP)),
-            KeyCode::Char('e') => Some(Action::RollCamera(config::CAMERA_ROLL_STEP)),
-            KeyCode::Left | KeyCode::Char('a') | KeyCode::Char('A') => Some(Action::RotateCamera {
+            KeyCode::Char('z') | KeyCode::Char('Z') => Some(Action::FitCamera),
+            KeyCode::Char('4') => Some(Action::ToggleFourByFour),
+            KeyCode::Char('k') | KeyCode::Char('K') => Some(Action::ToggleDualView),
+            KeyCode::Char('y') | KeyCode::Char('Y') => Some(Action::ToggleHistory),
+            // Roll and rotation stay off letters, which are all taken by
+            // face and slice turns.
+            KeyCode::Char(',') => Some(Action::RollCamera(-config::CAMERA_ROLL_STEP)),
+            KeyCode::Char('.') => Some(Action::RollCamera(config::CAMERA_ROLL_STEP)),
+            KeyCode::Left => Some(Action::RotateCamera {
                 d_theta: -config::CAMERA_ROTATE_STEP,
                 d_phi: 0.0,
             }),
-            KeyCode::Right | KeyCode::Char('d') | KeyCode::Char('D') => {
-                Some(Action::RotateCamera {
-                    d_theta: config::CAMERA_ROTATE_STEP,
-                    d_phi: 0.0,
-                })
-            }
-            KeyCode::Up | KeyCode::Char('w') | KeyCode::Char('W') => Some(Action::RotateCamera {
+            KeyCode::Right => Some(Action::RotateCamera {
+                d_theta: config::CAMERA_ROTATE_STEP,
+                d_phi: 0.0,
+            }),
+            KeyCode::Up => Some(Action::RotateCamera {
                 d_theta: 0.0,
                 d_phi: config::CAMERA_ELEVATION_STEP,
             }),
-            KeyCode::Down | KeyCode::Char('s') | KeyCode::Char('S') => Some(Action::RotateCamera {
+            KeyCode::Down => Some(Action::RotateCamera {
                 d_theta: 0.0,
                 d_phi: -config::CAMERA_ELEVATION_STEP,
             }),
@@ -127,26 +321,42 @@ fn poll_timeout() -> Duration {
 }
 
 fn parse_move_letter(letter: char, prime: bool, double: bool) -> Option<Move> {
-    use Move::*;
-    Some(match (letter, prime, double) {
-        ('u', _, true) => U2,
-        ('u', true, false) => UPrime,
-        ('u', false, false) => U,
-        ('d', _, true) => D2,
-        ('d', true, false) => DPrime,
-        ('d', false, false) => D,
-        ('r', _, true) => R2,
-        ('r', true, false) => RPrime,
-        ('r', false, false) => R,
-        ('l', _, true) => L2,
-        ('l', true, false) => LPrime,
-        ('l', false, false) => L,
-        ('f', _, true) => F2,
-        ('f', true, false) => FPrime,
-        ('f', false, false) => F,
-        ('b', _, true) => B2,
-        ('b', true, false) => BPrime,
-        ('b', false, false) => B,
-        _ => return None,
-    })
+    cube::move_for(letter.to_ascii_uppercase(), prime, double)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(handler: &mut InputHandler, ch: char) -> Option<Move> {
+        let modifiers = if ch.is_ascii_uppercase() {
+            KeyModifiers::SHIFT
+        } else {
+            KeyModifiers::NONE
+        };
+        match handler.handle_key_event(KeyEvent::new(KeyCode::Char(ch), modifiers)) {
+            Some(Some(Action::TwistFace(mv))) => Some(mv),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn slice_letters_twist_slices() {
+        let mut handler = InputHandler::new(Macros::default());
+        for (ch, prime) in [
+            ('m', false),
+            ('M', true),
+            ('e', false),
+            ('E', true),
+            ('s', false),
+            ('S', true),
+            ('d', false),
+        ] {
+            let expected = cube::move_for(ch.to_ascii_uppercase(), prime, false);
+            assert!(expected.is_some());
+            assert_eq!(press(&mut handler, ch), expected, "key {ch}");
+        }
+        assert_eq!(press(&mut handler, '2'), None);
+        assert_eq!(press(&mut handler, 's'), cube::move_for('S', false, true));
+    }
 }
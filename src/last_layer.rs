@@ -0,0 +1,251 @@
+use once_cell::sync::Lazy;
+
+use crate::cube::{self, Axis, Cube, Face, Move, RotationDir, facelet_descriptors};
+use crate::stages::{self, CfopStage};
+
+/// The 57 OLL cases by number, each with an algorithm that solves it.
+/// Algorithms may use wide turns and rotations; see `case_moves`.
+pub const OLL_CASES: [(&str, &str); 57] = [
+    ("OLL 1", "R U2 R2 F R F' U2 R' F R F'"),
+    ("OLL 2", "F R U R' U' F' f R U R' U' f'"),
+    ("OLL 3", "f R U R' U' f' U' F R U R' U' F'"),
+    ("OLL 4", "f R U R' U' f' U F R U R' U' F'"),
+    ("OLL 5", "r' U2 R U R' U r"),
+    ("OLL 6", "r U2 R' U' R U' r'"),
+    ("OLL 7", "r U R' U R U2 r'"),
+    ("OLL 8", "l' U' L U' L' U2 l"),
+    ("OLL 9", "R U R' U' R' F R2 U R' U' F'"),
+    ("OLL 10", "R U R' U R' F R F' R U2 R'"),
+    ("OLL 11", "r U R' U R' F R F' R U2 r'"),
+    ("OLL 12", "M' R' U' R U' R' U2 R U' R r'"),
+    ("OLL 13", "F U R U' R2 F' R U R U' R'"),
+    ("OLL 14", "R' F R U R' F' R F U' F'"),
+    ("OLL 15", "r' U' r R' U' R U r' U r"),
+    ("OLL 16", "r U r' R U R' U' r U' r'"),
+    ("OLL 17", "R U R' U R' F R F' U2 R' F R F'"),
+    ("OLL 18", "r U R' U R U2 r2 U' R U' R' U2 r"),
+    ("OLL 19", "M U R U R' U' M' R' F R F'"),
+    ("OLL 20", "r U R' U' M2 U R U' R' U' M'"),
+    ("OLL 21 (H)", "R U2 R' U' R U R' U' R U' R'"),
+    ("OLL 22 (Pi)", "R U2 R2 U' R2 U' R2 U2 R"),
+    ("OLL 23 (Headlights)", "R2 D' R U2 R' D R U2 R"),
+    ("OLL 24 (Chameleon)", "r U R' U' r' F R F'"),
+    ("OLL 25 (Bowtie)", "F' r U R' U' r' F R"),
+    ("OLL 26 (Anti-Sune)", "R U2 R' U' R U' R'"),
+    ("OLL 27 (Sune)", "R U R' U R U2 R'"),
+    ("OLL 28", "r U R' U' M U R U' R'"),
+    ("OLL 29", "R U R' U' R U' R' F' U' F R U R'"),
+    ("OLL 30", "F R' F R2 U' R' U' R U R' F2"),
+    ("OLL 31", "R' U' F U R U' R' F' R"),
+    ("OLL 32", "L U F' U' L' U L F L'"),
+    ("OLL 33", "R U R' U' R' F R F'"),
+    ("OLL 34", "R U R2 U' R' F R U R U' F'"),
+    ("OLL 35", "R U2 R2 F R F' R U2 R'"),
+    ("OLL 36", "L' U' L U' L' U L U L F' L' F"),
+    ("OLL 37", "F R' F' R U R U' R'"),
+    ("OLL 38", "R U R' U R U' R' U' R' F R F'"),
+    ("OLL 39", "L F' L' U' L U F U' L'"),
+    ("OLL 40", "R' F R U R' U' F' U R"),
+    ("OLL 41", "R U R' U R U2 R' F R U R' U' F'"),
+    ("OLL 42", "R' U' R U' R' U2 R F R U R' U' F'"),
+    ("OLL 43", "F' U' L' U L F"),
+    ("OLL 44", "F U R U' R' F'"),
+    ("OLL 45", "F R U R' U' F'"),
+    ("OLL 46", "R' U' R' F R F' U R"),
+    ("OLL 47", "R' U' R' F R F' R' F R F' U R"),
+    ("OLL 48", "F R U R' U' R U R' U' F'"),
+    ("OLL 49", "r U' r2 U r2 U r2 U' r"),
+    ("OLL 50", "r' U r2 U' r2 U' r2 U r'"),
+    ("OLL 51", "F U R U' R' U R U' R' F'"),
+    ("OLL 52", "R U R' U R U' B U' B' R'"),
+    ("OLL 53", "l' U2 L U L' U' L U L' U l"),
+    ("OLL 54", "r U2 R' U' R U R' U' R U' r'"),
+    ("OLL 55", "R' F R U R U' R2 F' R2 U' R' U R U R'"),
+    ("OLL 56", "r' U' r U' R' U R U' R' U R r' U r"),
+    ("OLL 57", "R U R' U' M' U R U' r'"),
+];
+
+/// The 21 PLL cases, each with an algorithm that solves it.
+pub const PLL_CASES: [(&str, &str); 21] = [
+    ("Aa perm", "x R' U R' D2 R U' R' D2 R2 x'"),
+    ("Ab perm", "x R2 D2 R U R' D2 R U' R x'"),
+    ("E perm", "x' R U' R' D R U R' D' R U R' D R U' R' D' x"),
+    ("F perm", "R' U' F' R U R' U' R' F R2 U' R' U' R U R' U R"),
+    ("Ga perm", "R2 U R' U R' U' R U' R2 U' D R' U R D'"),
+    ("Gb perm", "R' U' R U D' R2 U R' U R U' R U' R2 D"),
+    ("Gc perm", "R2 U' R U' R U R' U R2 U D' R U' R' D"),
+    ("Gd perm", "R U R' U' D R2 U' R U' R' U R' U R2 D'"),
+    ("H perm", "M2 U M2 U2 M2 U M2"),
+    ("Ja perm", "R' U L' U2 R U' R' U2 R L"),
+    ("Jb perm", "R U R' F' R U R' U' R' F R2 U' R'"),
+    (
+        "Na perm",
+        "R U R' U R U R' F' R U R' U' R' F R2 U' R' U2 R U' R'",
+    ),
+    ("Nb perm", "R' U R U' R' F' U' F R U R' F R' F' R U' R"),
+    ("Ra perm", "R U' R' U' R U R D R' U' R D' R' U2 R'"),
+    ("Rb perm", "R2 F R U R U' R' F' R U2 R' U2 R"),
+    ("T perm", "R U R' U' R' F R2 U' R' U' R U R' F'"),
+    ("Ua perm", "M2 U M U2 M' U M2"),
+    ("Ub perm", "M2 U' M U2 M' U' M2"),
+    ("V perm", "R' U R' U' y R' F' R2 U' R' U R' F R F"),
+    ("Y perm", "F R U' R' U' R U R' F' R U R' U' R' F R F'"),
+    ("Z perm", "M' U M2 U M2 U M' U2 M2"),
+];
+
+/// A last-layer pattern summed up so that it doesn't depend on which way
+/// the cube is turned about U or on the last-layer turn.
+type Fingerprint = Vec<u8>;
+
+static OLL_FINGERPRINTS: Lazy<Vec<(Fingerprint, &str)>> =
+    Lazy::new(|| fingerprints(&OLL_CASES, oll_fingerprint));
+
+static PLL_FINGERPRINTS: Lazy<Vec<(Fingerprint, &str)>> =
+    Lazy::new(|| fingerprints(&PLL_CASES, pll_fingerprint));
+
+/// The OLL or PLL case left once F2L is done, by name, or `None` before
+/// then or when the cube is solved up to a U turn.
+pub fn case_name(cube: &Cube) -> Option<&'static str> {
+    let (table, fingerprint) = match stages::cfop_stage(cube) {
+        CfopStage::Oll => (&*OLL_FINGERPRINTS, oll_fingerprint(cube)),
+        CfopStage::Pll => (&*PLL_FINGERPRINTS, pll_fingerprint(cube)),
+        _ => return None,
+    };
+    table
+        .iter()
+        .find(|(known, _)| *known == fingerprint)
+        .map(|(_, name)| *name)
+}
+
+/// Parses one of the case algorithms, spelling out wide turns and rotations
+/// as the face and slice turns `parse_algorithm` knows.
+pub fn case_moves(algorithm: &str) -> Vec<Move> {
+    let mut spelled = Vec::new();
+    for token in algorithm.split_whitespace() {
+        let (letter, suffix) = token.split_at(1);
+        let parts = match letter {
+            "r" => "R M'",
+            "l" => "L M",
+            "f" => "F S",
+            "b" => "B S'",
+            "u" => "U E'",
+            "d" => "D E",
+            "x" => "R M' L'",
+            "y" => "U E' D'",
+            "z" => "F S B'",
+            _ => {
+                spelled.push(token.to_string());
+                continue;
+            }
+        };
+        for part in parts.split(' ') {
+            let face = &part[..1];
+            let prime = part.ends_with('\'');
+            spelled.push(match suffix {
+                "2" => format!("{face}2"),
+                "'" if prime => face.to_string(),
+                "'" => format!("{face}'"),
+                _ => part.to_string(),
+            });
+        }
+    }
+    cube::parse_algorithm(&spelled.join(" ")).expect("case algorithms are valid")
+}
+
+fn fingerprints(
+    cases: &[(&'static str, &str)],
+    fingerprint: fn(&Cube) -> Fingerprint,
+) -> Vec<(Fingerprint, &'static str)> {
+    cases
+        .iter()
+        .map(|(name, algorithm)| {
+            let mut cube = Cube::new();
+            cube.apply_sequence(&cube::invert(&case_moves(algorithm)));
+            (fingerprint(&cube), *name)
+        })
+        .collect()
+}
+
+/// Which U-layer stickers show the U color.
+fn oll_fingerprint(cube: &Cube) -> Fingerprint {
+    canonical(cube, |cube, idx| {
+        u8::from(cube.face_colors()[idx] == cube.center_color(Face::Up))
+    })
+}
+
+/// For each U-layer side sticker, how many faces around from its own face
+/// the face of its color sits.
+fn pll_fingerprint(cube: &Cube) -> Fingerprint {
+    const SIDES: [Face; 4] = [Face::Front, Face::Right, Face::Back, Face::Left];
+    canonical(cube, |cube, idx| {
+        let face = facelet_descriptors()[idx].face;
+        let Some(at) = SIDES.iter().position(|side| *side == face) else {
+            return 0;
+        };
+        let color = cube.face_colors()[idx];
+        let home = SIDES
+            .iter()
+            .position(|side| cube.center_color(*side) == color)
+            .unwrap_or(at);
+        ((home + 4 - at) % 4) as u8
+    })
+}
+
+/// The smallest U-layer reading over every U turn of the layer and every
+/// y rotation of the whole cube.
+fn canonical(cube: &Cube, read: impl Fn(&Cube, usize) -> u8) -> Fingerprint {
+    let top: Vec<usize> = facelet_descriptors()
+        .iter()
+        .enumerate()
+        .filter(|(_, desc)| desc.coord.y == 1)
+        .map(|(idx, _)| idx)
+        .collect();
+    let mut best: Option<Fingerprint> = None;
+    let mut turned = *cube;
+    for _ in 0..4 {
+        turned.apply_move(Move::U);
+        let mut viewed = turned;
+        for _ in 0..4 {
+            viewed.rotate(Axis::Y, RotationDir::Clockwise);
+            let reading: Fingerprint = top.iter().map(|idx| read(&viewed, *idx)).collect();
+            if best.as_ref().is_none_or(|best| reading < *best) {
+                best = Some(reading);
+            }
+        }
+    }
+    best.expect("four turns were read")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn every_case_is_recognized_under_any_turn() {
+        for (name, algorithm) in OLL_CASES.iter().chain(&PLL_CASES) {
+            let mut cube = Cube::new();
+            cube.apply_sequence(&cube::invert(&case_moves(algorithm)));
+            assert_eq!(case_name(&cube), Some(*name));
+            cube.apply_move(Move::U);
+            cube.rotate(Axis::Y, RotationDir::CounterClockwise);
+            assert_eq!(case_name(&cube), Some(*name));
+        }
+    }
+
+    #[test]
+    fn fingerprints_are_distinct() {
+        for table in [&*OLL_FINGERPRINTS, &*PLL_FINGERPRINTS] {
+            let distinct: HashSet<&Fingerprint> = table.iter().map(|(print, _)| print).collect();
+            assert_eq!(distinct.len(), table.len());
+        }
+    }
+
+    #[test]
+    fn spells_out_wide_turns() {
+        assert_eq!(
+            case_moves("r' x2 R"),
+            cube::parse_algorithm("R' M R2 M2 L2 R").unwrap()
+        );
+    }
+}
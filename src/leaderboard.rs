@@ -0,0 +1,146 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub moves: usize,
+    pub scramble: String,
+}
+
+/// Fewest-moves solve per scramble, persisted as `moves<TAB>scramble` lines.
+pub struct Leaderboard {
+    path: Option<PathBuf>,
+    entries: Vec<Entry>,
+}
+
+impl Leaderboard {
+    pub fn load(path: Option<PathBuf>) -> io::Result<Self> {
+        let entries = match &path {
+            Some(path) => read_entries(path)?,
+            None => Vec::new(),
+        };
+        let mut board = Self { path, entries };
+        board.sort();
+        Ok(board)
+    }
+
+    /// A board that is never saved.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Records a solve and returns whether it set a new best for its scramble.
+    pub fn record(&mut self, scramble: &str, moves: usize) -> io::Result<bool> {
+        match self.entries.iter_mut().find(|e| e.scramble == scramble) {
+            Some(entry) if entry.moves <= moves => return Ok(false),
+            Some(entry) => entry.moves = moves,
+            None => self.entries.push(Entry {
+                moves,
+                scramble: scramble.to_string(),
+            }),
+        }
+        self.sort();
+        self.save()?;
+        Ok(true)
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    fn sort(&mut self) {
+        self.entries.sort_by_key(|e| e.moves);
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let body: String = self
+            .entries
+            .iter()
+            .map(|e| format!("{}\t{}\n", e.moves, e.scramble))
+            .collect();
+        fs::write(path, body)
+    }
+}
+
+fn read_entries(path: &Path) -> io::Result<Vec<Entry>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    Ok(text
+        .lines()
+        .filter_map(|line| {
+            let (moves, scramble) = line.split_once('\t')?;
+            Some(Entry {
+                moves: moves.trim().parse().ok()?,
+                scramble: scramble.trim().to_string(),
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_board(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "cubex-leaderboard-test-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("leaderboard.tsv")
+    }
+
+    #[test]
+    fn records_survive_a_reload_in_move_order() {
+        let path = temp_board("reload");
+        let mut board = Leaderboard::load(Some(path.clone())).unwrap();
+        assert!(board.entries().is_empty());
+        assert!(board.record("R U R' U'", 30).unwrap());
+        assert!(board.record("F2 B2", 12).unwrap());
+        let reloaded = Leaderboard::load(Some(path.clone())).unwrap();
+        assert_eq!(reloaded.entries(), board.entries());
+        assert_eq!(
+            reloaded.entries()[0],
+            Entry {
+                moves: 12,
+                scramble: "F2 B2".to_string(),
+            }
+        );
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn keeps_the_fewest_moves_per_scramble() {
+        let mut board = Leaderboard::load(None).unwrap();
+        assert!(board.record("R U", 20).unwrap());
+        assert!(!board.record("R U", 25).unwrap());
+        assert!(!board.record("R U", 20).unwrap());
+        assert!(board.record("R U", 14).unwrap());
+        assert_eq!(board.entries().len(), 1);
+        assert_eq!(board.entries()[0].moves, 14);
+    }
+
+    #[test]
+    fn skips_malformed_lines() {
+        let path = temp_board("malformed");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "17\tR U\nnot a line\nx\tF B\n\n9\tL D\n").unwrap();
+        let board = Leaderboard::load(Some(path.clone())).unwrap();
+        let moves: Vec<usize> = board.entries().iter().map(|e| e.moves).collect();
+        assert_eq!(moves, [9, 17]);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,80 @@
+use std::fs;
+use std::path::Path;
+
+use crate::cube::{self, Move};
+
+/// Algorithms bound to single keys, each run as one action. A macro key
+/// takes over whatever that key did before.
+#[derive(Clone, Debug, Default)]
+pub struct Macros {
+    bindings: Vec<(char, Vec<Move>)>,
+}
+
+impl Macros {
+    pub fn sequence(&self, key: char) -> Option<&[Move]> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|(_, moves)| moves.as_slice())
+    }
+}
+
+pub fn load(path: &Path) -> Result<Macros, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|err| format!("cannot read macros '{}': {err}", path.display()))?;
+    parse(&text).map_err(|err| format!("{}: {err}", path.display()))
+}
+
+/// Parses `key = "moves"` lines, e.g. `t = "R U R' U' R' F R F'"`, with
+/// `#` comments. Keys are single characters and a later line for the same
+/// key replaces the earlier one.
+pub fn parse(text: &str) -> Result<Macros, String> {
+    let mut macros = Macros::default();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let at = |msg: String| format!("line {}: {msg}", number + 1);
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| at(format!("expected key = \"moves\", got '{line}'")))?;
+        let key = key.trim();
+        let mut chars = key.chars();
+        let key = match (chars.next(), chars.next()) {
+            (Some(ch), None) => ch,
+            _ => return Err(at(format!("macro key '{key}' must be a single character"))),
+        };
+        let value = value
+            .trim()
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .ok_or_else(|| at(format!("moves for '{key}' must be a quoted string")))?;
+        let moves = cube::parse_algorithm(value).map_err(|err| at(err.to_string()))?;
+        macros.bindings.retain(|(bound, _)| *bound != key);
+        macros.bindings.push((key, moves));
+    }
+    Ok(macros)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_binds_keys_and_reports_bad_lines() {
+        let macros =
+            parse("# drills\nt = \"R U R' U' R' F R F'\"\n\nj = \"U\"\nj = \"U2\"").unwrap();
+        assert_eq!(
+            cube::format_sequence(macros.sequence('t').unwrap()),
+            "R U R' U' R' F R F'"
+        );
+        assert_eq!(macros.sequence('j'), Some(&[Move::U2][..]));
+        assert_eq!(macros.sequence('x'), None);
+
+        let err = parse("t = \"R U\"\ntt = \"R\"").unwrap_err();
+        assert!(err.starts_with("line 2:"), "{err}");
+        let err = parse("t = \"R X\"").unwrap_err();
+        assert!(err.contains("column 3"), "{err}");
+    }
+}
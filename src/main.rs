@@ -1,10 +1,33 @@
 mod app;
+mod background;
+mod beginner;
+mod bld;
+mod cache;
+mod capture;
+mod cli;
 mod config;
 mod cube;
+mod cube_explorer;
+mod effects;
+mod export;
 mod geometry;
+mod history;
 mod input;
+mod last_layer;
+mod leaderboard;
+mod macros;
+mod nxn;
+mod optimal;
+mod palette;
 mod raster;
+mod reconstruction;
+mod recording;
+mod sixel;
+mod solver;
+mod stages;
 mod terminal;
+mod theme;
+mod watch;
 
 use std::io;
 
@@ -12,15 +35,26 @@ use app::App;
 use terminal::{FrameWriter, TerminalGuard};
 
 fn main() {
-    if let Err(err) = run_app() {
+    let options = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("Error: {err}\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if let Err(err) = run_app(options) {
         eprintln!("Error: {err}");
         std::process::exit(1);
     }
 }
 
-fn run_app() -> io::Result<()> {
-    let _guard = TerminalGuard::new()?;
+fn run_app(mut options: cli::Options) -> io::Result<()> {
+    if let Some(theme) = options.theme.take() {
+        theme::install(theme);
+    }
+    palette::install(config::COLOR_DEPTH.unwrap_or_else(palette::ColorDepth::detect));
+    let _guard = TerminalGuard::new(options.mouse)?;
     let frame_writer = FrameWriter::new();
-    let mut app = App::new(frame_writer);
+    let mut app = App::new(frame_writer, options)?;
     app.run()
 }
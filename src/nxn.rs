@@ -0,0 +1,606 @@
+use rand::Rng;
+
+use crate::cube::{
+    Axis, Cube, FACELET_COUNT, Face, FaceColor, LatticePoint, Move, ParseError, RotationDir,
+    rotate_coord, rotate_face,
+};
+use crate::solver;
+
+pub const MIN_SIZE: usize = 2;
+pub const MAX_SIZE: usize = 9;
+
+/// Standard 4x4 parity fixes, in WCA notation: the first flips the UF
+/// dedge, the second swaps the UF and UB dedges and leaves U a half turn
+/// off.
+pub const OLL_PARITY_ALG: &str = "2R2 B2 U2 2L U2 2R' U2 2R U2 F2 2R F2 2L' B2 2R2";
+pub const PLL_PARITY_ALG: &str = "2R2 U2 2R2 Uw2 2R2 Uw2";
+
+/// A turn of one or more layers counted from `face`: `depth` 1 is the outer
+/// layer, and `wide` turns every layer from the outer one down to `depth`.
+/// `turns` are clockwise quarter turns as seen looking at `face`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LayerMove {
+    pub face: Face,
+    pub depth: usize,
+    pub wide: bool,
+    pub turns: u8,
+}
+
+impl LayerMove {
+    /// WCA-style notation: `R`, `2R` for the second layer, `Rw` / `3Rw` for
+    /// wide turns.
+    pub fn notation(self) -> String {
+        let prefix = match (self.wide, self.depth) {
+            (false, 1) | (true, 2) => String::new(),
+            (_, depth) => depth.to_string(),
+        };
+        let wide = if self.wide { "w" } else { "" };
+        format!(
+            "{prefix}{}{wide}{}",
+            self.face.letter(),
+            turn_suffix(self.turns)
+        )
+    }
+
+    /// SiGN notation: `R`, `3R` for the third layer alone, `r` / `3r` for
+    /// wide turns.
+    pub fn sign_notation(self) -> String {
+        let prefix = match (self.wide, self.depth) {
+            (false, 1) | (true, 2) => String::new(),
+            (_, depth) => depth.to_string(),
+        };
+        let letter = if self.wide {
+            self.face.letter().to_ascii_lowercase()
+        } else {
+            self.face.letter()
+        };
+        format!("{prefix}{letter}{}", turn_suffix(self.turns))
+    }
+}
+
+/// How `LayerMove`s are written out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Notation {
+    /// `Rw`, `3Rw`, `2R`, as in WCA scrambles.
+    Wca,
+    /// `r`, `3r`, `2R`, as in SiGN.
+    #[cfg_attr(not(test), allow(dead_code))]
+    Sign,
+}
+
+impl Notation {
+    pub fn format(self, mv: LayerMove) -> String {
+        match self {
+            Notation::Wca => mv.notation(),
+            Notation::Sign => mv.sign_notation(),
+        }
+    }
+}
+
+fn turn_suffix(turns: u8) -> &'static str {
+    match turns % 4 {
+        2 => "2",
+        3 => "'",
+        _ => "",
+    }
+}
+
+/// Parses big-cube moves for a cube of `size` in WCA or SiGN notation,
+/// mixed freely: `R`, `3R` (third layer only), `Rw` / `r` (two layers),
+/// `3Rw` / `3r` (three layers), ranges such as `2-3u'` (layers two and
+/// three), `M`, `E` and `S` on odd sizes, and the rotations `x`, `y`, `z`.
+/// A range not starting at the outer layer turns as one move per layer.
+pub fn parse_algorithm(text: &str, size: usize) -> Result<Vec<LayerMove>, ParseError> {
+    let mut moves = Vec::new();
+    let mut chars = text.chars().enumerate().peekable();
+    while let Some(&(index, ch)) = chars.peek() {
+        if ch.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let error = |message: String| ParseError {
+            column: index + 1,
+            message,
+        };
+        let from = read_number(&mut chars);
+        let to = match chars.peek() {
+            Some((_, '-')) if from.is_some() => {
+                chars.next();
+                Some(
+                    read_number(&mut chars)
+                        .ok_or_else(|| error("range needs an end layer".to_string()))?,
+                )
+            }
+            _ => None,
+        };
+        let Some((_, letter)) = chars.next() else {
+            return Err(error("expected a move after the layer number".to_string()));
+        };
+        let wide_letter = letter.is_ascii_lowercase();
+        let face = match letter.to_ascii_uppercase() {
+            'U' => Face::Up,
+            'D' => Face::Down,
+            'R' => Face::Right,
+            'L' => Face::Left,
+            'F' => Face::Front,
+            'B' => Face::Back,
+            _ => {
+                let (face, depth) = match letter {
+                    'x' => (Face::Right, size),
+                    'y' => (Face::Up, size),
+                    'z' => (Face::Front, size),
+                    'M' if size % 2 == 1 => (Face::Left, size / 2 + 1),
+                    'E' if size % 2 == 1 => (Face::Down, size / 2 + 1),
+                    'S' if size % 2 == 1 => (Face::Front, size / 2 + 1),
+                    'M' | 'E' | 'S' => {
+                        return Err(error(format!("{letter} needs an odd-sized cube")));
+                    }
+                    _ => return Err(error(format!("expected a move, found '{letter}'"))),
+                };
+                if from.is_some() {
+                    return Err(error(format!("{letter} takes no layer number")));
+                }
+                let turns = read_turns(&mut chars).map_err(error)?;
+                moves.push(LayerMove {
+                    face,
+                    depth,
+                    wide: letter.is_ascii_lowercase(),
+                    turns,
+                });
+                continue;
+            }
+        };
+        let wide = wide_letter
+            || matches!(chars.peek(), Some((_, 'w'))) && {
+                chars.next();
+                true
+            };
+        let turns = read_turns(&mut chars).map_err(error)?;
+        let (first, last) = match (from, to) {
+            (Some(first), Some(last)) => (first, last),
+            (Some(depth), None) => (if wide { 1 } else { depth }, depth),
+            (None, _) => (1, if wide { 2 } else { 1 }),
+        };
+        if first == 0 || first > last || last > size {
+            return Err(error(format!(
+                "layers {first} to {last} don't fit a {size}x{size}x{size}"
+            )));
+        }
+        if first == 1 {
+            moves.push(LayerMove {
+                face,
+                depth: last,
+                wide: last > 1,
+                turns,
+            });
+        } else {
+            moves.extend((first..=last).map(|depth| LayerMove {
+                face,
+                depth,
+                wide: false,
+                turns,
+            }));
+        }
+    }
+    Ok(moves)
+}
+
+type Chars<'a> = std::iter::Peekable<std::iter::Enumerate<std::str::Chars<'a>>>;
+
+fn read_number(chars: &mut Chars) -> Option<usize> {
+    let mut digits = String::new();
+    while let Some(&(_, digit)) = chars.peek()
+        && digit.is_ascii_digit()
+    {
+        digits.push(digit);
+        chars.next();
+    }
+    digits.parse().ok()
+}
+
+/// Reads `2`, `'` or `2'` after a move as quarter turns.
+fn read_turns(chars: &mut Chars) -> Result<u8, String> {
+    let mut turns = 1;
+    if let Some((_, '2')) = chars.peek() {
+        chars.next();
+        turns = 2;
+    }
+    if let Some((_, '\'' | '’')) = chars.peek() {
+        chars.next();
+        turns = 4 - turns;
+    }
+    match chars.peek() {
+        Some((_, next)) if next.is_ascii_digit() || matches!(next, '\'' | '’') => {
+            Err("too many modifiers".to_string())
+        }
+        _ => Ok(turns),
+    }
+}
+
+/// A cube of any size from `MIN_SIZE` to `MAX_SIZE`. Facelets are stored
+/// face by face in `Face::all()` order, then row by row, matching the 3x3
+/// `Cube` layout when the size is 3.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BigCube {
+    size: usize,
+    stickers: Vec<FaceColor>,
+    /// Whether an odd number of inner-layer quarter turns has been made,
+    /// i.e. the wing edges are an odd permutation away from solved.
+    wing_parity: bool,
+}
+
+impl BigCube {
+    pub fn new(size: usize) -> Self {
+        assert!(
+            (MIN_SIZE..=MAX_SIZE).contains(&size),
+            "unsupported size {size}"
+        );
+        let stickers = Face::all()
+            .iter()
+            .flat_map(|face| std::iter::repeat_n(face.default_color(), size * size))
+            .collect();
+        Self {
+            size,
+            stickers,
+            wing_parity: false,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn face_colors(&self) -> &[FaceColor] {
+        &self.stickers
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(self.size);
+    }
+
+    /// Every face a single color; big cubes have no fixed centers to tell
+    /// a solved cube from a rotated one.
+    pub fn is_solved(&self) -> bool {
+        self.stickers
+            .chunks(self.size * self.size)
+            .all(|face| face.iter().all(|color| *color == face[0]))
+    }
+
+    /// On a 4x4, whether reduction will end in OLL parity (a single flipped
+    /// dedge) however the centers and edges get paired.
+    pub fn has_oll_parity(&self) -> bool {
+        self.size == 4 && self.wing_parity
+    }
+
+    /// On a 4x4, whether the centers are solved and the edges paired, so
+    /// it solves like a 3x3 from here, parity aside.
+    pub fn is_reduced(&self) -> bool {
+        self.reduced().is_some()
+    }
+
+    /// On a 4x4 whose centers are solved and edges paired, whether the two
+    /// pieces swapped that a 3x3 can't have (PLL parity).
+    pub fn has_pll_parity(&self) -> bool {
+        self.reduced()
+            .is_some_and(|cube| solver::has_swap_parity(&cube))
+    }
+
+    /// A 4x4 with its centers solved and edges paired as the 3x3 it now
+    /// plays as: the outer corner, one wing of each edge and one center
+    /// sticker per face, recolored so each center has its usual color.
+    fn reduced(&self) -> Option<Cube> {
+        const CENTERS: [usize; 4] = [5, 6, 9, 10];
+        const WING_PAIRS: [(usize, usize); 4] = [(1, 2), (4, 8), (7, 11), (13, 14)];
+        const SAMPLED: [usize; 3] = [0, 1, 3];
+        if self.size != 4 {
+            return None;
+        }
+        let faces: Vec<&[FaceColor]> = self.stickers.chunks(16).collect();
+        let reduced = faces.iter().all(|face| {
+            CENTERS.iter().all(|&i| face[i] == face[CENTERS[0]])
+                && WING_PAIRS.iter().all(|&(a, b)| face[a] == face[b])
+        });
+        if !reduced {
+            return None;
+        }
+        let recolor = |color: FaceColor| {
+            let face = faces.iter().position(|face| face[CENTERS[0]] == color)?;
+            Some(Face::all()[face].default_color())
+        };
+        let mut colors = [FaceColor::White; FACELET_COUNT];
+        for (face, stickers) in faces.iter().enumerate() {
+            for (row, &from_row) in SAMPLED.iter().enumerate() {
+                for (col, &from_col) in SAMPLED.iter().enumerate() {
+                    colors[face * 9 + row * 3 + col] = recolor(stickers[from_row * 4 + from_col])?;
+                }
+            }
+        }
+        Some(Cube::from_colors(colors))
+    }
+
+    pub fn apply(&mut self, mv: LayerMove) {
+        let spec = mv.face.spec();
+        let depth = mv.depth.clamp(1, self.size);
+        let layers = if mv.wide { 1..=depth } else { depth..=depth };
+        for from_face in layers {
+            let index = if spec.normal.dir > 0 {
+                self.size - from_face
+            } else {
+                from_face - 1
+            };
+            // Clockwise seen from the face is clockwise about the axis only
+            // for faces on its positive end.
+            let dir = if spec.normal.dir > 0 {
+                RotationDir::Clockwise
+            } else {
+                RotationDir::CounterClockwise
+            };
+            for _ in 0..mv.turns % 4 {
+                self.turn_layer(spec.normal.axis, index, dir);
+            }
+        }
+    }
+
+    /// Applies a 3x3 move: outer turns act on the outer layers and slices
+    /// on the middle layer, which only exists for odd sizes.
+    pub fn apply_move(&mut self, mv: Move) {
+        let def = mv.def();
+        let index = match def.layer {
+            -1 => 0,
+            1 => self.size - 1,
+            _ if self.size % 2 == 1 => self.size / 2,
+            _ => return,
+        };
+        for _ in 0..def.turns {
+            self.turn_layer(def.axis, index, def.dir);
+        }
+    }
+
+    /// WCA-style random-move scramble of outer and wide turns that never
+    /// turns the same axis twice in a row. Wide turns reach the inner layers
+    /// (so both parities come up); on even sizes they are limited to U, R
+    /// and F, since Dw, Lw and Bw only repeat those plus a cube rotation.
+    pub fn scramble<R: Rng + ?Sized>(&mut self, len: usize, rng: &mut R) -> Vec<LayerMove> {
+        let mut last_axis: Option<Axis> = None;
+        let mut sequence = Vec::with_capacity(len);
+        while sequence.len() < len {
+            let face = Face::all()[rng.gen_range(0..6)];
+            let axis = face.spec().normal.axis;
+            if Some(axis) == last_axis {
+                continue;
+            }
+            let wide_allowed = self.size >= 4
+                && (self.size % 2 == 1 || matches!(face, Face::Up | Face::Right | Face::Front));
+            let depth = if wide_allowed {
+                rng.gen_range(1..=self.size / 2)
+            } else {
+                1
+            };
+            last_axis = Some(axis);
+            let mv = LayerMove {
+                face,
+                depth,
+                wide: depth > 1,
+                turns: rng.gen_range(1..=3),
+            };
+            self.apply(mv);
+            sequence.push(mv);
+        }
+        sequence
+    }
+
+    /// Turns layer `index` (0 at the negative end of `axis`) a quarter turn.
+    fn turn_layer(&mut self, axis: Axis, index: usize, dir: RotationDir) {
+        let n = self.size as i8;
+        let layer = 2 * index as i8 - (n - 1);
+        if index != 0 && index != self.size - 1 && layer != 0 {
+            self.wing_parity = !self.wing_parity;
+        }
+        let mut updated = self.stickers.clone();
+        for (idx, color) in self.stickers.iter().enumerate() {
+            let (face, point) = self.position(idx);
+            let component = point.component(axis);
+            let on_end_face = component.abs() == n && component.signum() == layer.signum();
+            let in_layer = component == layer || (on_end_face && layer.abs() == n - 1);
+            if in_layer {
+                let target =
+                    self.index_at(rotate_face(face, axis, dir), rotate_coord(point, axis, dir));
+                updated[target] = *color;
+            }
+        }
+        self.stickers = updated;
+    }
+
+    /// Face and doubled lattice position of facelet `idx`: in-plane
+    /// components are odd or even steps in `-(n-1)..=(n-1)` and the
+    /// normal component is `±n`, so every sticker gets its own point.
+    fn position(&self, idx: usize) -> (Face, LatticePoint) {
+        let n = self.size;
+        let face = Face::all()[idx / (n * n)];
+        let row = (idx % (n * n) / n) as i8;
+        let col = (idx % n) as i8;
+        let n = n as i8;
+        let spec = face.spec();
+        let mut point = LatticePoint::zero();
+        point.set_component(spec.normal.axis, spec.normal.dir * n);
+        point.add_component(spec.right.axis, spec.right.dir * (2 * col - (n - 1)));
+        point.add_component(spec.up.axis, spec.up.dir * ((n - 1) - 2 * row));
+        (face, point)
+    }
+
+    fn index_at(&self, face: Face, point: LatticePoint) -> usize {
+        let n = self.size as i8;
+        let spec = face.spec();
+        let col = (point.component(spec.right.axis) * spec.right.dir + (n - 1)) / 2;
+        let row = ((n - 1) - point.component(spec.up.axis) * spec.up.dir) / 2;
+        let face_index = Face::all()
+            .iter()
+            .position(|f| *f == face)
+            .expect("face in Face::all");
+        face_index * self.size * self.size + row as usize * self.size + col as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::Cube;
+
+    #[test]
+    fn size_three_matches_the_3x3_engine() {
+        let moves = [
+            Move::R,
+            Move::U,
+            Move::FPrime,
+            Move::L2,
+            Move::M,
+            Move::D,
+            Move::B2,
+            Move::S,
+        ];
+        let mut cube = Cube::new();
+        let mut big = BigCube::new(3);
+        cube.apply_sequence(&moves);
+        for mv in moves {
+            big.apply_move(mv);
+        }
+        assert_eq!(big.face_colors(), cube.face_colors());
+    }
+
+    #[test]
+    fn parity_algorithms_make_their_parity() {
+        let run = |alg: &str| {
+            let mut cube = BigCube::new(4);
+            for mv in parse_algorithm(alg, 4).unwrap() {
+                cube.apply(mv);
+            }
+            cube
+        };
+        let oll = run(OLL_PARITY_ALG);
+        assert!(oll.has_oll_parity());
+        assert!(!oll.has_pll_parity());
+        let reduced = oll.reduced().unwrap();
+        assert_eq!(reduced.differing_facelets(&Cube::new()).len(), 2);
+
+        let mut pll = run(PLL_PARITY_ALG);
+        assert!(!pll.has_oll_parity());
+        assert!(pll.has_pll_parity());
+        // Only the F and B stickers of the swapped dedges are out of place.
+        pll.apply_move(Move::U2);
+        let reduced = pll.reduced().unwrap();
+        assert_eq!(reduced.differing_facelets(&Cube::new()).len(), 2);
+
+        let mut solved = BigCube::new(4);
+        assert!(!solved.has_pll_parity());
+        solved.apply_move(Move::R);
+        assert!(!solved.has_pll_parity());
+    }
+
+    #[test]
+    fn layer_turns_return_after_four_quarters() {
+        for size in MIN_SIZE..=5 {
+            let mut cube = BigCube::new(size);
+            let mv = LayerMove {
+                face: Face::Right,
+                depth: size / 2,
+                wide: true,
+                turns: 1,
+            };
+            cube.apply(mv);
+            assert!(!cube.is_solved(), "{size}");
+            for _ in 0..3 {
+                cube.apply(mv);
+            }
+            assert!(cube.is_solved(), "{size}");
+        }
+    }
+
+    #[test]
+    fn notation_follows_wca_style() {
+        let mv = |depth, wide, turns| LayerMove {
+            face: Face::Right,
+            depth,
+            wide,
+            turns,
+        };
+        assert_eq!(mv(1, false, 1).notation(), "R");
+        assert_eq!(mv(2, false, 3).notation(), "2R'");
+        assert_eq!(mv(2, true, 2).notation(), "Rw2");
+        assert_eq!(mv(3, true, 1).notation(), "3Rw");
+    }
+
+    #[test]
+    fn parses_wca_and_sign_notation() {
+        let mv = |face, depth, wide, turns| LayerMove {
+            face,
+            depth,
+            wide,
+            turns,
+        };
+        assert_eq!(
+            parse_algorithm("R 3Rw2 r' 3r 2L", 5).unwrap(),
+            vec![
+                mv(Face::Right, 1, false, 1),
+                mv(Face::Right, 3, true, 2),
+                mv(Face::Right, 2, true, 3),
+                mv(Face::Right, 3, true, 1),
+                mv(Face::Left, 2, false, 1),
+            ]
+        );
+        assert_eq!(
+            parse_algorithm("2-3u' 1-2Fw x M2", 5).unwrap(),
+            vec![
+                mv(Face::Up, 2, false, 3),
+                mv(Face::Up, 3, false, 3),
+                mv(Face::Front, 2, true, 1),
+                mv(Face::Right, 5, true, 1),
+                mv(Face::Left, 3, false, 2),
+            ]
+        );
+        assert_eq!(parse_algorithm("R 5R", 4).unwrap_err().column, 3);
+        assert!(parse_algorithm("M", 4).is_err());
+        assert!(parse_algorithm("R2'2", 4).is_err());
+
+        let scramble = parse_algorithm("Rw2 3Fw' 2D U", 6).unwrap();
+        for notation in [Notation::Wca, Notation::Sign] {
+            let text: Vec<String> = scramble.iter().map(|mv| notation.format(*mv)).collect();
+            assert_eq!(parse_algorithm(&text.join(" "), 6).unwrap(), scramble);
+        }
+        assert_eq!(Notation::Sign.format(scramble[1]), "3f'");
+    }
+
+    #[test]
+    fn inner_quarter_turns_flip_4x4_parity() {
+        let mut cube = BigCube::new(4);
+        let inner = LayerMove {
+            face: Face::Right,
+            depth: 2,
+            wide: false,
+            turns: 1,
+        };
+        cube.apply(inner);
+        assert!(cube.has_oll_parity());
+        cube.apply(LayerMove { turns: 2, ..inner });
+        assert!(cube.has_oll_parity());
+        cube.apply(LayerMove {
+            wide: true,
+            ..inner
+        });
+        assert!(!cube.has_oll_parity());
+    }
+
+    #[test]
+    fn even_scrambles_only_use_urf_wide_turns() {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let mut cube = BigCube::new(4);
+        let scramble = cube.scramble(200, &mut rng);
+        assert!(scramble.iter().any(|mv| mv.wide));
+        assert!(
+            scramble
+                .iter()
+                .filter(|mv| mv.wide)
+                .all(|mv| matches!(mv.face, Face::Up | Face::Right | Face::Front))
+        );
+    }
+}
@@ -0,0 +1,325 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use once_cell::sync::{Lazy, OnceCell};
+
+use crate::cache;
+use crate::cube::{Cube, Move};
+use crate::solver::{self, CORNER_PERMS, CubieCube, MOVE_COUNT, SOLVED, TABLES, TWISTS, Tables};
+
+/// Every position solves in at most 20 face turns.
+const GODS_NUMBER: usize = 20;
+const CORNER_STATES: usize = CORNER_PERMS * TWISTS;
+/// Marks a corner state the table build has not reached yet.
+const UNSEEN: u8 = 0xF;
+const CORNER_CACHE_FILE: &str = "corner-depths.bin";
+/// Bump whenever the corner coordinates or table layout change, so stale
+/// caches get rebuilt.
+const CORNER_CACHE_VERSION: u32 = 1;
+
+/// What a background solve started by `spawn` reports.
+#[derive(Debug)]
+pub enum Update {
+    /// The corner table is being built, which only happens when there is
+    /// no valid cached copy.
+    BuildingTables,
+    /// No solution is shorter than this; searching at this length now.
+    Searching(usize),
+    Finished(Result<Vec<Move>, String>),
+}
+
+/// Where each corner permutation goes under each of the 18 face turns.
+static CORNER_MOVE: Lazy<Vec<u16>> = Lazy::new(|| {
+    solver::move_table(
+        CORNER_PERMS,
+        0..MOVE_COUNT,
+        |c, i| solver::unrank(i, &mut c.cp),
+        |c| solver::rank(&c.cp),
+    )
+});
+
+static CORNER_DEPTHS: OnceCell<NibbleTable> = OnceCell::new();
+
+/// Four-bit entries packed two to a byte, keeping the 88 million corner
+/// states at 44 MB.
+struct NibbleTable {
+    bytes: Vec<u8>,
+}
+
+impl NibbleTable {
+    fn new(len: usize, fill: u8) -> Self {
+        Self {
+            bytes: vec![fill << 4 | fill; len.div_ceil(2)],
+        }
+    }
+
+    fn get(&self, index: usize) -> u8 {
+        self.bytes[index / 2] >> (index % 2 * 4) & 0xF
+    }
+
+    fn set(&mut self, index: usize, value: u8) {
+        let shift = index % 2 * 4;
+        let byte = &mut self.bytes[index / 2];
+        *byte = *byte & !(0xF << shift) | value << shift;
+    }
+}
+
+/// The corner table from the cache, or else built and then cached. Caching
+/// is best effort: without a data directory the table is rebuilt each run.
+fn corner_depths(progress: &mut impl FnMut(Update)) -> &'static NibbleTable {
+    CORNER_DEPTHS.get_or_init(|| {
+        let path = cache::path(CORNER_CACHE_FILE);
+        let len = CORNER_STATES.div_ceil(2);
+        if let Some(bytes) = path
+            .as_deref()
+            .and_then(|path| cache::read(path, CORNER_CACHE_VERSION, len))
+        {
+            return NibbleTable { bytes };
+        }
+        progress(Update::BuildingTables);
+        let table = build_corner_depths();
+        if let Some(path) = path {
+            let _ = cache::write(&path, CORNER_CACHE_VERSION, &table.bytes);
+        }
+        table
+    })
+}
+
+fn corner_move(state: usize, m: usize, tables: &Tables) -> usize {
+    let (perm, twist) = (state / TWISTS, state % TWISTS);
+    CORNER_MOVE[perm * MOVE_COUNT + m] as usize * TWISTS
+        + tables.twist_move[twist * MOVE_COUNT + m] as usize
+}
+
+/// Moves needed to solve the corners alone from every permutation and
+/// twist, filled one depth at a time by scanning the whole table: forwards
+/// from the newest layer while it is small, backwards from the unreached
+/// states once they are the smaller set.
+fn build_corner_depths() -> NibbleTable {
+    let tables = &*TABLES;
+    let mut depths = NibbleTable::new(CORNER_STATES, UNSEEN);
+    depths.set(0, 0);
+    let mut reached = 1;
+    let mut depth = 0;
+    while reached < CORNER_STATES {
+        let backwards = reached > CORNER_STATES / 2;
+        for state in 0..CORNER_STATES {
+            if backwards {
+                if depths.get(state) == UNSEEN
+                    && (0..MOVE_COUNT).any(|m| depths.get(corner_move(state, m, tables)) == depth)
+                {
+                    depths.set(state, depth + 1);
+                    reached += 1;
+                }
+            } else if depths.get(state) == depth {
+                for m in 0..MOVE_COUNT {
+                    let next = corner_move(state, m, tables);
+                    if depths.get(next) == UNSEEN {
+                        depths.set(next, depth + 1);
+                        reached += 1;
+                    }
+                }
+            }
+        }
+        depth += 1;
+    }
+    depths
+}
+
+/// A search node: the full state for the goal test plus the coordinates
+/// the heuristics look up.
+#[derive(Clone, Copy)]
+struct Node {
+    cube: CubieCube,
+    twist: u16,
+    flip: u16,
+    slice: u16,
+    corners: u16,
+}
+
+impl Node {
+    fn new(cube: CubieCube) -> Self {
+        Self {
+            cube,
+            twist: cube.twist(),
+            flip: cube.flip(),
+            slice: cube.slice(),
+            corners: solver::rank(&cube.cp),
+        }
+    }
+
+    fn apply(&self, m: usize, tables: &Tables) -> Self {
+        Self {
+            cube: self.cube.apply(m),
+            twist: tables.twist_move[self.twist as usize * MOVE_COUNT + m],
+            flip: tables.flip_move[self.flip as usize * MOVE_COUNT + m],
+            slice: tables.slice_move[self.slice as usize * MOVE_COUNT + m],
+            corners: CORNER_MOVE[self.corners as usize * MOVE_COUNT + m],
+        }
+    }
+}
+
+/// Lower bound from the two-phase solver's phase 1 tables: orienting the
+/// pieces and gathering the E-slice edges can't take more moves than
+/// solving.
+fn phase1_bound(node: &Node, tables: &Tables) -> usize {
+    let slice = node.slice as usize;
+    let twist = tables.slice_twist_depth[slice * TWISTS + node.twist as usize];
+    let flip = tables.slice_flip_depth[slice * solver::FLIPS + node.flip as usize];
+    twist.max(flip) as usize
+}
+
+fn full_bound(node: &Node, tables: &Tables, corner_depths: &NibbleTable) -> usize {
+    let corners = corner_depths.get(node.corners as usize * TWISTS + node.twist as usize);
+    phase1_bound(node, tables).max(corners as usize)
+}
+
+struct Search<'a, H> {
+    tables: &'a Tables,
+    bound: H,
+    /// Checked at every node; once set the search unwinds with no answer.
+    stop: &'a AtomicBool,
+    path: Vec<usize>,
+}
+
+impl<H: Fn(&Node, &Tables) -> usize> Search<'_, H> {
+    fn solve(&mut self, start: CubieCube, mut progress: impl FnMut(usize)) -> Option<Vec<usize>> {
+        let root = Node::new(start);
+        let first = (self.bound)(&root, self.tables);
+        for length in first..=GODS_NUMBER {
+            progress(length);
+            if self.search(&root, length) {
+                return Some(std::mem::take(&mut self.path));
+            }
+            if self.stopped() {
+                return None;
+            }
+        }
+        None
+    }
+
+    /// Depth-first search for a solution of exactly `depth` more moves,
+    /// cut off wherever the bound says that is too few.
+    fn search(&mut self, node: &Node, depth: usize) -> bool {
+        if depth == 0 {
+            return node.cube == SOLVED;
+        }
+        if self.stopped() || (self.bound)(node, self.tables) > depth {
+            return false;
+        }
+        for m in 0..MOVE_COUNT {
+            if solver::redundant(self.path.last().copied(), m) {
+                continue;
+            }
+            self.path.push(m);
+            if self.search(&node.apply(m, self.tables), depth - 1) {
+                return true;
+            }
+            self.path.pop();
+        }
+        false
+    }
+
+    fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+}
+
+/// Finds a shortest solution with IDA*, bounded by the phase 1 tables and a
+/// full corner table (built on first use, taking several seconds, then
+/// loaded from the table cache). Can take minutes on a deep position, reporting each
+/// length it rules out through `progress`. Setting `stop` abandons the
+/// search, though a table build already under way runs to the end.
+pub fn solve(
+    cube: &Cube,
+    stop: &AtomicBool,
+    mut progress: impl FnMut(Update),
+) -> Result<Vec<Move>, String> {
+    let start = solver::cubie_state(cube)?;
+    let corners = corner_depths(&mut progress);
+    let mut search = Search {
+        tables: &TABLES,
+        bound: |node: &Node, tables: &Tables| full_bound(node, tables, corners),
+        stop,
+        path: Vec::new(),
+    };
+    match search.solve(start, |length| progress(Update::Searching(length))) {
+        Some(path) => Ok(solver::to_moves(&path)),
+        None if stop.load(Ordering::Relaxed) => Err("cancelled".to_string()),
+        None => Err(format!("no solution within {GODS_NUMBER} moves")),
+    }
+}
+
+/// Runs `solve` on a background thread; progress and then the result
+/// arrive on the returned channel. Setting the returned flag stops the
+/// search and lets the thread finish.
+pub fn spawn(cube: Cube) -> (Receiver<Update>, Arc<AtomicBool>) {
+    let (sender, receiver) = mpsc::channel();
+    let stop = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&stop);
+    thread::spawn(move || {
+        let result = solve(&cube, &flag, |update| {
+            let _ = sender.send(update);
+        });
+        let _ = sender.send(Update::Finished(result));
+    });
+    (receiver, stop)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube;
+
+    #[test]
+    fn nibble_table_packs_two_entries_per_byte() {
+        let mut table = NibbleTable::new(5, UNSEEN);
+        table.set(2, 7);
+        table.set(3, 0);
+        assert_eq!(
+            (0..5).map(|i| table.get(i)).collect::<Vec<_>>(),
+            [15, 15, 7, 0, 15]
+        );
+    }
+
+    #[test]
+    fn finds_shortest_solutions() {
+        // The corner table takes too long to build in a debug test run, so
+        // search with the phase 1 bound alone.
+        let mut search = Search {
+            tables: &TABLES,
+            bound: phase1_bound,
+            stop: &AtomicBool::new(false),
+            path: Vec::new(),
+        };
+        for (scramble, optimal) in [("R U R' U'", 4), ("R U2 R' U2 R", 5), ("F R2 F'", 3)] {
+            let moves = cube::parse_algorithm(scramble).unwrap();
+            let mut cube = Cube::new();
+            cube.apply_sequence(&moves);
+            let start = solver::cubie_state(&cube).unwrap();
+            let path = search.solve(start, |_| {}).unwrap();
+            assert_eq!(path.len(), optimal, "{scramble}");
+            cube.apply_sequence(&solver::to_moves(&path));
+            assert!(cube.is_solved(), "{scramble}");
+        }
+    }
+
+    #[test]
+    fn stopped_search_gives_up() {
+        let stop = AtomicBool::new(true);
+        let mut search = Search {
+            tables: &TABLES,
+            bound: phase1_bound,
+            stop: &stop,
+            path: Vec::new(),
+        };
+        let mut cube = Cube::new();
+        cube.apply_sequence(&cube::parse_algorithm("R U R' U'").unwrap());
+        let start = solver::cubie_state(&cube).unwrap();
+        let mut lengths = Vec::new();
+        assert_eq!(search.solve(start, |length| lengths.push(length)), None);
+        assert_eq!(lengths.len(), 1);
+    }
+}
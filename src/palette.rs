@@ -0,0 +1,186 @@
+use crossterm::style::Color;
+use once_cell::sync::OnceCell;
+
+/// How many colors the terminal can show, from richest to poorest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorDepth {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl ColorDepth {
+    /// Reads `COLORTERM` and `TERM` the way most terminals advertise
+    /// themselves; anything unrecognized gets the 16 basic colors.
+    pub fn detect() -> Self {
+        let colorterm = std::env::var("COLORTERM").ok();
+        let term = std::env::var("TERM").ok();
+        Self::from_env(colorterm.as_deref(), term.as_deref())
+    }
+
+    fn from_env(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        if matches!(colorterm, Some("truecolor" | "24bit")) {
+            ColorDepth::TrueColor
+        } else if term.is_some_and(|term| term.contains("256color")) {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+}
+
+static ACTIVE: OnceCell<ColorDepth> = OnceCell::new();
+
+/// The depth installed at startup, or truecolor so nothing is changed.
+pub fn active() -> ColorDepth {
+    *ACTIVE.get_or_init(|| ColorDepth::TrueColor)
+}
+
+/// Makes `depth` the active one; only the first call has any effect.
+pub fn install(depth: ColorDepth) {
+    let _ = ACTIVE.set(depth);
+}
+
+/// `color` as the active depth can show it: RGB colors fall back to the
+/// nearest entry of the 256-color palette or to the nearest of the 16 basic
+/// colors. Named colors are already basic and pass through.
+pub fn fit(color: Color) -> Color {
+    fit_to(color, active())
+}
+
+fn fit_to(color: Color, depth: ColorDepth) -> Color {
+    match (depth, color) {
+        (ColorDepth::TrueColor, _) => color,
+        (ColorDepth::Ansi256, Color::Rgb { r, g, b }) => Color::AnsiValue(nearest_256([r, g, b])),
+        (ColorDepth::Ansi16, Color::Rgb { r, g, b }) => nearest_16([r, g, b]),
+        (ColorDepth::Ansi16, Color::AnsiValue(value)) => nearest_16(ansi_rgb(value)),
+        _ => color,
+    }
+}
+
+/// RGB value of `color`, or `None` for the terminal's own default.
+pub fn to_rgb(color: Color) -> Option<[u8; 3]> {
+    match color {
+        Color::Rgb { r, g, b } => Some([r, g, b]),
+        Color::AnsiValue(value) => Some(ansi_rgb(value)),
+        named => BASIC
+            .iter()
+            .find(|(basic, _)| *basic == named)
+            .map(|(_, rgb)| *rgb),
+    }
+}
+
+/// Channel levels of the 6x6x6 color cube at indices 16 to 231.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The basic colors with xterm's default RGB values. Dark yellow comes out
+/// brown or orange on most terminals, which is what orange stickers want.
+const BASIC: [(Color, [u8; 3]); 16] = [
+    (Color::Black, [0, 0, 0]),
+    (Color::DarkRed, [205, 0, 0]),
+    (Color::DarkGreen, [0, 205, 0]),
+    (Color::DarkYellow, [205, 205, 0]),
+    (Color::DarkBlue, [0, 0, 238]),
+    (Color::DarkMagenta, [205, 0, 205]),
+    (Color::DarkCyan, [0, 205, 205]),
+    (Color::Grey, [229, 229, 229]),
+    (Color::DarkGrey, [127, 127, 127]),
+    (Color::Red, [255, 0, 0]),
+    (Color::Green, [0, 255, 0]),
+    (Color::Yellow, [255, 255, 0]),
+    (Color::Blue, [92, 92, 255]),
+    (Color::Magenta, [255, 0, 255]),
+    (Color::Cyan, [0, 255, 255]),
+    (Color::White, [255, 255, 255]),
+];
+
+fn distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+    a.iter()
+        .zip(&b)
+        .map(|(x, y)| (i32::from(*x) - i32::from(*y)).pow(2) as u32)
+        .sum()
+}
+
+/// The closer of the nearest color cube entry and the nearest grey ramp
+/// entry (indices 232 to 255).
+pub fn nearest_256(rgb: [u8; 3]) -> u8 {
+    let level = |channel: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|i| (i32::from(CUBE_LEVELS[*i]) - i32::from(channel)).abs())
+            .unwrap_or(0)
+    };
+    let [r, g, b] = rgb.map(level);
+    let cube = (16 + 36 * r + 6 * g + b) as u8;
+    let mean = rgb.iter().map(|c| u32::from(*c)).sum::<u32>() / 3;
+    let grey = 232 + (mean.saturating_sub(3) / 10).min(23) as u8;
+    if distance(ansi_rgb(grey), rgb) < distance(ansi_rgb(cube), rgb) {
+        grey
+    } else {
+        cube
+    }
+}
+
+fn nearest_16(rgb: [u8; 3]) -> Color {
+    BASIC
+        .iter()
+        .min_by_key(|(_, basic)| distance(*basic, rgb))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::White)
+}
+
+/// RGB value of a 256-color palette entry.
+pub fn ansi_rgb(value: u8) -> [u8; 3] {
+    match value {
+        0..=15 => BASIC[value as usize].1,
+        16..=231 => {
+            let index = value - 16;
+            [index / 36, index / 6 % 6, index % 6].map(|i| CUBE_LEVELS[i as usize])
+        }
+        _ => [8 + 10 * (value - 232); 3],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_depth_from_environment() {
+        assert_eq!(
+            ColorDepth::from_env(Some("truecolor"), Some("xterm")),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            ColorDepth::from_env(None, Some("xterm-256color")),
+            ColorDepth::Ansi256
+        );
+        assert_eq!(
+            ColorDepth::from_env(None, Some("linux")),
+            ColorDepth::Ansi16
+        );
+        assert_eq!(ColorDepth::from_env(None, None), ColorDepth::Ansi16);
+    }
+
+    #[test]
+    fn rgb_falls_back_to_nearest_palette_entry() {
+        let orange = Color::Rgb {
+            r: 255,
+            g: 140,
+            b: 0,
+        };
+        assert_eq!(fit_to(orange, ColorDepth::TrueColor), orange);
+        assert_eq!(fit_to(orange, ColorDepth::Ansi256), Color::AnsiValue(208));
+        assert_eq!(fit_to(orange, ColorDepth::Ansi16), Color::DarkYellow);
+        let grey = Color::Rgb {
+            r: 100,
+            g: 100,
+            b: 100,
+        };
+        assert_eq!(fit_to(grey, ColorDepth::Ansi256), Color::AnsiValue(241));
+        assert_eq!(
+            fit_to(Color::AnsiValue(196), ColorDepth::Ansi16),
+            Color::Red
+        );
+        assert_eq!(fit_to(Color::Yellow, ColorDepth::Ansi16), Color::Yellow);
+    }
+}
@@ -1,23 +1,489 @@
 use std::fmt::Write;
 
-use crossterm::style::{Color, ResetColor, SetForegroundColor};
+use crossterm::style::{Color, ResetColor, SetBackgroundColor, SetForegroundColor};
 
 use crate::config;
-use crate::cube::Cube;
-use crate::geometry::{self, Camera, ProjectedFace, Vec2, Viewport};
+use crate::cube::{Cube, FACELET_COUNT, FaceColor, facelet_descriptors};
+use crate::geometry::{self, Camera, GroundHit, ProjectedFace, Vec2, Viewport};
+use crate::palette;
+use crate::theme;
+
+/// Maps a facelet index to a glyph drawn over the middle of that sticker.
+pub type FaceletLabel = fn(usize) -> Option<char>;
+
+const LABEL_DEPTH_BIAS: f32 = 0.01;
+/// Borders sit just in front of their sticker but behind its label.
+const BORDER_DEPTH_BIAS: f32 = 0.005;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RendererKind {
+    Raster,
+    Raycast,
+}
+
+impl std::str::FromStr for RendererKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "raster" => Ok(RendererKind::Raster),
+            "raycast" => Ok(RendererKind::Raycast),
+            other => Err(format!(
+                "unknown renderer '{other}' (expected raster or raycast)"
+            )),
+        }
+    }
+}
+
+/// Shading parameters shared by every render backend.
+#[derive(Clone, Copy, Debug)]
+pub struct RenderSettings {
+    /// Exponent applied to brightness before it is mapped to a shade; values
+    /// above 1 lift midtones.
+    pub gamma: f32,
+    /// Draw without color, telling faces apart by fill pattern instead.
+    pub monochrome: bool,
+    pub shading: ShadingStyle,
+    /// Fixed fill character per face color (indexed by `FaceColor::index`),
+    /// used instead of the brightness ramp or monochrome pattern.
+    pub glyphs: [Option<char>; 6],
+    /// Draw the dark cube body behind the stickers (raster renderer only).
+    pub body: bool,
+    /// Paint stickers as colored cell backgrounds behind blank cells rather
+    /// than as colored characters.
+    pub solid: bool,
+    /// Trace the edges of every sticker so neighbours of the same color
+    /// stay apart.
+    pub borders: bool,
+    /// Draw only the edges of the stickers, hiding those behind the cube
+    /// (raster renderer only).
+    pub wireframe: bool,
+    /// Draw a floor grid under the cube with its shadow on it (raster
+    /// renderer only).
+    pub ground: bool,
+    /// Pick between the two nearest shades by cell position, so levels
+    /// between them come out as a mix instead of a hard band.
+    pub dither: bool,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            gamma: config::SHADE_GAMMA,
+            monochrome: false,
+            shading: ShadingStyle::Smooth,
+            glyphs: config::FACE_GLYPHS,
+            body: config::CUBE_BODY,
+            solid: false,
+            borders: config::STICKER_BORDERS,
+            wireframe: false,
+            ground: config::GROUND_PLANE,
+            dither: config::DITHER_SHADES,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShadingStyle {
+    Smooth,
+    /// Cel shading: a few flat brightness bands and dark sticker outlines.
+    Toon,
+    /// Solid blocks with brightness scaled into the sticker's RGB color.
+    /// Falls back to the smooth ramp on 16-color terminals.
+    Tinted,
+}
+
+impl ShadingStyle {
+    pub fn next(self) -> Self {
+        match self {
+            ShadingStyle::Smooth => ShadingStyle::Toon,
+            ShadingStyle::Toon => ShadingStyle::Tinted,
+            ShadingStyle::Tinted => ShadingStyle::Smooth,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct StickerPaint {
+    facelet: usize,
+    color: FaceColor,
+    brightness: f32,
+}
+
+/// The cell drawn for a sticker at screen cell (`x`, `y`).
+fn sticker_cell(paint: StickerPaint, x: usize, y: usize, settings: &RenderSettings) -> Cell {
+    let glyph = settings.glyphs[paint.color.index()];
+    let tinted = settings.shading == ShadingStyle::Tinted
+        && palette::active() != palette::ColorDepth::Ansi16;
+    if settings.monochrome {
+        Cell::new(
+            glyph.unwrap_or_else(|| pattern_char(paint.color, x, y)),
+            None,
+        )
+    } else if settings.solid {
+        let color = if tinted {
+            tint(paint, settings)
+        } else {
+            config::face_color_to_ansi(paint.color)
+        };
+        Cell {
+            ch: ' ',
+            color: None,
+            bg: Some(color),
+        }
+    } else if tinted && glyph.is_none() {
+        Cell::new(TINTED_GLYPH, Some(tint(paint, settings)))
+    } else {
+        Cell::new(
+            glyph.unwrap_or_else(|| shade_to_char(paint.brightness, x, y, settings)),
+            Some(config::face_color_to_ansi(paint.color)),
+        )
+    }
+}
+
+const TINTED_GLYPH: char = '█';
+
+/// The sticker's RGB color dimmed by its gamma-corrected brightness.
+fn tint(paint: StickerPaint, settings: &RenderSettings) -> Color {
+    let level = gamma_correct(paint.brightness, settings.gamma);
+    let [r, g, b] = config::face_color_to_rgb(paint.color)
+        .map(|channel| (f32::from(channel) * level).round() as u8);
+    Color::Rgb { r, g, b }
+}
+
+fn ink(color: FaceColor, settings: &RenderSettings) -> Option<Color> {
+    (!settings.monochrome).then(|| config::face_color_to_ansi(color))
+}
+
+/// Ink for a glyph drawn on a sticker of `color`: the sticker color itself,
+/// or on solid stickers black or white, whichever stands out.
+fn label_ink(color: FaceColor, settings: &RenderSettings) -> Option<Color> {
+    if !settings.solid || settings.monochrome {
+        return ink(color, settings);
+    }
+    let [r, g, b] = config::face_color_to_rgb(color);
+    let luma = 0.299 * f32::from(r) + 0.587 * f32::from(g) + 0.114 * f32::from(b);
+    Some(if luma > 140.0 {
+        Color::Black
+    } else {
+        Color::White
+    })
+}
+
+/// Position-dependent fill used in monochrome mode so each face color keeps
+/// a distinct texture: solid, dots, two hatches, checker and stripes.
+fn pattern_char(color: FaceColor, x: usize, y: usize) -> char {
+    let checker = (x + y).is_multiple_of(2);
+    match color {
+        FaceColor::White => '#',
+        FaceColor::Yellow => {
+            if checker {
+                '.'
+            } else {
+                ' '
+            }
+        }
+        FaceColor::Red => '/',
+        FaceColor::Orange => '\\',
+        FaceColor::Blue => {
+            if checker {
+                'X'
+            } else {
+                ' '
+            }
+        }
+        FaceColor::Green => {
+            if y.is_multiple_of(2) {
+                '='
+            } else {
+                '-'
+            }
+        }
+    }
+}
+
+/// How character cells are used: one sample per cell, or several colored
+/// sub-pixels folded into block glyphs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellMode {
+    Ascii,
+    /// 2x2 sub-pixels per cell drawn with quadrant blocks (`▘▝▖▗▚`...).
+    Quadrant,
+    /// Two stacked sub-pixels per cell drawn as `▀`, the top one in the
+    /// foreground color and the bottom one in the background color.
+    HalfBlock,
+    /// 2x4 sub-pixels per cell drawn as braille dots (`⠁⠂⠄⡀`...), lit
+    /// wherever anything was drawn.
+    Braille,
+}
+
+impl std::str::FromStr for CellMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ascii" => Ok(CellMode::Ascii),
+            "quadrant" => Ok(CellMode::Quadrant),
+            "half" => Ok(CellMode::HalfBlock),
+            "braille" => Ok(CellMode::Braille),
+            other => Err(format!(
+                "unknown cell mode '{other}' (expected ascii, quadrant, half or braille)"
+            )),
+        }
+    }
+}
+
+/// A strategy for turning a cube of any size, given as its facelet colors
+/// in `Cube` / `BigCube` order, into a character frame.
+pub trait RenderBackend {
+    fn render(&mut self, colors: &[FaceColor], camera: &Camera, viewport: Viewport) -> Frame;
+
+    fn settings_mut(&mut self) -> &mut RenderSettings;
+
+    fn set_labels(&mut self, _labels: Option<FaceletLabel>) {}
+
+    fn set_ghost(&mut self, _target: Option<Cube>) {}
+
+    fn set_highlight(&mut self, _facelets: Vec<usize>) {}
+}
+
+/// Viewport used by `render_snapshot`, small enough to keep golden files
+/// readable in a diff.
+pub const SNAPSHOT_VIEWPORT: Viewport = Viewport {
+    width: 48,
+    height: 20,
+};
+
+/// Renders `cube` from the default camera into `SNAPSHOT_VIEWPORT` with
+/// default settings and returns the plain text, then a blank line and the
+/// same grid with each cell's color as a letter (W, Y, R, O, B, G for the
+/// stickers, `#` for the body, `?` for anything else), so turns show up
+/// even where the glyphs stay the same. Nothing here depends on the
+/// terminal or the clock, so the output is stable across runs.
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn render_snapshot(cube: &Cube, kind: RendererKind) -> String {
+    let mut renderer = backend(kind, RenderSettings::default());
+    let frame = renderer.render(cube.face_colors(), &Camera::new(), SNAPSHOT_VIEWPORT);
+    let mut snapshot = frame.as_plain_string();
+    snapshot.push('\n');
+    for row in frame.cells.chunks(frame.width.max(1)) {
+        snapshot.extend(row.iter().map(|cell| color_letter(cell.color)));
+        snapshot.push('\n');
+    }
+    snapshot
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+fn color_letter(color: Option<Color>) -> char {
+    const COLORS: [FaceColor; 6] = [
+        FaceColor::White,
+        FaceColor::Yellow,
+        FaceColor::Red,
+        FaceColor::Orange,
+        FaceColor::Blue,
+        FaceColor::Green,
+    ];
+    let Some(color) = color else {
+        return ' ';
+    };
+    if color == theme::active().body {
+        return '#';
+    }
+    COLORS
+        .iter()
+        .find(|face| config::face_color_to_ansi(**face) == color)
+        .and_then(|face| face.name().chars().next())
+        .map_or('?', |letter| letter.to_ascii_uppercase())
+}
+
+pub fn backend(kind: RendererKind, settings: RenderSettings) -> Box<dyn RenderBackend> {
+    match kind {
+        RendererKind::Raster => Box::new(Renderer::new(settings)),
+        RendererKind::Raycast => Box::new(RaycastRenderer::new(settings)),
+    }
+}
+
+/// Wraps `inner` so it renders at sub-pixel resolution when `mode` asks
+/// for it.
+pub fn with_cell_mode(inner: Box<dyn RenderBackend>, mode: CellMode) -> Box<dyn RenderBackend> {
+    match mode {
+        CellMode::Ascii => inner,
+        CellMode::Quadrant => Box::new(QuadrantRenderer { inner }),
+        CellMode::HalfBlock => Box::new(HalfBlockRenderer { inner }),
+        CellMode::Braille => Box::new(BrailleRenderer { inner }),
+    }
+}
+
+/// Where the flat net view puts each sticker: stickers are `sticker_width`
+/// by `sticker_height` cells, with a blank column between faces, and the
+/// whole net is centered in the viewport.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NetLayout {
+    left: usize,
+    top: usize,
+    sticker_width: usize,
+    sticker_height: usize,
+}
+
+/// The net is four faces across and three down.
+const NET_STICKERS_ACROSS: usize = 12;
+const NET_STICKERS_DOWN: usize = 9;
+const NET_FACE_GAP: usize = 1;
+
+impl NetLayout {
+    /// The largest layout that fits `viewport`, with stickers twice as wide
+    /// as they are tall so they look square in a terminal.
+    pub fn fit(viewport: Viewport) -> Self {
+        let (width, height) = (viewport.width as usize, viewport.height as usize);
+        let sticker_height = (height / NET_STICKERS_DOWN)
+            .min(width.saturating_sub(3 * NET_FACE_GAP) / (2 * NET_STICKERS_ACROSS))
+            .max(1);
+        let sticker_width = 2 * sticker_height;
+        let net_width = NET_STICKERS_ACROSS * sticker_width + 3 * NET_FACE_GAP;
+        let net_height = NET_STICKERS_DOWN * sticker_height + 2 * NET_FACE_GAP;
+        Self {
+            left: width.saturating_sub(net_width) / 2,
+            top: height.saturating_sub(net_height) / 2,
+            sticker_width,
+            sticker_height,
+        }
+    }
+
+    /// Top-left cell of the sticker at facelet `index`.
+    fn origin(&self, index: usize) -> (usize, usize) {
+        let desc = facelet_descriptors()[index];
+        let (face_col, face_row) = desc.face.net_slot();
+        let x = self.left
+            + face_col * (3 * self.sticker_width + NET_FACE_GAP)
+            + desc.col as usize * self.sticker_width;
+        let y = self.top
+            + face_row * (3 * self.sticker_height + NET_FACE_GAP)
+            + desc.row as usize * self.sticker_height;
+        (x, y)
+    }
+
+    /// The facelet drawn in cell (`x`, `y`), if any.
+    pub fn facelet_at(&self, x: usize, y: usize) -> Option<usize> {
+        (0..FACELET_COUNT).find(|index| {
+            let (left, top) = self.origin(*index);
+            (left..left + self.sticker_width).contains(&x)
+                && (top..top + self.sticker_height).contains(&y)
+        })
+    }
+}
+
+/// Draws a 3x3 as a flat net, each sticker a block of cells in its face
+/// color at full brightness. Wide stickers keep their last column blank so
+/// neighbours stay apart. Labels and highlights work as in `Renderer`.
+pub fn render_net(
+    colors: &[FaceColor],
+    settings: &RenderSettings,
+    labels: Option<FaceletLabel>,
+    highlight: &[usize],
+    viewport: Viewport,
+) -> Frame {
+    if viewport.width == 0 || viewport.height == 0 || colors.len() != FACELET_COUNT {
+        return Frame::blank(viewport);
+    }
+    let layout = NetLayout::fit(viewport);
+    let mut canvas = AsciiCanvas::new(viewport.width as usize, viewport.height as usize);
+    let fill_width = if layout.sticker_width > 2 {
+        layout.sticker_width - 1
+    } else {
+        layout.sticker_width
+    };
+    for (index, color) in colors.iter().enumerate() {
+        let (left, top) = layout.origin(index);
+        let paint = StickerPaint {
+            facelet: index,
+            color: *color,
+            brightness: 1.0,
+        };
+        for y in top..top + layout.sticker_height {
+            for x in left..left + fill_width {
+                let cell = sticker_cell(paint, x, y, settings);
+                canvas.plot_facelet(x, y, 1.0, cell, index);
+            }
+        }
+        if let Some(ch) = labels.and_then(|labels| labels(index)) {
+            let x = left + (fill_width - 1) / 2;
+            let y = top + (layout.sticker_height - 1) / 2;
+            canvas.overlay(x, y, 0.0, ch, label_ink(*color, settings));
+        }
+    }
+    if !highlight.is_empty() {
+        canvas.highlight(highlight, theme::active().highlight);
+    }
+    canvas.to_frame()
+}
+
+/// A net small enough to overlay a corner of the 3D view: stickers are
+/// square half-block pixels, or whole cells with `--mono`, where the two
+/// halves of a cell could not be told apart.
+pub fn render_mini_net(colors: &[FaceColor], settings: &RenderSettings) -> Frame {
+    if colors.len() != FACELET_COUNT {
+        return Frame::empty();
+    }
+    let layout = NetLayout {
+        left: 0,
+        top: 0,
+        sticker_width: 1,
+        sticker_height: 1,
+    };
+    let width = NET_STICKERS_ACROSS + 3 * NET_FACE_GAP;
+    let height = NET_STICKERS_DOWN + 2 * NET_FACE_GAP;
+    if settings.monochrome {
+        let mut canvas = AsciiCanvas::new(width, height);
+        for (index, color) in colors.iter().enumerate() {
+            let (x, y) = layout.origin(index);
+            let paint = StickerPaint {
+                facelet: index,
+                color: *color,
+                brightness: 1.0,
+            };
+            canvas.plot_facelet(x, y, 1.0, sticker_cell(paint, x, y, settings), index);
+        }
+        return canvas.to_frame();
+    }
+    let mut pixels = vec![None; width * height];
+    for (index, color) in colors.iter().enumerate() {
+        let (x, y) = layout.origin(index);
+        pixels[y * width + x] = Some(Some(theme::active().face(*color)));
+    }
+    let mut frame = Frame::blank(Viewport {
+        width: width as u16,
+        height: height.div_ceil(2) as u16,
+    });
+    for y in 0..frame.height {
+        for x in 0..width {
+            let top = pixels[2 * y * width + x];
+            let bottom = pixels.get((2 * y + 1) * width + x).copied().flatten();
+            frame.cells[y * width + x] = fold_half_block(top, bottom);
+        }
+    }
+    frame
+}
 
 pub struct Renderer {
     canvas: AsciiCanvas,
+    settings: RenderSettings,
+    labels: Option<FaceletLabel>,
+    ghost: Option<Cube>,
+    highlight: Vec<usize>,
 }
 
 impl Renderer {
-    pub fn new() -> Self {
+    pub fn new(settings: RenderSettings) -> Self {
         Self {
             canvas: AsciiCanvas::new(0, 0),
+            settings,
+            labels: None,
+            ghost: None,
+            highlight: Vec::new(),
         }
     }
 
-    pub fn render(&mut self, cube: &Cube, camera: &Camera, viewport: Viewport) -> Frame {
+    pub fn render(&mut self, colors: &[FaceColor], camera: &Camera, viewport: Viewport) -> Frame {
         if viewport.width == 0 || viewport.height == 0 {
             return Frame::empty();
         }
@@ -25,44 +491,181 @@ impl Renderer {
         self.canvas.ensure_size(viewport);
         self.canvas.clear();
 
-        let faces = geometry::project_cube(cube, camera, viewport);
-        for face in faces {
-            self.draw_face(&face);
+        let faces = geometry::project_cube(colors, camera, viewport);
+        // Labels, the ghost and highlights are indexed by 3x3 facelet.
+        let is_3x3 = colors.len() == FACELET_COUNT;
+        if self.settings.body {
+            for face in &geometry::project_body(colors, camera, viewport) {
+                self.draw_body(face);
+            }
+        }
+        for face in &faces {
+            self.draw_face(face);
+        }
+        if self.settings.wireframe {
+            // The fills stay behind in the depth buffer to hide the edges
+            // they cover.
+            self.canvas.clear_cells();
+            for face in &faces {
+                self.draw_border(face, ink(face.color, &self.settings));
+            }
+        } else if self.settings.borders {
+            let outline = Some(theme::active().outline);
+            for face in &faces {
+                self.draw_border(face, outline);
+            }
+        }
+        if self.settings.ground {
+            self.draw_ground(camera, viewport);
+        }
+        if self.settings.shading == ShadingStyle::Toon {
+            self.canvas
+                .outline(config::TOON_OUTLINE_GLYPH, Some(theme::active().outline));
+        }
+        if let Some(labels) = self.labels.filter(|_| is_3x3) {
+            for face in &faces {
+                if let Some(ch) = labels(face.facelet) {
+                    let color = label_ink(face.color, &self.settings);
+                    self.draw_center_glyph(face, ch, color);
+                }
+            }
+        }
+        if let Some(target) = self.ghost.filter(|_| is_3x3) {
+            let target_colors = target.face_colors();
+            for face in &faces {
+                let wanted = target_colors[face.facelet];
+                if wanted != face.color {
+                    let color = ink(wanted, &self.settings);
+                    self.draw_center_glyph(face, config::GHOST_GLYPH, color);
+                }
+            }
+        }
+        if is_3x3 && !self.highlight.is_empty() {
+            self.canvas
+                .highlight(&self.highlight, theme::active().highlight);
         }
 
         self.canvas.to_frame()
     }
 
     fn draw_face(&mut self, face: &ProjectedFace) {
-        let ch = shade_to_char(face.brightness);
-        let color = config::face_color_to_ansi(face.color);
-        self.fill_triangle(
-            face.points[0],
-            face.points[1],
-            face.points[2],
-            face.depth,
-            ch,
-            Some(color),
-        );
-        self.fill_triangle(
-            face.points[0],
-            face.points[2],
-            face.points[3],
-            face.depth,
+        let paint = StickerPaint {
+            facelet: face.facelet,
+            color: face.color,
+            brightness: face.brightness,
+        };
+        let settings = self.settings;
+        self.fill_quad(face, paint.facelet, |x, y| {
+            sticker_cell(paint, x, y, &settings)
+        });
+    }
+
+    /// Fills the cube body behind a sticker in a dark solid color.
+    fn draw_body(&mut self, face: &ProjectedFace) {
+        let theme = theme::active();
+        let body = theme.body;
+        let cell = if self.settings.monochrome {
+            // The faintest shade that still shows up.
+            Cell::new(theme.shades[1], None)
+        } else if self.settings.solid {
+            Cell {
+                ch: ' ',
+                color: None,
+                bg: Some(body),
+            }
+        } else {
+            Cell::new(config::BODY_GLYPH, Some(body))
+        };
+        self.fill_quad(face, BODY_OWNER, |_, _| cell);
+    }
+
+    /// Draws the floor wherever the cube doesn't cover it: grid lines where
+    /// a cell's grid square differs from the next cell's, and shadow glyphs
+    /// as dark as the share of light the cube blocks.
+    fn draw_ground(&mut self, camera: &Camera, viewport: Viewport) {
+        let basis = camera.basis();
+        let (width, height) = (self.canvas.width, self.canvas.height);
+        let hits: Vec<Option<GroundHit>> = (0..width * height)
+            .map(|idx| {
+                let (x, y) = ((idx % width) as f32 + 0.5, (idx / width) as f32 + 0.5);
+                geometry::cast_ground_ray(&basis, viewport, x, y)
+            })
+            .collect();
+        let ground = (!self.settings.monochrome).then_some(config::GROUND_COLOR);
+        for y in 0..height {
+            for x in 0..width {
+                let Some(hit) = hits[y * width + x] else {
+                    continue;
+                };
+                let crosses = |next: Option<&Option<GroundHit>>| {
+                    let next = next.copied().flatten();
+                    next.map_or((false, false), |next| {
+                        (next.tile.0 != hit.tile.0, next.tile.1 != hit.tile.1)
+                    })
+                };
+                let right = crosses((x + 1 < width).then(|| &hits[y * width + x + 1]));
+                let below = crosses(hits.get((y + 1) * width + x));
+                let glyphs = config::SHADOW_GLYPHS;
+                let ch = if hit.shadow > 0.0 {
+                    let level = (hit.shadow * glyphs.len() as f32).ceil() as usize;
+                    glyphs[level.clamp(1, glyphs.len()) - 1]
+                } else if right.0 || right.1 || below.0 || below.1 {
+                    config::GROUND_GLYPH
+                } else {
+                    continue;
+                };
+                self.canvas
+                    .plot_facelet(x, y, hit.depth, Cell::new(ch, ground), GROUND_OWNER);
+            }
+        }
+    }
+
+    fn fill_quad(
+        &mut self,
+        face: &ProjectedFace,
+        owner: usize,
+        fill: impl Fn(usize, usize) -> Cell,
+    ) {
+        let corner = |i: usize| (face.points[i], face.point_depths[i]);
+        self.fill_triangle([corner(0), corner(1), corner(2)], owner, &fill);
+        self.fill_triangle([corner(0), corner(2), corner(3)], owner, &fill);
+    }
+
+    fn draw_border(&mut self, face: &ProjectedFace, color: Option<Color>) {
+        for i in 0..4 {
+            let j = (i + 1) % 4;
+            self.canvas.line(
+                (face.points[i], face.point_depths[i] - BORDER_DEPTH_BIAS),
+                (face.points[j], face.point_depths[j] - BORDER_DEPTH_BIAS),
+                color,
+            );
+        }
+    }
+
+    fn draw_center_glyph(&mut self, face: &ProjectedFace, ch: char, color: Option<Color>) {
+        if face.center.x < 0.0 || face.center.y < 0.0 {
+            return;
+        }
+        self.canvas.overlay(
+            face.center.x.round() as usize,
+            face.center.y.round() as usize,
+            face.depth - LABEL_DEPTH_BIAS,
             ch,
-            Some(color),
+            color,
         );
     }
 
+    /// Fills the triangle with corners at screen points paired with their
+    /// view depths. Depth is interpolated per cell, perspective-correctly
+    /// (1/z is what varies linearly on screen), so stickers that cross at
+    /// glancing angles occlude each other where they actually cross.
     fn fill_triangle(
         &mut self,
-        a: Vec2,
-        b: Vec2,
-        c: Vec2,
-        depth: f32,
-        ch: char,
-        color: Option<Color>,
+        corners: [(Vec2, f32); 3],
+        owner: usize,
+        fill: impl Fn(usize, usize) -> Cell,
     ) {
+        let [(a, _), (b, _), (c, _)] = corners;
         let min_x = a.x.min(b.x).min(c.x).floor().max(0.0) as i32;
         let max_x = a.x.max(b.x).max(c.x).ceil() as i32;
         let min_y = a.y.min(b.y).min(c.y).floor().max(0.0) as i32;
@@ -71,50 +674,508 @@ impl Renderer {
         for y in min_y..=max_y {
             for x in min_x..=max_x {
                 let p = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
-                if inside_triangle(p, a, b, c) {
-                    self.canvas.plot(x as usize, y as usize, depth, ch, color);
+                let Some(weights) = barycentric(p, a, b, c) else {
+                    continue;
+                };
+                let inverse_depth: f32 = weights
+                    .iter()
+                    .zip(&corners)
+                    .map(|(weight, (_, depth))| weight / depth)
+                    .sum();
+                let (x, y) = (x as usize, y as usize);
+                self.canvas
+                    .plot_facelet(x, y, 1.0 / inverse_depth, fill(x, y), owner);
+            }
+        }
+    }
+}
+
+impl RenderBackend for Renderer {
+    fn render(&mut self, colors: &[FaceColor], camera: &Camera, viewport: Viewport) -> Frame {
+        Renderer::render(self, colors, camera, viewport)
+    }
+
+    fn settings_mut(&mut self) -> &mut RenderSettings {
+        &mut self.settings
+    }
+
+    fn set_labels(&mut self, labels: Option<FaceletLabel>) {
+        self.labels = labels;
+    }
+
+    /// Marks every sticker that differs from `target` with a glyph in the
+    /// target's color.
+    fn set_ghost(&mut self, target: Option<Cube>) {
+        self.ghost = target;
+    }
+
+    /// Puts the highlight background behind the given facelets.
+    fn set_highlight(&mut self, facelets: Vec<usize>) {
+        self.highlight = facelets;
+    }
+}
+
+/// Experimental renderer that shoots one ray per character cell instead of
+/// rasterizing projected quads, so occlusion never depends on face sorting.
+pub struct RaycastRenderer {
+    canvas: AsciiCanvas,
+    settings: RenderSettings,
+    labels: Option<FaceletLabel>,
+}
+
+impl RaycastRenderer {
+    pub fn new(settings: RenderSettings) -> Self {
+        Self {
+            canvas: AsciiCanvas::new(0, 0),
+            settings,
+            labels: None,
+        }
+    }
+
+    /// Puts each label on the cell under its sticker's center, if the ray
+    /// through that cell hit the sticker rather than something in front.
+    fn draw_labels(
+        &mut self,
+        labels: FaceletLabel,
+        colors: &[FaceColor],
+        camera: &Camera,
+        viewport: Viewport,
+    ) {
+        for face in geometry::project_cube(colors, camera, viewport) {
+            let Some(ch) = labels(face.facelet) else {
+                continue;
+            };
+            let (x, y) = (face.center.x.round(), face.center.y.round());
+            if x < 0.0 || y < 0.0 || x as usize >= self.canvas.width {
+                continue;
+            }
+            let idx = y as usize * self.canvas.width + x as usize;
+            if self.canvas.owner.get(idx) == Some(&face.facelet) {
+                let cell = &mut self.canvas.cells[idx];
+                cell.ch = ch;
+                cell.color = label_ink(face.color, &self.settings);
+            }
+        }
+    }
+}
+
+impl RenderBackend for RaycastRenderer {
+    fn render(&mut self, colors: &[FaceColor], camera: &Camera, viewport: Viewport) -> Frame {
+        if viewport.width == 0 || viewport.height == 0 {
+            return Frame::empty();
+        }
+
+        self.canvas.ensure_size(viewport);
+        self.canvas.clear();
+
+        let basis = camera.basis();
+        for y in 0..self.canvas.height {
+            for x in 0..self.canvas.width {
+                let hit = geometry::cast_screen_ray(
+                    colors,
+                    &basis,
+                    viewport,
+                    x as f32 + 0.5,
+                    y as f32 + 0.5,
+                );
+                if let Some(hit) = hit {
+                    let paint = StickerPaint {
+                        facelet: hit.facelet,
+                        color: hit.color,
+                        brightness: hit.brightness,
+                    };
+                    let cell = sticker_cell(paint, x, y, &self.settings);
+                    self.canvas.plot_facelet(x, y, hit.depth, cell, hit.facelet);
+                }
+            }
+        }
+        if self.settings.shading == ShadingStyle::Toon {
+            self.canvas
+                .outline(config::TOON_OUTLINE_GLYPH, Some(theme::active().outline));
+        }
+        // Labels are indexed by 3x3 facelet.
+        if let Some(labels) = self.labels.filter(|_| colors.len() == FACELET_COUNT) {
+            self.draw_labels(labels, colors, camera, viewport);
+        }
+
+        self.canvas.to_frame()
+    }
+
+    fn settings_mut(&mut self) -> &mut RenderSettings {
+        &mut self.settings
+    }
+
+    fn set_labels(&mut self, labels: Option<FaceletLabel>) {
+        self.labels = labels;
+    }
+}
+
+/// Renders the inner backend at twice the resolution on both axes and folds
+/// each 2x2 block into one quadrant glyph. A cell can only hold two colors,
+/// so the two most common sub-pixel colors win and the rest fall back to
+/// the background one.
+pub struct QuadrantRenderer {
+    inner: Box<dyn RenderBackend>,
+}
+
+const QUADRANT_GLYPHS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+
+impl RenderBackend for QuadrantRenderer {
+    fn render(&mut self, colors: &[FaceColor], camera: &Camera, viewport: Viewport) -> Frame {
+        let fine_viewport = Viewport {
+            width: viewport.width.saturating_mul(2),
+            height: viewport.height.saturating_mul(2),
+        };
+        let fine = self.inner.render(colors, camera, fine_viewport);
+        let mut frame = Frame::blank(viewport);
+        if fine.width < frame.width * 2 || fine.height < frame.height * 2 {
+            return frame;
+        }
+        for y in 0..frame.height {
+            for x in 0..frame.width {
+                let samples = [
+                    fine.cells[(2 * y) * fine.width + 2 * x],
+                    fine.cells[(2 * y) * fine.width + 2 * x + 1],
+                    fine.cells[(2 * y + 1) * fine.width + 2 * x],
+                    fine.cells[(2 * y + 1) * fine.width + 2 * x + 1],
+                ]
+                .map(subpixel_ink);
+                frame.cells[y * frame.width + x] = fold_quadrant(samples);
+            }
+        }
+        frame
+    }
+
+    fn settings_mut(&mut self) -> &mut RenderSettings {
+        self.inner.settings_mut()
+    }
+
+    fn set_labels(&mut self, labels: Option<FaceletLabel>) {
+        self.inner.set_labels(labels);
+    }
+
+    fn set_ghost(&mut self, target: Option<Cube>) {
+        self.inner.set_ghost(target);
+    }
+
+    fn set_highlight(&mut self, facelets: Vec<usize>) {
+        self.inner.set_highlight(facelets);
+    }
+}
+
+/// Renders the inner backend at twice the vertical resolution and stacks
+/// each pair of rows into one cell. Terminal cells are about twice as tall
+/// as they are wide, so the sub-pixels come out roughly square.
+pub struct HalfBlockRenderer {
+    inner: Box<dyn RenderBackend>,
+}
+
+impl RenderBackend for HalfBlockRenderer {
+    fn render(&mut self, colors: &[FaceColor], camera: &Camera, viewport: Viewport) -> Frame {
+        let fine_viewport = Viewport {
+            width: viewport.width,
+            height: viewport.height.saturating_mul(2),
+        };
+        let fine = self.inner.render(colors, camera, fine_viewport);
+        let mut frame = Frame::blank(viewport);
+        if fine.width < frame.width || fine.height < frame.height * 2 {
+            return frame;
+        }
+        for y in 0..frame.height {
+            for x in 0..frame.width {
+                let top = subpixel_ink(fine.cells[(2 * y) * fine.width + x]);
+                let bottom = subpixel_ink(fine.cells[(2 * y + 1) * fine.width + x]);
+                frame.cells[y * frame.width + x] = fold_half_block(top, bottom);
+            }
+        }
+        frame
+    }
+
+    fn settings_mut(&mut self) -> &mut RenderSettings {
+        self.inner.settings_mut()
+    }
+
+    fn set_labels(&mut self, labels: Option<FaceletLabel>) {
+        self.inner.set_labels(labels);
+    }
+
+    fn set_ghost(&mut self, target: Option<Cube>) {
+        self.inner.set_ghost(target);
+    }
+
+    fn set_highlight(&mut self, facelets: Vec<usize>) {
+        self.inner.set_highlight(facelets);
+    }
+}
+
+fn fold_half_block(top: Option<Option<Color>>, bottom: Option<Option<Color>>) -> Cell {
+    match (top, bottom) {
+        (None, None) => Cell::default(),
+        (Some(top), None) => Cell::new('▀', top),
+        (None, Some(bottom)) => Cell::new('▄', bottom),
+        (Some(top), Some(bottom)) if top == bottom => Cell::new('█', top),
+        (Some(top), Some(bottom)) => Cell {
+            ch: '▀',
+            color: top,
+            bg: bottom,
+        },
+    }
+}
+
+/// Renders the inner backend at 2x4 sub-pixels per cell and collects them
+/// on a `BrailleCanvas`. Dots only say whether something was drawn, so the
+/// picture is essentially monochrome: each cell takes the color of most of
+/// its lit dots, and with `--mono` there is no color at all.
+pub struct BrailleRenderer {
+    inner: Box<dyn RenderBackend>,
+}
+
+impl RenderBackend for BrailleRenderer {
+    fn render(&mut self, colors: &[FaceColor], camera: &Camera, viewport: Viewport) -> Frame {
+        let fine_viewport = Viewport {
+            width: viewport.width.saturating_mul(BRAILLE_COLS as u16),
+            height: viewport.height.saturating_mul(BRAILLE_ROWS as u16),
+        };
+        let fine = self.inner.render(colors, camera, fine_viewport);
+        let mut canvas = BrailleCanvas::new(viewport.width as usize, viewport.height as usize);
+        for y in 0..fine.height {
+            for x in 0..fine.width {
+                if let Some(ink) = subpixel_ink(fine.cells[y * fine.width + x]) {
+                    canvas.plot(x, y, ink);
                 }
             }
         }
+        canvas.to_frame()
+    }
+
+    fn settings_mut(&mut self) -> &mut RenderSettings {
+        self.inner.settings_mut()
+    }
+
+    fn set_labels(&mut self, labels: Option<FaceletLabel>) {
+        self.inner.set_labels(labels);
+    }
+
+    fn set_ghost(&mut self, target: Option<Cube>) {
+        self.inner.set_ghost(target);
+    }
+
+    fn set_highlight(&mut self, facelets: Vec<usize>) {
+        self.inner.set_highlight(facelets);
+    }
+}
+
+const BRAILLE_COLS: usize = 2;
+const BRAILLE_ROWS: usize = 4;
+
+/// Bit of each braille dot, indexed `[row][col]`; the bottom row was added
+/// to the pattern block last, hence its high bits.
+const BRAILLE_DOTS: [[u8; BRAILLE_COLS]; BRAILLE_ROWS] =
+    [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// Accumulates sub-pixel coverage as braille dot masks, tallying the ink
+/// of each lit dot so the cell can take the most common one.
+struct BrailleCanvas {
+    width: usize,
+    height: usize,
+    dots: Vec<u8>,
+    inks: Vec<Vec<(Option<Color>, usize)>>,
+}
+
+impl BrailleCanvas {
+    fn new(width: usize, height: usize) -> Self {
+        let area = width * height;
+        Self {
+            width,
+            height,
+            dots: vec![0; area],
+            inks: vec![Vec::new(); area],
+        }
+    }
+
+    /// Lights the dot at sub-pixel `x`, `y`.
+    fn plot(&mut self, x: usize, y: usize, ink: Option<Color>) {
+        let (cx, cy) = (x / BRAILLE_COLS, y / BRAILLE_ROWS);
+        if cx >= self.width || cy >= self.height {
+            return;
+        }
+        let idx = cy * self.width + cx;
+        self.dots[idx] |= BRAILLE_DOTS[y % BRAILLE_ROWS][x % BRAILLE_COLS];
+        let inks = &mut self.inks[idx];
+        match inks.iter_mut().find(|(color, _)| *color == ink) {
+            Some((_, count)) => *count += 1,
+            None => inks.push((ink, 1)),
+        }
     }
+
+    fn to_frame(&self) -> Frame {
+        let cells = self
+            .dots
+            .iter()
+            .zip(&self.inks)
+            .map(|(dots, inks)| {
+                if *dots == 0 {
+                    return Cell::default();
+                }
+                let color = inks
+                    .iter()
+                    .max_by_key(|(_, count)| *count)
+                    .and_then(|(color, _)| *color);
+                let ch = char::from_u32(0x2800 + u32::from(*dots)).unwrap_or(' ');
+                Cell::new(ch, color)
+            })
+            .collect();
+        Frame {
+            width: self.width,
+            height: self.height,
+            cells,
+            images: Vec::new(),
+        }
+    }
+}
+
+/// `None` for an empty sub-pixel, otherwise the (possibly absent) ink color;
+/// a blank cell is inked by its background.
+fn subpixel_ink(cell: Cell) -> Option<Option<Color>> {
+    let ink = if cell.ch == ' ' { cell.bg } else { cell.color };
+    (cell != Cell::default()).then_some(ink)
 }
 
-fn shade_to_char(brightness: f32) -> char {
-    let ramp = config::ASCII_SHADES;
-    let idx = (brightness.clamp(0.0, 1.0) * (ramp.len() as f32 - 1.0)).round() as usize;
-    ramp[idx]
+fn fold_quadrant(samples: [Option<Option<Color>>; 4]) -> Cell {
+    let mut counts: Vec<(Option<Option<Color>>, usize)> = Vec::with_capacity(4);
+    for sample in samples {
+        match counts.iter_mut().find(|(ink, _)| *ink == sample) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((sample, 1)),
+        }
+    }
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    // Prefer drawing ink as the foreground so a lone empty sub-pixel becomes
+    // the background rather than the other way round.
+    let (fg, bg) = match (counts.first(), counts.get(1)) {
+        (Some((first, _)), Some((second, _))) if first.is_none() => (*second, *first),
+        (Some((first, _)), Some((second, _))) => (*first, *second),
+        (Some((first, _)), None) => (*first, None),
+        (None, _) => (None, None),
+    };
+    let Some(fg_color) = fg else {
+        return Cell::default();
+    };
+    let mask = samples
+        .iter()
+        .enumerate()
+        .filter(|(_, sample)| **sample == fg)
+        .fold(0, |mask, (bit, _)| mask | (1 << bit));
+    Cell {
+        ch: QUADRANT_GLYPHS[mask],
+        color: fg_color,
+        bg: bg.flatten(),
+    }
+}
+
+/// Glyph for a line running `dx` cells across and `dy` cells down. Cells
+/// are about twice as tall as they are wide, which the thresholds allow for.
+fn line_glyph(dx: f32, dy: f32) -> char {
+    if dy.abs() * 4.0 < dx.abs() {
+        '-'
+    } else if dx.abs() < dy.abs() {
+        '|'
+    } else if (dx < 0.0) == (dy < 0.0) {
+        '\\'
+    } else {
+        '/'
+    }
 }
 
-fn inside_triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
-    let ab = cross_z(a, b, p);
-    let bc = cross_z(b, c, p);
-    let ca = cross_z(c, a, p);
-    (ab >= 0.0 && bc >= 0.0 && ca >= 0.0) || (ab <= 0.0 && bc <= 0.0 && ca <= 0.0)
+fn shade_to_char(brightness: f32, x: usize, y: usize, settings: &RenderSettings) -> char {
+    let ramp = &theme::active().shades;
+    let mut level = gamma_correct(brightness, settings.gamma);
+    // Toon bands are meant to be flat, so they are never dithered.
+    let threshold = if settings.shading == ShadingStyle::Toon {
+        let bands = config::TOON_BANDS as f32;
+        level = (level * bands).ceil().max(1.0) / bands;
+        0.5
+    } else if settings.dither {
+        bayer_threshold(x, y)
+    } else {
+        0.5
+    };
+    let top = ramp.len() - 1;
+    let idx = (level * top as f32 + threshold) as usize;
+    ramp[idx.min(top)]
+}
+
+/// 4x4 ordered dither matrix; every value is used once per tile.
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Where in [0, 1) cell (`x`, `y`) rounds up to the next shade. Averaged
+/// over a tile it is one half, so flat areas keep their mean brightness.
+fn bayer_threshold(x: usize, y: usize) -> f32 {
+    (f32::from(BAYER[y % 4][x % 4]) + 0.5) / 16.0
+}
+
+pub fn gamma_correct(brightness: f32, gamma: f32) -> f32 {
+    brightness.clamp(0.0, 1.0).powf(1.0 / gamma.max(0.01))
+}
+
+/// Weights of `a`, `b` and `c` that sum to `p`, or `None` when `p` is
+/// outside the triangle or the triangle has no area.
+pub fn barycentric(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> Option<[f32; 3]> {
+    let area = cross_z(a, b, c);
+    if area == 0.0 {
+        return None;
+    }
+    let weights = [
+        cross_z(b, c, p) / area,
+        cross_z(c, a, p) / area,
+        cross_z(a, b, p) / area,
+    ];
+    weights.iter().all(|w| *w >= 0.0).then_some(weights)
 }
 
 fn cross_z(a: Vec2, b: Vec2, p: Vec2) -> f32 {
     (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Cell {
     pub ch: char,
     pub color: Option<Color>,
+    pub bg: Option<Color>,
 }
 
-impl Default for Cell {
-    fn default() -> Self {
+impl Cell {
+    pub fn new(ch: char, color: Option<Color>) -> Self {
         Self {
-            ch: ' ',
-            color: None,
+            ch,
+            color,
+            bg: None,
         }
     }
 }
 
+impl Default for Cell {
+    fn default() -> Self {
+        Self::new(' ', None)
+    }
+}
+
 pub struct Frame {
     width: usize,
     height: usize,
     cells: Vec<Cell>,
+    images: Vec<FrameImage>,
+}
+
+/// A bitmap drawn over the frame's cells with its top-left corner in cell
+/// (`x`, `y`), already encoded for the terminal.
+#[derive(Clone, Debug)]
+pub struct FrameImage {
+    pub x: usize,
+    pub y: usize,
+    pub data: String,
 }
 
 impl Frame {
@@ -123,22 +1184,118 @@ impl Frame {
             width: 0,
             height: 0,
             cells: Vec::new(),
+            images: Vec::new(),
+        }
+    }
+
+    pub fn blank(viewport: Viewport) -> Self {
+        let width = viewport.width as usize;
+        let height = viewport.height as usize;
+        Self {
+            width,
+            height,
+            cells: vec![Cell::default(); width * height],
+            images: Vec::new(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn cell(&self, x: usize, y: usize) -> Cell {
+        self.cells[y * self.width + x]
+    }
+
+    pub fn images(&self) -> &[FrameImage] {
+        &self.images
+    }
+
+    pub fn add_image(&mut self, image: FrameImage) {
+        self.images.push(image);
+    }
+
+    /// Copies `other` into this frame with its top-left corner at (`x`, `y`),
+    /// clipping whatever falls outside. Its images move along, unclipped.
+    pub fn paste(&mut self, other: &Frame, x: usize, y: usize) {
+        for row in 0..other.height.min(self.height.saturating_sub(y)) {
+            for col in 0..other.width.min(self.width.saturating_sub(x)) {
+                self.cells[(y + row) * self.width + x + col] = other.cells[row * other.width + col];
+            }
+        }
+        for image in &other.images {
+            self.images.push(FrameImage {
+                x: image.x + x,
+                y: image.y + y,
+                data: image.data.clone(),
+            });
+        }
+    }
+
+    /// Gives every cell without a background color `color`.
+    pub fn fill_empty_background(&mut self, color: Color) {
+        for cell in &mut self.cells {
+            cell.bg.get_or_insert(color);
+        }
+    }
+
+    /// Sets the background color of `len` cells starting at (`x`, `y`).
+    pub fn fill_background(&mut self, x: usize, y: usize, len: usize, color: Color) {
+        if y >= self.height {
+            return;
+        }
+        for col in x..(x + len).min(self.width) {
+            self.cells[y * self.width + col].bg = Some(color);
+        }
+    }
+
+    /// Writes a cell only if nothing has been drawn there yet, so background
+    /// layers never cover the cube.
+    pub fn put_background(&mut self, x: usize, y: usize, ch: char, color: Option<Color>) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let cell = &mut self.cells[y * self.width + x];
+        if *cell == Cell::default() {
+            *cell = Cell::new(ch, color);
+        }
+    }
+
+    /// Writes `text` starting at column `x` of row `y`, clipping at the edge.
+    pub fn put_str(&mut self, x: usize, y: usize, text: &str, color: Option<Color>) {
+        if y >= self.height {
+            return;
+        }
+        for (offset, ch) in text.chars().enumerate() {
+            let col = x + offset;
+            if col >= self.width {
+                break;
+            }
+            self.cells[y * self.width + col] = Cell::new(ch, color);
         }
     }
 
     pub fn as_ansi_string(&self) -> String {
         let mut output = String::with_capacity(self.cells.len() * 2);
-        let mut current_color: Option<Color> = None;
+        let mut current: (Option<Color>, Option<Color>) = (None, None);
         for y in 0..self.height {
             for x in 0..self.width {
                 let cell = self.cells[y * self.width + x];
-                if cell.color != current_color {
-                    if let Some(color) = cell.color {
-                        let _ = write!(&mut output, "{}", SetForegroundColor(color));
-                    } else {
+                if (cell.color, cell.bg) != current {
+                    if cell.color.is_none() || cell.bg.is_none() {
                         let _ = write!(&mut output, "{}", ResetColor);
                     }
-                    current_color = cell.color;
+                    if let Some(color) = cell.color {
+                        let _ = write!(&mut output, "{}", SetForegroundColor(palette::fit(color)));
+                    }
+                    if let Some(bg) = cell.bg {
+                        let _ = write!(&mut output, "{}", SetBackgroundColor(palette::fit(bg)));
+                    }
+                    current = (cell.color, cell.bg);
                 }
                 output.push(cell.ch);
             }
@@ -150,6 +1307,16 @@ impl Frame {
         let _ = write!(&mut output, "{}", ResetColor);
         output
     }
+
+    /// The frame's characters without any color escapes, one line per row.
+    pub fn as_plain_string(&self) -> String {
+        let mut output = String::with_capacity(self.cells.len() + self.height);
+        for row in self.cells.chunks(self.width.max(1)) {
+            output.extend(row.iter().map(|cell| cell.ch));
+            output.push('\n');
+        }
+        output
+    }
 }
 
 impl Frame {
@@ -158,15 +1325,23 @@ impl Frame {
             width: canvas.width,
             height: canvas.height,
             cells: canvas.cells.clone(),
+            images: Vec::new(),
         }
     }
 }
 
+const NO_OWNER: usize = usize::MAX;
+/// Owner of cells showing the cube body: filled, but not a sticker.
+const BODY_OWNER: usize = usize::MAX - 1;
+/// Owner of floor cells, which edge passes treat like empty ones.
+const GROUND_OWNER: usize = usize::MAX - 2;
+
 struct AsciiCanvas {
     width: usize,
     height: usize,
     cells: Vec<Cell>,
     depth: Vec<f32>,
+    owner: Vec<usize>,
 }
 
 impl AsciiCanvas {
@@ -177,6 +1352,7 @@ impl AsciiCanvas {
             height,
             cells: vec![Cell::default(); area],
             depth: vec![f32::INFINITY; area],
+            owner: vec![NO_OWNER; area],
         }
     }
 
@@ -191,25 +1367,120 @@ impl AsciiCanvas {
         let area = width * height;
         self.cells = vec![Cell::default(); area];
         self.depth = vec![f32::INFINITY; area];
+        self.owner = vec![NO_OWNER; area];
     }
 
     fn clear(&mut self) {
-        for cell in &mut self.cells {
-            *cell = Cell::default();
-        }
+        self.clear_cells();
         for depth in &mut self.depth {
             *depth = f32::INFINITY;
         }
+        for owner in &mut self.owner {
+            *owner = NO_OWNER;
+        }
+    }
+
+    /// Blanks every cell but keeps the depth and owner of what was drawn.
+    fn clear_cells(&mut self) {
+        for cell in &mut self.cells {
+            *cell = Cell::default();
+        }
     }
 
     fn plot(&mut self, x: usize, y: usize, depth: f32, ch: char, color: Option<Color>) {
+        self.plot_cell(x, y, depth, Cell::new(ch, color));
+    }
+
+    fn plot_cell(&mut self, x: usize, y: usize, depth: f32, cell: Cell) {
         if x >= self.width || y >= self.height {
             return;
         }
         let idx = y * self.width + x;
         if depth < self.depth[idx] {
             self.depth[idx] = depth;
-            self.cells[idx] = Cell { ch, color };
+            self.cells[idx] = cell;
+        }
+    }
+
+    /// Like `plot`, but keeps the background already in the cell, so glyphs
+    /// drawn over solid stickers keep the sticker color around them.
+    fn overlay(&mut self, x: usize, y: usize, depth: f32, ch: char, color: Option<Color>) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let bg = self.cells[y * self.width + x].bg;
+        self.plot_cell(x, y, depth, Cell { ch, color, bg });
+    }
+
+    /// Like `plot_cell`, but also records which facelet owns the cell so
+    /// edge passes can find sticker boundaries.
+    fn plot_facelet(&mut self, x: usize, y: usize, depth: f32, cell: Cell, facelet: usize) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let idx = y * self.width + x;
+        if depth < self.depth[idx] {
+            self.owner[idx] = facelet;
+        }
+        self.plot_cell(x, y, depth, cell);
+    }
+
+    /// Draws a line between two screen points paired with their view depths,
+    /// one cell per step along the longer axis, in a glyph that follows the
+    /// slope. Depth is interpolated like `Renderer::fill_triangle` does.
+    fn line(&mut self, from: (Vec2, f32), to: (Vec2, f32), color: Option<Color>) {
+        let ((a, a_depth), (b, b_depth)) = (from, to);
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let ch = line_glyph(dx, dy);
+        let steps = dx.abs().max(dy.abs()).ceil().max(1.0) as usize;
+        for step in 0..=steps {
+            let t = step as f32 / steps as f32;
+            let (x, y) = (a.x + dx * t, a.y + dy * t);
+            if x < 0.0 || y < 0.0 {
+                continue;
+            }
+            let depth = 1.0 / ((1.0 - t) / a_depth + t / b_depth);
+            self.plot(x as usize, y as usize, depth, ch, color);
+        }
+    }
+
+    /// Paints the empty or body cells bordering any sticker, tracing each
+    /// facelet without eating into its fill.
+    fn outline(&mut self, ch: char, color: Option<Color>) {
+        let owner = self.owner.clone();
+        let (width, height) = (self.width, self.height);
+        let is_sticker = |owner: usize| !matches!(owner, NO_OWNER | BODY_OWNER | GROUND_OWNER);
+        let owner_at = |x: isize, y: isize| {
+            if x < 0 || y < 0 || x as usize >= width || y as usize >= height {
+                NO_OWNER
+            } else {
+                owner[y as usize * width + x as usize]
+            }
+        };
+        for y in 0..height {
+            for x in 0..width {
+                let idx = y * width + x;
+                let empty = owner[idx] == NO_OWNER && self.cells[idx] == Cell::default();
+                if !empty && !matches!(owner[idx], BODY_OWNER | GROUND_OWNER) {
+                    continue;
+                }
+                let (xi, yi) = (x as isize, y as isize);
+                let edge = [(xi - 1, yi), (xi + 1, yi), (xi, yi - 1), (xi, yi + 1)]
+                    .iter()
+                    .any(|&(nx, ny)| is_sticker(owner_at(nx, ny)));
+                if edge {
+                    self.cells[y * width + x] = Cell::new(ch, color);
+                }
+            }
+        }
+    }
+
+    /// Fills the background of every cell owned by one of `facelets`.
+    fn highlight(&mut self, facelets: &[usize], bg: Color) {
+        for (cell, owner) in self.cells.iter_mut().zip(&self.owner) {
+            if facelets.contains(owner) {
+                cell.bg = Some(bg);
+            }
         }
     }
 
@@ -217,3 +1488,310 @@ impl AsciiCanvas {
         Frame::from_canvas(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::Move;
+
+    /// Compares against `src/snapshots/<name>.txt`; run the tests with
+    /// `UPDATE_SNAPSHOTS=1` to rewrite the golden files after an intended
+    /// rendering change.
+    fn assert_snapshot(name: &str, actual: &str) {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src/snapshots")
+            .join(format!("{name}.txt"));
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::write(&path, actual).expect("write snapshot");
+            return;
+        }
+        let expected = std::fs::read_to_string(&path).expect("read snapshot");
+        assert_eq!(actual, expected, "snapshot {name} changed");
+    }
+
+    #[test]
+    fn solved_cube_snapshot() {
+        let frame = render_snapshot(&Cube::new(), RendererKind::Raster);
+        assert_snapshot("solved", &frame);
+    }
+
+    #[test]
+    fn turned_cube_snapshot() {
+        let mut cube = Cube::new();
+        cube.apply_sequence(&[Move::R, Move::U]);
+        let frame = render_snapshot(&cube, RendererKind::Raster);
+        assert_snapshot("r_u", &frame);
+    }
+
+    #[test]
+    fn solved_cube_raycast_snapshot() {
+        let frame = render_snapshot(&Cube::new(), RendererKind::Raycast);
+        assert_snapshot("solved_raycast", &frame);
+    }
+
+    #[test]
+    fn turned_cube_raycast_snapshot() {
+        let mut cube = Cube::new();
+        cube.apply_sequence(&[Move::R, Move::U]);
+        let frame = render_snapshot(&cube, RendererKind::Raycast);
+        assert_snapshot("r_u_raycast", &frame);
+    }
+
+    #[test]
+    fn depth_is_interpolated_across_triangles() {
+        let settings = RenderSettings::default();
+        let mut renderer = Renderer::new(settings);
+        renderer.canvas.ensure_size(Viewport {
+            width: 10,
+            height: 4,
+        });
+        let paint = |facelet| StickerPaint {
+            facelet,
+            color: FaceColor::White,
+            brightness: 1.0,
+        };
+        // A flat triangle at depth 2 and one tilting from depth 1 on the
+        // left to 3 on the right cross halfway along.
+        let flat = [
+            (Vec2::new(0.0, 0.0), 2.0),
+            (Vec2::new(10.0, 0.0), 2.0),
+            (Vec2::new(0.0, 10.0), 2.0),
+        ];
+        let tilted = [
+            (Vec2::new(0.0, 0.0), 1.0),
+            (Vec2::new(10.0, 0.0), 3.0),
+            (Vec2::new(0.0, 10.0), 1.0),
+        ];
+        renderer.fill_triangle(flat, 0, |x, y| sticker_cell(paint(0), x, y, &settings));
+        renderer.fill_triangle(tilted, 1, |x, y| sticker_cell(paint(1), x, y, &settings));
+        assert_eq!(renderer.canvas.owner[0], 1);
+        assert_eq!(renderer.canvas.owner[8], 0);
+        assert!((renderer.canvas.depth[0] - 1.0 / (1.0 - 0.05 + 0.05 / 3.0)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn body_shows_only_between_stickers() {
+        let render = |body| {
+            let mut renderer = Renderer::new(RenderSettings {
+                body,
+                ..RenderSettings::default()
+            });
+            renderer.render(Cube::new().face_colors(), &Camera::new(), SNAPSHOT_VIEWPORT);
+            renderer.canvas.owner.clone()
+        };
+        let (bare, with_body) = (render(false), render(true));
+        assert!(with_body.contains(&BODY_OWNER));
+        for (bare, with_body) in bare.iter().zip(&with_body) {
+            if *bare != NO_OWNER {
+                assert_eq!(bare, with_body);
+            }
+        }
+    }
+
+    #[test]
+    fn mini_net_packs_two_sticker_rows_per_cell() {
+        let frame = render_mini_net(Cube::new().face_colors(), &RenderSettings::default());
+        assert_eq!((frame.width(), frame.height()), (15, 6));
+        // U's first two rows share a cell; its last row sits over the gap.
+        assert_eq!(frame.cell(4, 0).ch, '█');
+        assert_eq!(frame.cell(4, 1).ch, '▀');
+        assert_eq!(frame.cell(0, 0), Cell::default());
+        assert_eq!(frame.cell(0, 2).ch, '█');
+    }
+
+    #[test]
+    fn dither_mixes_the_two_nearest_shades() {
+        let ramp = theme::active().shades.clone();
+        let settings = RenderSettings {
+            gamma: 1.0,
+            ..RenderSettings::default()
+        };
+        // A quarter of the way from the third shade to the fourth.
+        let brightness = 2.25 / (ramp.len() - 1) as f32;
+        let tile: Vec<char> = (0..16)
+            .map(|i| shade_to_char(brightness, i % 4, i / 4, &settings))
+            .collect();
+        let upper = tile.iter().filter(|ch| **ch == ramp[3]).count();
+        assert_eq!(
+            tile.len() - upper,
+            tile.iter().filter(|ch| **ch == ramp[2]).count()
+        );
+        assert_eq!(upper, 4);
+        let flat = RenderSettings {
+            dither: false,
+            ..settings
+        };
+        assert!((0..16).all(|i| shade_to_char(brightness, i % 4, i / 4, &flat) == ramp[2]));
+    }
+
+    #[test]
+    fn raycast_labels_only_show_on_visible_stickers() {
+        let top_only: FaceletLabel = |facelet| (facelet < 9).then_some('U');
+        let labels = |camera: &Camera| {
+            let mut renderer = RaycastRenderer::new(RenderSettings::default());
+            renderer.set_labels(Some(top_only));
+            renderer
+                .render(Cube::new().face_colors(), camera, SNAPSHOT_VIEWPORT)
+                .as_plain_string()
+                .matches('U')
+                .count()
+        };
+        assert!(labels(&Camera::new()) > 0);
+        let mut below = Camera::new();
+        below.orbit(0.0, -2.0);
+        assert_eq!(labels(&below), 0);
+    }
+
+    #[test]
+    fn wireframe_draws_only_visible_edges() {
+        let render = |wireframe| {
+            let mut renderer = Renderer::new(RenderSettings {
+                wireframe,
+                ..RenderSettings::default()
+            });
+            renderer
+                .render(
+                    Cube::new().face_colors(),
+                    &Camera::new(),
+                    Viewport {
+                        width: 160,
+                        height: 80,
+                    },
+                )
+                .as_plain_string()
+        };
+        let (filled, wireframe) = (render(false), render(true));
+        let drawn = |text: &str| text.chars().filter(|ch| !ch.is_whitespace()).count();
+        assert!(drawn(&wireframe) > 0 && drawn(&wireframe) < drawn(&filled));
+        assert!(
+            wireframe
+                .chars()
+                .all(|ch| ch.is_whitespace() || "-|/\\".contains(ch))
+        );
+    }
+
+    #[test]
+    fn lines_follow_their_slope_behind_nearer_cells() {
+        let mut canvas = AsciiCanvas::new(6, 4);
+        canvas.plot(2, 0, 0.5, '#', None);
+        canvas.line((Vec2::new(0.0, 0.0), 1.0), (Vec2::new(5.0, 0.0), 1.0), None);
+        let top: String = canvas.cells[..6].iter().map(|cell| cell.ch).collect();
+        assert_eq!(top, "--#---");
+        assert_eq!(line_glyph(2.0, 2.0), '\\');
+        assert_eq!(line_glyph(-2.0, 2.0), '/');
+        assert_eq!(line_glyph(0.5, 3.0), '|');
+    }
+
+    #[test]
+    fn tinted_shading_scales_rgb_by_brightness() {
+        let settings = RenderSettings {
+            shading: ShadingStyle::Tinted,
+            gamma: 1.0,
+            ..RenderSettings::default()
+        };
+        let paint = StickerPaint {
+            facelet: 0,
+            color: FaceColor::Orange,
+            brightness: 0.5,
+        };
+        let cell = sticker_cell(paint, 0, 0, &settings);
+        assert_eq!(cell.ch, TINTED_GLYPH);
+        assert_eq!(
+            cell.color,
+            Some(Color::Rgb {
+                r: 128,
+                g: 70,
+                b: 0
+            })
+        );
+    }
+
+    #[test]
+    fn solid_stickers_fill_the_background() {
+        let settings = RenderSettings {
+            solid: true,
+            ..RenderSettings::default()
+        };
+        let paint = StickerPaint {
+            facelet: 0,
+            color: FaceColor::Red,
+            brightness: 0.5,
+        };
+        let cell = sticker_cell(paint, 0, 0, &settings);
+        assert_eq!(
+            (cell.ch, cell.color, cell.bg),
+            (' ', None, Some(Color::Red))
+        );
+        assert_eq!(subpixel_ink(cell), Some(Some(Color::Red)));
+
+        let mut canvas = AsciiCanvas::new(1, 1);
+        canvas.plot_cell(0, 0, 1.0, cell);
+        canvas.overlay(0, 0, 0.5, 'A', label_ink(FaceColor::Red, &settings));
+        assert_eq!(canvas.cells[0].bg, Some(Color::Red));
+        assert_eq!(canvas.cells[0].color, Some(Color::White));
+    }
+
+    #[test]
+    fn net_places_every_sticker_once() {
+        let viewport = Viewport {
+            width: 80,
+            height: 24,
+        };
+        let layout = NetLayout::fit(viewport);
+        assert_eq!((layout.sticker_width, layout.sticker_height), (4, 2));
+        for index in 0..FACELET_COUNT {
+            let (x, y) = layout.origin(index);
+            assert_eq!(layout.facelet_at(x, y), Some(index));
+        }
+        let frame = render_net(
+            Cube::new().face_colors(),
+            &RenderSettings::default(),
+            None,
+            &[],
+            viewport,
+        );
+        let (x, y) = layout.origin(0);
+        assert_ne!(frame.cells[y * frame.width + x], Cell::default());
+        assert_eq!(layout.facelet_at(0, 0), None);
+    }
+
+    #[test]
+    fn quadrant_fold_keeps_two_most_common_colors() {
+        let red = Some(Some(Color::Red));
+        let blue = Some(Some(Color::Blue));
+        let cell = fold_quadrant([red, red, blue, None]);
+        assert_eq!(cell.ch, '▀');
+        assert_eq!(cell.color, Some(Color::Red));
+        assert_eq!(cell.bg, Some(Color::Blue));
+        assert_eq!(fold_quadrant([None, None, None, red]).ch, '▗');
+        assert_eq!(fold_quadrant([None; 4]), Cell::default());
+    }
+
+    #[test]
+    fn half_block_fold_stacks_top_over_bottom() {
+        let red = Some(Some(Color::Red));
+        let blue = Some(Some(Color::Blue));
+        let cell = fold_half_block(red, blue);
+        assert_eq!(cell.ch, '▀');
+        assert_eq!(cell.color, Some(Color::Red));
+        assert_eq!(cell.bg, Some(Color::Blue));
+        assert_eq!(
+            fold_half_block(None, blue),
+            Cell::new('▄', Some(Color::Blue))
+        );
+        assert_eq!(fold_half_block(red, red).ch, '█');
+        assert_eq!(fold_half_block(None, None), Cell::default());
+    }
+
+    #[test]
+    fn braille_canvas_sets_one_dot_per_subpixel() {
+        let mut canvas = BrailleCanvas::new(2, 1);
+        canvas.plot(0, 0, Some(Color::Red));
+        canvas.plot(1, 3, Some(Color::Blue));
+        canvas.plot(1, 2, Some(Color::Blue));
+        canvas.plot(9, 9, Some(Color::Red));
+        let frame = canvas.to_frame();
+        assert_eq!(frame.cells[0], Cell::new('⢡', Some(Color::Blue)));
+        assert_eq!(frame.cells[1], Cell::default());
+    }
+}
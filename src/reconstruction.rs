@@ -0,0 +1,152 @@
+use crate::cube::{self, Cube, Move, ParseError};
+
+/// A solve written out as text: the scramble on the first line, then one
+/// line per step of the solution with the stage in a `//` comment, e.g.
+///
+/// ```text
+/// R U R' U' F2 D B2 L   // scramble
+/// y' F R' F'            // cross
+/// U R U' R'             // F2L 1
+/// ```
+#[derive(Debug)]
+pub struct Reconstruction {
+    pub scramble: Vec<Move>,
+    /// Each solution line's comment, or `Step n` without one, and its
+    /// moves.
+    pub stages: Vec<(String, Vec<Move>)>,
+}
+
+/// Reads a reconstruction and checks that its solution solves its
+/// scramble. Wide moves (`r`, `u'`, ...) and rotations (`x`, `y2`, ...)
+/// are played as the face and slice turns they are made of.
+pub fn parse(text: &str) -> Result<Reconstruction, String> {
+    let mut scramble = None;
+    let mut stages = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let code = code(line);
+        if code.trim().is_empty() {
+            continue;
+        }
+        let moves =
+            parse_moves(code).map_err(|err| format!("line {}: {}", number + 1, err.message))?;
+        if scramble.is_none() {
+            scramble = Some(moves);
+            continue;
+        }
+        let name = match line[code.len()..].trim_start_matches('/').trim() {
+            "" => format!("Step {}", stages.len() + 1),
+            name => name.to_string(),
+        };
+        stages.push((name, moves));
+    }
+    let scramble = scramble.ok_or("no scramble found")?;
+    if stages.is_empty() {
+        return Err("no solution after the scramble".to_string());
+    }
+    let mut cube = Cube::new();
+    cube.apply_sequence(&scramble);
+    for (_, moves) in &stages {
+        cube.apply_sequence(moves);
+    }
+    // Rotations may leave the solved cube facing another way.
+    if !cube.facelets_match_centers(|_| true) {
+        return Err("the solution does not solve the scramble".to_string());
+    }
+    Ok(Reconstruction { scramble, stages })
+}
+
+/// `line` without its `//` comment.
+pub fn code(line: &str) -> &str {
+    line.split("//").next().unwrap_or_default()
+}
+
+/// Reads moves in standard notation, along with wide moves and rotations.
+pub fn parse_moves(code: &str) -> Result<Vec<Move>, ParseError> {
+    cube::parse_algorithm(&expand(code))
+}
+
+/// `code` with each wide move and rotation spelled out as face and slice
+/// turns, which `parse_algorithm` reads.
+fn expand(code: &str) -> String {
+    let mut out = String::new();
+    let mut chars = code.chars().peekable();
+    while let Some(ch) = chars.next() {
+        // Each part is a turn and whether it goes against the wide move.
+        let parts: &[(char, bool)] = match ch {
+            'r' => &[('R', false), ('M', true)],
+            'l' => &[('L', false), ('M', false)],
+            'u' => &[('U', false), ('E', true)],
+            'd' => &[('D', false), ('E', false)],
+            'f' => &[('F', false), ('S', false)],
+            'b' => &[('B', false), ('S', true)],
+            'x' => &[('R', false), ('M', true), ('L', true)],
+            'y' => &[('U', false), ('E', true), ('D', true)],
+            'z' => &[('F', false), ('S', false), ('B', true)],
+            _ => {
+                out.push(ch);
+                continue;
+            }
+        };
+        let mut modifiers = String::new();
+        while let Some(&modifier) = chars.peek()
+            && matches!(modifier, '\'' | '’' | '2')
+        {
+            modifiers.push(modifier);
+            chars.next();
+        }
+        let double = modifiers.contains('2');
+        let prime = modifiers.contains(['\'', '’']);
+        out.push_str(" (");
+        for &(face, against) in parts {
+            out.push(' ');
+            out.push(face);
+            if double {
+                out.push('2');
+            } else if prime != against {
+                out.push('\'');
+            }
+        }
+        out.push_str(") ");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_stages_and_checks_the_solution() {
+        let text = "// sexy move\nR U R' U'   // scramble\n\nU R U' // first\nR'\n";
+        let reconstruction = parse(text).unwrap();
+        assert_eq!(
+            reconstruction.scramble,
+            vec![Move::R, Move::U, Move::RPrime, Move::UPrime]
+        );
+        assert_eq!(
+            reconstruction.stages,
+            vec![
+                ("first".to_string(), vec![Move::U, Move::R, Move::UPrime]),
+                ("Step 2".to_string(), vec![Move::RPrime]),
+            ]
+        );
+        assert_eq!(
+            parse("R U\nU' R\n").err().as_deref(),
+            Some("the solution does not solve the scramble")
+        );
+        assert!(parse("R U\n").is_err());
+        assert!(parse("R U\nU' Q\n").unwrap_err().starts_with("line 2:"));
+    }
+
+    #[test]
+    fn wide_moves_and_rotations_expand() {
+        let mut wide = Cube::new();
+        wide.apply_sequence(&cube::parse_algorithm(&expand("r U2 x' y2")).unwrap());
+        let mut turns = Cube::new();
+        turns.apply_sequence(&cube::parse_algorithm("R M' U2 R' M L U2 E2 D2").unwrap());
+        assert_eq!(wide, turns);
+        // y2 L y2 and r M each turn R once more. The last y leaves the
+        // solved cube turned, which still counts.
+        assert!(parse("R2\ny2 L y2\nr M y\n").is_ok());
+    }
+}
@@ -0,0 +1,420 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::config;
+use crate::palette;
+use crate::raster::{Cell, Frame};
+use crate::theme;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordFormat {
+    /// asciicast v2, for `asciinema play` and the asciinema web player.
+    Cast,
+    /// An animated GIF with one block of pixels per terminal cell.
+    Gif,
+}
+
+impl std::str::FromStr for RecordFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cast" => Ok(RecordFormat::Cast),
+            "gif" => Ok(RecordFormat::Gif),
+            other => Err(format!(
+                "unknown record format '{other}' (expected cast or gif)"
+            )),
+        }
+    }
+}
+
+impl RecordFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            RecordFormat::Cast => "cast",
+            RecordFormat::Gif => "gif",
+        }
+    }
+}
+
+/// Streams every blitted frame, stamped with the time since recording
+/// began, to `<stem>.cast` or `<stem>.gif`.
+pub struct Recorder {
+    path: PathBuf,
+    started: Instant,
+    encoder: Encoder,
+}
+
+enum Encoder {
+    Cast(CastWriter),
+    Gif(GifWriter),
+}
+
+impl Recorder {
+    pub fn create(stem: &Path, format: RecordFormat) -> io::Result<Self> {
+        let path = stem.with_extension(format.extension());
+        let out = BufWriter::new(File::create(&path)?);
+        let encoder = match format {
+            RecordFormat::Cast => Encoder::Cast(CastWriter {
+                out,
+                size: None,
+                last: String::new(),
+            }),
+            RecordFormat::Gif => Encoder::Gif(GifWriter {
+                out,
+                size: None,
+                pending: None,
+            }),
+        };
+        Ok(Self {
+            path,
+            started: Instant::now(),
+            encoder,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn capture(&mut self, frame: &Frame, ansi: &str) -> io::Result<()> {
+        let time = self.started.elapsed().as_secs_f32();
+        match &mut self.encoder {
+            Encoder::Cast(cast) => cast.capture(time, frame, ansi),
+            Encoder::Gif(gif) => gif.capture(time, frame),
+        }
+    }
+
+    /// Writes out whatever is still buffered and returns the file's path.
+    pub fn finish(self) -> io::Result<PathBuf> {
+        match self.encoder {
+            Encoder::Cast(mut cast) => cast.out.flush()?,
+            Encoder::Gif(gif) => gif.finish()?,
+        }
+        Ok(self.path)
+    }
+}
+
+/// asciicast v2: a JSON header line, then one `[time, "o", data]` line per
+/// change of screen and a `[time, "r", "WxH"]` line per resize.
+struct CastWriter {
+    out: BufWriter<File>,
+    size: Option<(usize, usize)>,
+    last: String,
+}
+
+impl CastWriter {
+    fn capture(&mut self, time: f32, frame: &Frame, ansi: &str) -> io::Result<()> {
+        let size = (frame.width(), frame.height());
+        match self.size {
+            None => {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |since| since.as_secs());
+                writeln!(
+                    self.out,
+                    r#"{{"version": 2, "width": {}, "height": {}, "timestamp": {timestamp}}}"#,
+                    size.0, size.1
+                )?;
+            }
+            Some(old) if old != size => {
+                writeln!(self.out, r#"[{time:.6}, "r", "{}x{}"]"#, size.0, size.1)?;
+            }
+            Some(_) if ansi == self.last => return Ok(()),
+            Some(_) => {}
+        }
+        self.size = Some(size);
+        // The same cursor move the frame writer puts before each frame.
+        let data = json_string(&format!("\x1b[1;1H{ansi}"));
+        writeln!(self.out, r#"[{time:.6}, "o", {data}]"#)?;
+        self.last = ansi.to_string();
+        Ok(())
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            ch if (ch as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// An animated GIF over the 256-color xterm palette. Each frame is held
+/// back until the next one differs, since its delay is only known then.
+struct GifWriter {
+    out: BufWriter<File>,
+    /// Pixel size, fixed by the first frame; later frames are clipped or
+    /// padded to it.
+    size: Option<(usize, usize)>,
+    pending: Option<(Vec<u8>, f32)>,
+}
+
+/// Foreground of cells that don't set one, as most terminals show it.
+const DEFAULT_FOREGROUND: [u8; 3] = [229, 229, 229];
+/// How long the last frame stays up before the GIF loops.
+const LAST_FRAME_SECONDS: f32 = 1.0;
+
+impl GifWriter {
+    fn capture(&mut self, time: f32, frame: &Frame) -> io::Result<()> {
+        let (cell_width, cell_height) = config::RECORD_GIF_CELL;
+        let size = match self.size {
+            Some(size) => size,
+            None => {
+                let size = (frame.width() * cell_width, frame.height() * cell_height);
+                self.write_header(size)?;
+                self.size = Some(size);
+                size
+            }
+        };
+        let pixels = rasterize(frame, size);
+        match self.pending.take() {
+            Some((previous, since)) if previous == pixels => {
+                self.pending = Some((previous, since));
+            }
+            Some((previous, since)) => {
+                self.write_image(&previous, time - since)?;
+                self.pending = Some((pixels, time));
+            }
+            None => self.pending = Some((pixels, time)),
+        }
+        Ok(())
+    }
+
+    fn write_header(&mut self, (width, height): (usize, usize)) -> io::Result<()> {
+        self.out.write_all(b"GIF89a")?;
+        self.out.write_all(&(width as u16).to_le_bytes())?;
+        self.out.write_all(&(height as u16).to_le_bytes())?;
+        // A global table of 256 colors, background color 0.
+        self.out.write_all(&[0xf7, 0, 0])?;
+        for index in 0..=255 {
+            self.out.write_all(&palette::ansi_rgb(index))?;
+        }
+        // Netscape extension: loop forever.
+        self.out
+            .write_all(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00")
+    }
+
+    fn write_image(&mut self, pixels: &[u8], seconds: f32) -> io::Result<()> {
+        let (width, height) = self.size.unwrap_or_default();
+        // Browsers stretch delays under 2 hundredths to 10.
+        let delay = ((seconds * 100.0).round() as u16).max(2);
+        self.out.write_all(&[0x21, 0xf9, 0x04, 0x00])?;
+        self.out.write_all(&delay.to_le_bytes())?;
+        self.out.write_all(&[0x00, 0x00])?;
+        self.out.write_all(&[0x2c, 0, 0, 0, 0])?;
+        self.out.write_all(&(width as u16).to_le_bytes())?;
+        self.out.write_all(&(height as u16).to_le_bytes())?;
+        self.out.write_all(&[0x00, LZW_MIN_CODE_SIZE])?;
+        for block in lzw_encode(pixels).chunks(255) {
+            self.out.write_all(&[block.len() as u8])?;
+            self.out.write_all(block)?;
+        }
+        self.out.write_all(&[0x00])
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        if let Some((pixels, _)) = self.pending.take() {
+            self.write_image(&pixels, LAST_FRAME_SECONDS)?;
+        }
+        if self.size.is_some() {
+            self.out.write_all(&[0x3b])?;
+        }
+        self.out.flush()
+    }
+}
+
+/// Palette indices for `frame` at `size` pixels. Each cell becomes a block
+/// in its background color mixed with its foreground by how much ink its
+/// glyph puts down: shade ramp glyphs by their place in the ramp, any
+/// other visible glyph in full.
+fn rasterize(frame: &Frame, (width, height): (usize, usize)) -> Vec<u8> {
+    let (cell_width, cell_height) = config::RECORD_GIF_CELL;
+    let shades = &theme::active().shades;
+    let ink = |ch: char| {
+        if ch == ' ' {
+            return 0.0;
+        }
+        match shades.iter().position(|shade| *shade == ch) {
+            Some(level) => level as f32 / (shades.len() - 1) as f32,
+            None => 1.0,
+        }
+    };
+    let mut colors: HashMap<Cell, u8> = HashMap::new();
+    let mut pixels = vec![0; width * height];
+    for row in 0..(height / cell_height).min(frame.height()) {
+        for col in 0..(width / cell_width).min(frame.width()) {
+            let cell = frame.cell(col, row);
+            let index = *colors.entry(cell).or_insert_with(|| {
+                let bg = cell.bg.and_then(palette::to_rgb).unwrap_or([0; 3]);
+                let fg = cell
+                    .color
+                    .and_then(palette::to_rgb)
+                    .unwrap_or(DEFAULT_FOREGROUND);
+                let ink = ink(cell.ch);
+                let mixed = [0, 1, 2]
+                    .map(|i| (bg[i] as f32 + (fg[i] as f32 - bg[i] as f32) * ink).round() as u8);
+                palette::nearest_256(mixed)
+            });
+            for y in row * cell_height..(row + 1) * cell_height {
+                let start = y * width + col * cell_width;
+                pixels[start..start + cell_width].fill(index);
+            }
+        }
+    }
+    pixels
+}
+
+const LZW_MIN_CODE_SIZE: u8 = 8;
+const LZW_MAX_CODE: u16 = 4095;
+
+/// GIF's variable-width LZW over 8-bit indices, codes packed least
+/// significant bit first. The table starts over with a clear code once all
+/// 4096 codes are used.
+fn lzw_encode(indices: &[u8]) -> Vec<u8> {
+    let clear = 1u16 << LZW_MIN_CODE_SIZE;
+    let end = clear + 1;
+    let mut bits = BitWriter::default();
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut width = LZW_MIN_CODE_SIZE + 1;
+    let mut next = end + 1;
+    bits.write(clear, width);
+    let Some((&first, rest)) = indices.split_first() else {
+        bits.write(end, width);
+        return bits.finish();
+    };
+    let mut prefix = u16::from(first);
+    for &index in rest {
+        if let Some(&code) = table.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+        bits.write(prefix, width);
+        if next > LZW_MAX_CODE {
+            bits.write(clear, width);
+            table.clear();
+            width = LZW_MIN_CODE_SIZE + 1;
+            next = end + 1;
+        } else {
+            table.insert((prefix, index), next);
+            // The decoder adds each entry one code later, so it is ready
+            // for wider codes just as the first one that needs them comes.
+            if next == 1 << width {
+                width += 1;
+            }
+            next += 1;
+        }
+        prefix = u16::from(index);
+    }
+    bits.write(prefix, width);
+    bits.write(end, width);
+    bits.finish()
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    pending: u32,
+    len: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, width: u8) {
+        self.pending |= u32::from(code) << self.len;
+        self.len += width;
+        while self.len >= 8 {
+            self.bytes.push(self.pending as u8);
+            self.pending >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.bytes.push(self.pending as u8);
+        }
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A plain GIF LZW decoder, to check the encoder against.
+    fn lzw_decode(data: &[u8]) -> Vec<u8> {
+        let clear = 256;
+        let mut read_bits = 0usize;
+        let mut read = |width: usize| {
+            let mut code = 0;
+            for bit in 0..width {
+                let at = read_bits + bit;
+                code |= usize::from(data[at / 8] >> (at % 8) & 1) << bit;
+            }
+            read_bits += width;
+            code
+        };
+        let mut output = Vec::new();
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let mut width = 9;
+        let mut previous: Option<Vec<u8>> = None;
+        loop {
+            let code = read(width);
+            if code == clear {
+                table = (0..=255u8).map(|i| vec![i]).collect();
+                table.extend([Vec::new(), Vec::new()]);
+                width = 9;
+                previous = None;
+                continue;
+            }
+            if code == clear + 1 {
+                return output;
+            }
+            let entry = match (table.get(code), &previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(previous)) => [previous.clone(), vec![previous[0]]].concat(),
+                (None, None) => panic!("unknown first code"),
+            };
+            if let Some(previous) = previous
+                && table.len() < 4096
+            {
+                table.push([previous, vec![entry[0]]].concat());
+                if table.len() == 1 << width && width < 12 {
+                    width += 1;
+                }
+            }
+            output.extend(&entry);
+            previous = Some(entry);
+        }
+    }
+
+    #[test]
+    fn lzw_round_trips_through_a_full_table() {
+        let short = [7, 7, 7, 7, 1, 2, 7, 7, 7];
+        assert_eq!(lzw_decode(&lzw_encode(&short)), short);
+        assert_eq!(lzw_decode(&lzw_encode(&[])), Vec::<u8>::new());
+        // Enough varied input to grow the codes to 12 bits and clear.
+        let long: Vec<u8> = (0..40_000u32).map(|i| (i * i / 7 % 251) as u8).collect();
+        assert_eq!(lzw_decode(&lzw_encode(&long)), long);
+    }
+
+    #[test]
+    fn cast_data_escapes_control_characters() {
+        assert_eq!(
+            json_string("\x1b[0m\"a\"\\\r\n"),
+            r#""\u001b[0m\"a\"\\\r\n""#
+        );
+    }
+}
@@ -0,0 +1,282 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+
+use crate::config;
+use crate::cube::FaceColor;
+use crate::geometry::{self, Camera, ProjectedFace, Vec2, Viewport};
+use crate::raster::{self, Frame, FrameImage, RenderBackend, RenderSettings};
+use crate::theme;
+
+/// How the cube is put on screen: as characters, or as a sixel bitmap on
+/// terminals that can show one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Graphics {
+    Text,
+    Sixel,
+    /// Sixel when the terminal looks like it supports it, else text.
+    Auto,
+}
+
+impl std::str::FromStr for Graphics {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Graphics::Text),
+            "sixel" => Ok(Graphics::Sixel),
+            "auto" => Ok(Graphics::Auto),
+            other => Err(format!(
+                "unknown graphics mode '{other}' (expected text, sixel or auto)"
+            )),
+        }
+    }
+}
+
+impl Graphics {
+    /// Whether to draw with sixels. Terminals don't announce sixel support
+    /// in the environment, so `Auto` goes by the ones known to have it.
+    pub fn use_sixel(self) -> bool {
+        match self {
+            Graphics::Text => false,
+            Graphics::Sixel => true,
+            Graphics::Auto => {
+                let term = std::env::var("TERM").ok();
+                let program = std::env::var("TERM_PROGRAM").ok();
+                supports_sixel(term.as_deref(), program.as_deref())
+            }
+        }
+    }
+}
+
+fn supports_sixel(term: Option<&str>, program: Option<&str>) -> bool {
+    const TERMS: [&str; 5] = ["sixel", "mlterm", "foot", "yaft", "contour"];
+    const PROGRAMS: [&str; 3] = ["WezTerm", "iTerm.app", "mintty"];
+    term.is_some_and(|term| TERMS.iter().any(|known| term.contains(known)))
+        || program.is_some_and(|program| PROGRAMS.contains(&program))
+}
+
+/// Draws the cube as a bitmap, `cell` pixels to a terminal cell, and hands
+/// it to the frame writer as a sixel image over an empty character frame.
+/// Labels, the ghost and highlights are only drawn by the text renderers.
+pub struct SixelRenderer {
+    settings: RenderSettings,
+    cell: (usize, usize),
+}
+
+impl SixelRenderer {
+    pub fn new(settings: RenderSettings, cell: (usize, usize)) -> Self {
+        Self { settings, cell }
+    }
+}
+
+impl RenderBackend for SixelRenderer {
+    fn render(&mut self, colors: &[FaceColor], camera: &Camera, viewport: Viewport) -> Frame {
+        let mut frame = Frame::blank(viewport);
+        // Stopping a row short keeps the image off the last line, where
+        // drawing it would scroll the screen.
+        let pixels = Viewport {
+            width: (viewport.width as usize * self.cell.0).min(u16::MAX as usize) as u16,
+            height: (viewport.height.saturating_sub(1) as usize * self.cell.1)
+                .min(u16::MAX as usize) as u16,
+        };
+        if pixels.width == 0 || pixels.height == 0 {
+            return frame;
+        }
+        let mut canvas = PixelCanvas::new(pixels.width as usize, pixels.height as usize);
+        if self.settings.body {
+            let body = theme::active().body;
+            let body = crate::palette::to_rgb(body).unwrap_or([0; 3]);
+            for face in geometry::project_body(colors, camera, pixels) {
+                canvas.fill_face(&face, body);
+            }
+        }
+        for face in geometry::project_cube(colors, camera, pixels) {
+            // Sixteen brightness steps keep the palette small.
+            let level = raster::gamma_correct(face.brightness, self.settings.gamma);
+            let level = (level * 16.0).round() / 16.0;
+            let rgb = config::face_color_to_rgb(face.color).map(|c| (c as f32 * level) as u8);
+            canvas.fill_face(&face, rgb);
+        }
+        frame.add_image(FrameImage {
+            x: 0,
+            y: 0,
+            data: canvas.to_sixel(),
+        });
+        frame
+    }
+
+    fn settings_mut(&mut self) -> &mut RenderSettings {
+        &mut self.settings
+    }
+}
+
+/// The bitmap counterpart of the character canvas: a color register per
+/// pixel (0 for nothing drawn) and a depth buffer.
+struct PixelCanvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+    depth: Vec<f32>,
+    /// RGB of registers 1 and up.
+    palette: Vec<[u8; 3]>,
+    registers: HashMap<[u8; 3], u8>,
+}
+
+/// Sixel terminals commonly offer 256 color registers.
+const MAX_REGISTERS: usize = 255;
+
+impl PixelCanvas {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height],
+            depth: vec![f32::INFINITY; width * height],
+            palette: Vec::new(),
+            registers: HashMap::new(),
+        }
+    }
+
+    /// The register holding `rgb`, claiming a new one while any are left.
+    fn register(&mut self, rgb: [u8; 3]) -> u8 {
+        if let Some(register) = self.registers.get(&rgb) {
+            return *register;
+        }
+        if self.palette.len() == MAX_REGISTERS {
+            return MAX_REGISTERS as u8;
+        }
+        self.palette.push(rgb);
+        let register = self.palette.len() as u8;
+        self.registers.insert(rgb, register);
+        register
+    }
+
+    fn fill_face(&mut self, face: &ProjectedFace, rgb: [u8; 3]) {
+        let register = self.register(rgb);
+        let corner = |i: usize| (face.points[i], face.point_depths[i]);
+        self.fill_triangle([corner(0), corner(1), corner(2)], register);
+        self.fill_triangle([corner(0), corner(2), corner(3)], register);
+    }
+
+    /// Fills pixels whose centers fall inside the triangle, interpolating
+    /// 1/depth across it the way the character rasterizer does.
+    fn fill_triangle(&mut self, corners: [(Vec2, f32); 3], register: u8) {
+        let [(a, _), (b, _), (c, _)] = corners;
+        let min_x = a.x.min(b.x).min(c.x).floor().max(0.0) as usize;
+        let max_x = (a.x.max(b.x).max(c.x).ceil() as usize).min(self.width);
+        let min_y = a.y.min(b.y).min(c.y).floor().max(0.0) as usize;
+        let max_y = (a.y.max(b.y).max(c.y).ceil() as usize).min(self.height);
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let p = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let Some(weights) = raster::barycentric(p, a, b, c) else {
+                    continue;
+                };
+                let inverse_depth: f32 = weights
+                    .iter()
+                    .zip(&corners)
+                    .map(|(weight, (_, depth))| weight / depth)
+                    .sum();
+                let depth = 1.0 / inverse_depth;
+                let idx = y * self.width + x;
+                if depth < self.depth[idx] {
+                    self.depth[idx] = depth;
+                    self.pixels[idx] = register;
+                }
+            }
+        }
+    }
+
+    /// Encodes the canvas as a sixel image with square pixels, leaving
+    /// pixels with nothing drawn transparent.
+    fn to_sixel(&self) -> String {
+        let mut sixel = String::new();
+        let _ = write!(sixel, "\x1bP9;1q\"1;1;{};{}", self.width, self.height);
+        for (i, [r, g, b]) in self.palette.iter().enumerate() {
+            let percent = |channel: &u8| u32::from(*channel) * 100 / 255;
+            let _ = write!(
+                sixel,
+                "#{};2;{};{};{}",
+                i + 1,
+                percent(r),
+                percent(g),
+                percent(b)
+            );
+        }
+        for top in (0..self.height).step_by(6) {
+            // Each register's six-pixel columns across this band.
+            let mut bands: BTreeMap<u8, Vec<u8>> = BTreeMap::new();
+            for dy in 0..6.min(self.height - top) {
+                let row = (top + dy) * self.width;
+                for x in 0..self.width {
+                    let register = self.pixels[row + x];
+                    if register != 0 {
+                        bands.entry(register).or_insert_with(|| vec![0; self.width])[x] |= 1 << dy;
+                    }
+                }
+            }
+            for (i, (register, columns)) in bands.iter().enumerate() {
+                if i > 0 {
+                    sixel.push('$');
+                }
+                let _ = write!(sixel, "#{register}");
+                push_runs(&mut sixel, columns);
+            }
+            sixel.push('-');
+        }
+        sixel.push_str("\x1b\\");
+        sixel
+    }
+}
+
+/// Appends sixel columns, run-length encoded and without trailing blanks.
+fn push_runs(sixel: &mut String, columns: &[u8]) {
+    let end = columns
+        .iter()
+        .rposition(|bits| *bits != 0)
+        .map_or(0, |at| at + 1);
+    let mut x = 0;
+    while x < end {
+        let bits = columns[x];
+        let run = columns[x..end]
+            .iter()
+            .take_while(|other| **other == bits)
+            .count();
+        let ch = (63 + bits) as char;
+        if run > 3 {
+            let _ = write!(sixel, "!{run}{ch}");
+        } else {
+            sixel.extend(std::iter::repeat_n(ch, run));
+        }
+        x += run;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_bands_per_register() {
+        let mut canvas = PixelCanvas::new(6, 7);
+        let red = canvas.register([255, 0, 0]);
+        let blue = canvas.register([0, 0, 255]);
+        for x in 0..5 {
+            canvas.pixels[x] = red;
+        }
+        canvas.pixels[6 + 1] = red;
+        canvas.pixels[6 * 6 + 5] = blue;
+        assert_eq!(
+            canvas.to_sixel(),
+            "\x1bP9;1q\"1;1;6;7#1;2;100;0;0#2;2;0;0;100#1@B@@@-#2!5?@-\x1b\\"
+        );
+    }
+
+    #[test]
+    fn recognizes_sixel_terminals() {
+        assert!(supports_sixel(Some("foot"), None));
+        assert!(supports_sixel(Some("xterm-256color"), Some("WezTerm")));
+        assert!(!supports_sixel(Some("xterm-256color"), None));
+        assert!(!supports_sixel(None, None));
+    }
+}
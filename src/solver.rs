@@ -0,0 +1,784 @@
+use std::collections::VecDeque;
+
+use once_cell::sync::Lazy;
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::cube::{self, Cube, Face, Move};
+
+const CORNERS: usize = 8;
+const EDGES: usize = 12;
+pub(crate) const TWISTS: usize = 2187;
+pub(crate) const FLIPS: usize = 2048;
+const SLICES: usize = 495;
+/// Slice coordinate with FR, FL, BL and BR all in the E slice.
+const SLICE_SOLVED: u16 = 494;
+pub(crate) const CORNER_PERMS: usize = 40320;
+const EDGE8_PERMS: usize = 40320;
+const SLICE_PERMS: usize = 24;
+
+/// Faces in the order the facelet string and the move indices use; move
+/// `face * 3 + power - 1` turns `MOVE_FACES[face]` `power` quarter turns.
+pub(crate) const MOVE_FACES: [char; 6] = ['U', 'R', 'F', 'D', 'L', 'B'];
+pub(crate) const MOVE_COUNT: usize = 18;
+/// U, U2, U', R2, F2, D, D2, D', L2, B2: the moves that keep a cube in the
+/// phase 2 group.
+const PHASE2_MOVES: [usize; 10] = [0, 1, 2, 4, 7, 9, 10, 11, 13, 16];
+
+/// Facelet-string positions of each corner's stickers, U/D sticker first
+/// and then clockwise. Corners are URF, UFL, ULB, UBR, DFR, DLF, DBL, DRB.
+const CORNER_FACELETS: [[usize; 3]; CORNERS] = [
+    [8, 9, 20],
+    [6, 18, 38],
+    [0, 36, 47],
+    [2, 45, 11],
+    [29, 26, 15],
+    [27, 44, 24],
+    [33, 53, 42],
+    [35, 17, 51],
+];
+const CORNER_COLORS: [[char; 3]; CORNERS] = [
+    ['U', 'R', 'F'],
+    ['U', 'F', 'L'],
+    ['U', 'L', 'B'],
+    ['U', 'B', 'R'],
+    ['D', 'F', 'R'],
+    ['D', 'L', 'F'],
+    ['D', 'B', 'L'],
+    ['D', 'R', 'B'],
+];
+/// Edges are UR, UF, UL, UB, DR, DF, DL, DB, FR, FL, BL, BR; the last four
+/// make up the E slice.
+const EDGE_FACELETS: [[usize; 2]; EDGES] = [
+    [5, 10],
+    [7, 19],
+    [3, 37],
+    [1, 46],
+    [32, 16],
+    [28, 25],
+    [30, 43],
+    [34, 52],
+    [23, 12],
+    [21, 41],
+    [50, 39],
+    [48, 14],
+];
+const EDGE_COLORS: [[char; 2]; EDGES] = [
+    ['U', 'R'],
+    ['U', 'F'],
+    ['U', 'L'],
+    ['U', 'B'],
+    ['D', 'R'],
+    ['D', 'F'],
+    ['D', 'L'],
+    ['D', 'B'],
+    ['F', 'R'],
+    ['F', 'L'],
+    ['B', 'L'],
+    ['B', 'R'],
+];
+
+/// The cube as pieces: which piece sits in each slot and how it is twisted
+/// or flipped there.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct CubieCube {
+    pub cp: [u8; CORNERS],
+    pub co: [u8; CORNERS],
+    pub ep: [u8; EDGES],
+    pub eo: [u8; EDGES],
+}
+
+pub(crate) const SOLVED: CubieCube = CubieCube {
+    cp: [0, 1, 2, 3, 4, 5, 6, 7],
+    co: [0; CORNERS],
+    ep: [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+    eo: [0; EDGES],
+};
+
+/// Quarter turns of U, R, F, D, L and B.
+const BASIC_MOVES: [CubieCube; 6] = [
+    CubieCube {
+        cp: [3, 0, 1, 2, 4, 5, 6, 7],
+        co: [0; CORNERS],
+        ep: [3, 0, 1, 2, 4, 5, 6, 7, 8, 9, 10, 11],
+        eo: [0; EDGES],
+    },
+    CubieCube {
+        cp: [4, 1, 2, 0, 7, 5, 6, 3],
+        co: [2, 0, 0, 1, 1, 0, 0, 2],
+        ep: [8, 1, 2, 3, 11, 5, 6, 7, 4, 9, 10, 0],
+        eo: [0; EDGES],
+    },
+    CubieCube {
+        cp: [1, 5, 2, 3, 0, 4, 6, 7],
+        co: [1, 2, 0, 0, 2, 1, 0, 0],
+        ep: [0, 9, 2, 3, 4, 8, 6, 7, 1, 5, 10, 11],
+        eo: [0, 1, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0],
+    },
+    CubieCube {
+        cp: [0, 1, 2, 3, 5, 6, 7, 4],
+        co: [0; CORNERS],
+        ep: [0, 1, 2, 3, 5, 6, 7, 4, 8, 9, 10, 11],
+        eo: [0; EDGES],
+    },
+    CubieCube {
+        cp: [0, 2, 6, 3, 4, 1, 5, 7],
+        co: [0, 1, 2, 0, 0, 2, 1, 0],
+        ep: [0, 1, 10, 3, 4, 5, 9, 7, 8, 2, 6, 11],
+        eo: [0; EDGES],
+    },
+    CubieCube {
+        cp: [0, 1, 3, 7, 4, 5, 2, 6],
+        co: [0, 0, 1, 2, 0, 0, 2, 1],
+        ep: [0, 1, 2, 11, 4, 5, 6, 10, 8, 9, 3, 7],
+        eo: [0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 1, 1],
+    },
+];
+
+static MOVE_CUBES: Lazy<Vec<CubieCube>> = Lazy::new(|| {
+    let mut cubes = Vec::with_capacity(MOVE_COUNT);
+    for basic in &BASIC_MOVES {
+        let mut cube = SOLVED;
+        for _ in 0..3 {
+            cube = cube.multiply(basic);
+            cubes.push(cube);
+        }
+    }
+    cubes
+});
+
+impl CubieCube {
+    /// Reads a facelet string, rejecting states no sequence of turns can
+    /// reach.
+    pub(crate) fn from_facelets(facelets: &str) -> Result<Self, String> {
+        let cube = Self::read_pieces(facelets)?;
+        cube.verify()?;
+        Ok(cube)
+    }
+
+    /// Which piece is in each slot and how it sits there, without checking
+    /// that a 3x3 could reach the result.
+    fn read_pieces(facelets: &str) -> Result<Self, String> {
+        let f: Vec<char> = facelets.chars().collect();
+        let mut cube = SOLVED;
+        for (slot, stickers) in CORNER_FACELETS.iter().enumerate() {
+            let twist = (0..3)
+                .find(|&ori| matches!(f[stickers[ori]], 'U' | 'D'))
+                .ok_or("a corner has no U or D sticker")?;
+            let colors = [0, 1, 2].map(|i| f[stickers[(twist + i) % 3]]);
+            let piece = CORNER_COLORS
+                .iter()
+                .position(|want| *want == colors)
+                .ok_or("a corner has impossible colors")?;
+            cube.cp[slot] = piece as u8;
+            cube.co[slot] = twist as u8;
+        }
+        for (slot, stickers) in EDGE_FACELETS.iter().enumerate() {
+            let colors = [f[stickers[0]], f[stickers[1]]];
+            let (piece, flip) = EDGE_COLORS
+                .iter()
+                .enumerate()
+                .find_map(|(piece, want)| {
+                    if colors == *want {
+                        Some((piece, 0))
+                    } else if colors == [want[1], want[0]] {
+                        Some((piece, 1))
+                    } else {
+                        None
+                    }
+                })
+                .ok_or("an edge has impossible colors")?;
+            cube.ep[slot] = piece as u8;
+            cube.eo[slot] = flip;
+        }
+        Ok(cube)
+    }
+
+    fn verify(&self) -> Result<(), String> {
+        let mut corners = self.cp;
+        corners.sort_unstable();
+        let mut edges = self.ep;
+        edges.sort_unstable();
+        if corners != SOLVED.cp || edges != SOLVED.ep {
+            return Err("a piece appears twice".to_string());
+        }
+        if self.co.iter().map(|&t| t as u32).sum::<u32>() % 3 != 0 {
+            return Err("a corner is twisted".to_string());
+        }
+        if self.eo.iter().map(|&t| t as u32).sum::<u32>() % 2 != 0 {
+            return Err("an edge is flipped".to_string());
+        }
+        if is_odd(&self.cp) != is_odd(&self.ep) {
+            return Err("two pieces are swapped".to_string());
+        }
+        Ok(())
+    }
+
+    /// The state after turning move index `m`.
+    pub fn apply(&self, m: usize) -> CubieCube {
+        self.multiply(&MOVE_CUBES[m])
+    }
+
+    /// The state reached by applying `other` after `self`.
+    fn multiply(&self, other: &CubieCube) -> CubieCube {
+        let mut product = *self;
+        for i in 0..CORNERS {
+            let from = other.cp[i] as usize;
+            product.cp[i] = self.cp[from];
+            product.co[i] = (self.co[from] + other.co[i]) % 3;
+        }
+        for i in 0..EDGES {
+            let from = other.ep[i] as usize;
+            product.ep[i] = self.ep[from];
+            product.eo[i] = (self.eo[from] + other.eo[i]) % 2;
+        }
+        product
+    }
+
+    pub(crate) fn twist(&self) -> u16 {
+        self.co[..CORNERS - 1]
+            .iter()
+            .fold(0, |acc, &t| acc * 3 + t as u16)
+    }
+
+    fn set_twist(&mut self, mut twist: u16) {
+        let mut sum = 0;
+        for i in (0..CORNERS - 1).rev() {
+            self.co[i] = (twist % 3) as u8;
+            sum += self.co[i];
+            twist /= 3;
+        }
+        self.co[CORNERS - 1] = (3 - sum % 3) % 3;
+    }
+
+    pub(crate) fn flip(&self) -> u16 {
+        self.eo[..EDGES - 1]
+            .iter()
+            .fold(0, |acc, &t| acc * 2 + t as u16)
+    }
+
+    fn set_flip(&mut self, mut flip: u16) {
+        let mut sum = 0;
+        for i in (0..EDGES - 1).rev() {
+            self.eo[i] = (flip % 2) as u8;
+            sum += self.eo[i];
+            flip /= 2;
+        }
+        self.eo[EDGES - 1] = sum % 2;
+    }
+
+    /// Which four slots hold the E-slice edges, ranked as a combination.
+    pub(crate) fn slice(&self) -> u16 {
+        self.ep
+            .iter()
+            .enumerate()
+            .filter(|(_, piece)| **piece >= 8)
+            .enumerate()
+            .map(|(k, (slot, _))| binomial(slot, k + 1))
+            .sum()
+    }
+
+    fn set_slice(&mut self, mut slice: u16) {
+        let mut in_slice = [false; EDGES];
+        for k in (1..=4).rev() {
+            let mut slot = k - 1;
+            while binomial(slot + 1, k) <= slice {
+                slot += 1;
+            }
+            in_slice[slot] = true;
+            slice -= binomial(slot, k);
+        }
+        let (mut slice_piece, mut other_piece) = (8, 0);
+        for (slot, piece) in self.ep.iter_mut().enumerate() {
+            if in_slice[slot] {
+                *piece = slice_piece;
+                slice_piece += 1;
+            } else {
+                *piece = other_piece;
+                other_piece += 1;
+            }
+        }
+    }
+
+    fn phase2_coords(&self) -> (u16, u16, u16) {
+        let slice: Vec<u8> = self.ep[8..].iter().map(|piece| piece - 8).collect();
+        (rank(&self.cp), rank(&self.ep[..8]), rank(&slice))
+    }
+}
+
+fn binomial(n: usize, k: usize) -> u16 {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1, |acc, i| acc * (n - i) as u16 / (i + 1) as u16)
+}
+
+fn is_odd(perm: &[u8]) -> bool {
+    let mut inversions = 0;
+    for i in 0..perm.len() {
+        inversions += perm[i + 1..].iter().filter(|&&p| p < perm[i]).count();
+    }
+    inversions % 2 == 1
+}
+
+/// Lexicographic rank of a permutation of `0..perm.len()`.
+pub(crate) fn rank(perm: &[u8]) -> u16 {
+    perm.iter().enumerate().fold(0, |acc, (i, &p)| {
+        let smaller_after = perm[i + 1..].iter().filter(|&&q| q < p).count();
+        acc * (perm.len() - i) as u16 + smaller_after as u16
+    })
+}
+
+pub(crate) fn unrank(mut index: u16, perm: &mut [u8]) {
+    let n = perm.len();
+    let mut digits = vec![0; n];
+    for (i, digit) in digits.iter_mut().enumerate().rev() {
+        let base = (n - i) as u16;
+        *digit = (index % base) as usize;
+        index /= base;
+    }
+    let mut unused: Vec<u8> = (0..n as u8).collect();
+    for (slot, digit) in perm.iter_mut().zip(digits) {
+        *slot = unused.remove(digit);
+    }
+}
+
+/// Move and pruning tables, built on first use (well under a second in a
+/// release build).
+pub(crate) struct Tables {
+    pub twist_move: Vec<u16>,
+    pub flip_move: Vec<u16>,
+    pub slice_move: Vec<u16>,
+    corner_move: Vec<u16>,
+    edge8_move: Vec<u16>,
+    slice_perm_move: Vec<u16>,
+    pub slice_twist_depth: Vec<u8>,
+    pub slice_flip_depth: Vec<u8>,
+    slice_corner_depth: Vec<u8>,
+    slice_edge8_depth: Vec<u8>,
+}
+
+pub(crate) static TABLES: Lazy<Tables> = Lazy::new(Tables::build);
+
+impl Tables {
+    fn build() -> Self {
+        let twist_move = move_table(TWISTS, 0..MOVE_COUNT, CubieCube::set_twist, |c| c.twist());
+        let flip_move = move_table(FLIPS, 0..MOVE_COUNT, CubieCube::set_flip, |c| c.flip());
+        let slice_move = move_table(SLICES, 0..MOVE_COUNT, CubieCube::set_slice, |c| c.slice());
+        let corner_move = move_table(
+            CORNER_PERMS,
+            PHASE2_MOVES,
+            |c, i| unrank(i, &mut c.cp),
+            |c| rank(&c.cp),
+        );
+        let edge8_move = move_table(
+            EDGE8_PERMS,
+            PHASE2_MOVES,
+            |c, i| unrank(i, &mut c.ep[..8]),
+            |c| rank(&c.ep[..8]),
+        );
+        let slice_perm_move = move_table(
+            SLICE_PERMS,
+            PHASE2_MOVES,
+            |c, i| {
+                unrank(i, &mut c.ep[8..]);
+                c.ep[8..].iter_mut().for_each(|piece| *piece += 8);
+            },
+            |c| c.phase2_coords().2,
+        );
+        let phase1 = MOVE_COUNT;
+        let phase2 = PHASE2_MOVES.len();
+        let slice_twist_depth = depth_table(
+            SLICES * TWISTS,
+            SLICE_SOLVED as usize * TWISTS,
+            phase1,
+            |state, m| {
+                let (slice, twist) = (state / TWISTS, state % TWISTS);
+                slice_move[slice * phase1 + m] as usize * TWISTS
+                    + twist_move[twist * phase1 + m] as usize
+            },
+        );
+        let slice_flip_depth = depth_table(
+            SLICES * FLIPS,
+            SLICE_SOLVED as usize * FLIPS,
+            phase1,
+            |state, m| {
+                let (slice, flip) = (state / FLIPS, state % FLIPS);
+                slice_move[slice * phase1 + m] as usize * FLIPS
+                    + flip_move[flip * phase1 + m] as usize
+            },
+        );
+        let slice_corner_depth = depth_table(SLICE_PERMS * CORNER_PERMS, 0, phase2, |state, m| {
+            let (slice, corner) = (state / CORNER_PERMS, state % CORNER_PERMS);
+            slice_perm_move[slice * phase2 + m] as usize * CORNER_PERMS
+                + corner_move[corner * phase2 + m] as usize
+        });
+        let slice_edge8_depth = depth_table(SLICE_PERMS * EDGE8_PERMS, 0, phase2, |state, m| {
+            let (slice, edge8) = (state / EDGE8_PERMS, state % EDGE8_PERMS);
+            slice_perm_move[slice * phase2 + m] as usize * EDGE8_PERMS
+                + edge8_move[edge8 * phase2 + m] as usize
+        });
+        Self {
+            twist_move,
+            flip_move,
+            slice_move,
+            corner_move,
+            edge8_move,
+            slice_perm_move,
+            slice_twist_depth,
+            slice_flip_depth,
+            slice_corner_depth,
+            slice_edge8_depth,
+        }
+    }
+}
+
+/// Where each of `size` coordinate values goes under each of `moves`,
+/// found by building a representative cube for the value and turning it.
+pub(crate) fn move_table(
+    size: usize,
+    moves: impl IntoIterator<Item = usize> + Clone,
+    set: impl Fn(&mut CubieCube, u16),
+    get: impl Fn(&CubieCube) -> u16,
+) -> Vec<u16> {
+    let mut table = Vec::with_capacity(size * MOVE_COUNT);
+    for value in 0..size {
+        let mut cube = SOLVED;
+        set(&mut cube, value as u16);
+        for m in moves.clone() {
+            table.push(get(&cube.multiply(&MOVE_CUBES[m])));
+        }
+    }
+    table
+}
+
+/// Breadth-first distances from `start` to every state, a lower bound on
+/// the moves left that the search prunes with.
+fn depth_table(
+    size: usize,
+    start: usize,
+    moves: usize,
+    next: impl Fn(usize, usize) -> usize,
+) -> Vec<u8> {
+    let mut depths = vec![u8::MAX; size];
+    depths[start] = 0;
+    let mut queue = VecDeque::from([start]);
+    while let Some(state) = queue.pop_front() {
+        let depth = depths[state];
+        for m in 0..moves {
+            let neighbour = next(state, m);
+            if depths[neighbour] == u8::MAX {
+                depths[neighbour] = depth + 1;
+                queue.push_back(neighbour);
+            }
+        }
+    }
+    depths
+}
+
+/// Skips a turn of the face just turned, and the second of two opposite
+/// faces when it would undo the usual U-before-D order.
+pub(crate) fn redundant(previous: Option<usize>, m: usize) -> bool {
+    previous.is_some_and(|previous| {
+        let (face, previous_face) = (m / 3, previous / 3);
+        face == previous_face || previous_face == face + 3
+    })
+}
+
+struct Search<'a> {
+    tables: &'a Tables,
+    start: CubieCube,
+    max_length: usize,
+    path: Vec<usize>,
+}
+
+impl Search<'_> {
+    fn phase1(&mut self, twist: u16, flip: u16, slice: u16, depth: usize) -> bool {
+        let t = self.tables;
+        if depth == 0 {
+            // A phase 1 solution ending in a phase 2 move is a shorter one
+            // already tried, followed by a move phase 2 could make itself.
+            let ends_in_phase2 = self.path.last().is_some_and(|m| PHASE2_MOVES.contains(m));
+            return twist == 0
+                && flip == 0
+                && slice == SLICE_SOLVED
+                && !ends_in_phase2
+                && self.start_phase2();
+        }
+        let slice_index = slice as usize;
+        let bound = t.slice_twist_depth[slice_index * TWISTS + twist as usize]
+            .max(t.slice_flip_depth[slice_index * FLIPS + flip as usize]);
+        if bound as usize > depth {
+            return false;
+        }
+        for m in 0..MOVE_COUNT {
+            if redundant(self.path.last().copied(), m) {
+                continue;
+            }
+            self.path.push(m);
+            let found = self.phase1(
+                t.twist_move[twist as usize * MOVE_COUNT + m],
+                t.flip_move[flip as usize * MOVE_COUNT + m],
+                t.slice_move[slice_index * MOVE_COUNT + m],
+                depth - 1,
+            );
+            if found {
+                return true;
+            }
+            self.path.pop();
+        }
+        false
+    }
+
+    fn start_phase2(&mut self) -> bool {
+        let cube = self
+            .path
+            .iter()
+            .fold(self.start, |cube, &m| cube.multiply(&MOVE_CUBES[m]));
+        let (corner, edge8, slice) = cube.phase2_coords();
+        let phase1_length = self.path.len();
+        (0..=self.max_length - phase1_length).any(|depth| self.phase2(corner, edge8, slice, depth))
+    }
+
+    fn phase2(&mut self, corner: u16, edge8: u16, slice: u16, depth: usize) -> bool {
+        let t = self.tables;
+        let row = slice as usize * CORNER_PERMS;
+        let bound = t.slice_corner_depth[row + corner as usize]
+            .max(t.slice_edge8_depth[row + edge8 as usize]);
+        if bound as usize > depth {
+            return false;
+        }
+        if depth == 0 {
+            return true;
+        }
+        let count = PHASE2_MOVES.len();
+        for (i, &m) in PHASE2_MOVES.iter().enumerate() {
+            if redundant(self.path.last().copied(), m) {
+                continue;
+            }
+            self.path.push(m);
+            let found = self.phase2(
+                t.corner_move[corner as usize * count + i],
+                t.edge8_move[edge8 as usize * count + i],
+                t.slice_perm_move[slice as usize * count + i],
+                depth - 1,
+            );
+            if found {
+                return true;
+            }
+            self.path.pop();
+        }
+        false
+    }
+}
+
+/// Solves `cube` with Kociemba's two-phase algorithm: phase 1 orients
+/// every piece and gathers the E-slice edges into the E slice, phase 2
+/// finishes with U, D and half turns only. Returns the first solution of
+/// at most `max_length` moves, trying the shortest phase 1 first, so
+/// solutions are near-optimal rather than optimal.
+pub fn solve(cube: &Cube, max_length: usize) -> Result<Vec<Move>, String> {
+    solve_cubie(cubie_state(cube)?, max_length)
+}
+
+fn solve_cubie(start: CubieCube, max_length: usize) -> Result<Vec<Move>, String> {
+    let tables = &*TABLES;
+    let mut search = Search {
+        tables,
+        start,
+        max_length,
+        path: Vec::new(),
+    };
+    let found = (0..=max_length)
+        .any(|depth| search.phase1(start.twist(), start.flip(), start.slice(), depth));
+    if !found {
+        return Err(format!("no solution within {max_length} moves"));
+    }
+    Ok(to_moves(&search.path))
+}
+
+/// A state drawn uniformly from every position reachable by turning:
+/// random permutations with matching parity, random twist and flip.
+fn random_cubie<R: Rng + ?Sized>(rng: &mut R) -> CubieCube {
+    let mut cube = SOLVED;
+    cube.cp.shuffle(rng);
+    cube.ep.shuffle(rng);
+    if is_odd(&cube.cp) != is_odd(&cube.ep) {
+        cube.ep.swap(0, 1);
+    }
+    cube.set_twist(rng.gen_range(0..TWISTS as u16));
+    cube.set_flip(rng.gen_range(0..FLIPS as u16));
+    cube
+}
+
+/// A random U layer over solved F2L: the four U corners and edges permuted
+/// with matching parity, twisted and flipped at random so the totals stay
+/// legal. Never the solved state.
+fn random_last_layer<R: Rng + ?Sized>(rng: &mut R) -> CubieCube {
+    loop {
+        let mut cube = SOLVED;
+        cube.cp[..4].shuffle(rng);
+        cube.ep[..4].shuffle(rng);
+        if is_odd(&cube.cp) != is_odd(&cube.ep) {
+            cube.ep.swap(0, 1);
+        }
+        for i in 0..3 {
+            cube.co[i] = rng.gen_range(0..3);
+            cube.eo[i] = rng.gen_range(0..2);
+        }
+        cube.co[3] = (6 - cube.co[..3].iter().sum::<u8>()) % 3;
+        cube.eo[3] = cube.eo[..3].iter().sum::<u8>() % 2;
+        if cube != SOLVED {
+            return cube;
+        }
+    }
+}
+
+/// A scramble for last-layer practice: F2L solved and a uniformly random
+/// last layer, reached by the inverse of its two-phase solution.
+pub fn random_last_layer_scramble<R: Rng + ?Sized>(rng: &mut R, max_length: usize) -> Vec<Move> {
+    loop {
+        if let Ok(solution) = solve_cubie(random_last_layer(rng), max_length) {
+            return cube::invert(&solution);
+        }
+    }
+}
+
+/// A WCA-style random-state scramble: picks a uniformly random state and
+/// returns the inverse of its two-phase solution, a sequence that reaches
+/// that state from solved.
+pub fn random_state_scramble<R: Rng + ?Sized>(rng: &mut R, max_length: usize) -> Vec<Move> {
+    loop {
+        if let Ok(solution) = solve_cubie(random_cubie(rng), max_length) {
+            return cube::invert(&solution);
+        }
+    }
+}
+
+/// `cube` as pieces, provided its centers are where solving expects them.
+pub(crate) fn cubie_state(cube: &Cube) -> Result<CubieCube, String> {
+    if Face::all()
+        .iter()
+        .any(|face| cube.center_color(*face) != face.default_color())
+    {
+        return Err(
+            "centers are out of place; undo the slice turns or reorient with Ctrl+O".to_string(),
+        );
+    }
+    CubieCube::from_facelets(&cube.to_facelet_string())
+}
+
+/// Whether `cube`'s corners and edges are permuted with different
+/// parities: two pieces swapped, which no 3x3 can reach but a reduced 4x4
+/// can (PLL parity). False for anything that can't be read as pieces.
+pub fn has_swap_parity(cube: &Cube) -> bool {
+    CubieCube::read_pieces(&cube.to_facelet_string())
+        .is_ok_and(|state| is_odd(&state.cp) != is_odd(&state.ep))
+}
+
+/// Move index of an outer face turn; slice turns have none.
+pub(crate) fn move_index(mv: Move) -> Option<usize> {
+    let notation = mv.notation();
+    let face = MOVE_FACES
+        .iter()
+        .position(|letter| notation.starts_with(*letter))?;
+    let power = match notation.chars().nth(1) {
+        Some('2') => 2,
+        Some('\'') => 3,
+        _ => 1,
+    };
+    Some(face * 3 + power - 1)
+}
+
+pub(crate) fn to_moves(path: &[usize]) -> Vec<Move> {
+    path.iter()
+        .map(|&m| {
+            let power = m % 3 + 1;
+            cube::move_for(MOVE_FACES[m / 3], power == 3, power == 2)
+                .expect("solver moves are face turns")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn cubie_moves_match_the_facelet_cube() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        let mut cube = Cube::new();
+        let scramble = cube.scramble_with(cube::ScrambleStyle::Standard, 30, &mut rng);
+        let expected = scramble
+            .iter()
+            .fold(SOLVED, |state, mv| state.apply(move_index(*mv).unwrap()));
+        assert_eq!(
+            CubieCube::from_facelets(&cube.to_facelet_string()),
+            Ok(expected)
+        );
+    }
+
+    #[test]
+    fn coordinates_round_trip() {
+        let mut cube = SOLVED;
+        for value in [0, 1, 777, TWISTS as u16 - 1] {
+            cube.set_twist(value);
+            assert_eq!(cube.twist(), value);
+        }
+        for value in [0, 5, 1500, FLIPS as u16 - 1] {
+            cube.set_flip(value);
+            assert_eq!(cube.flip(), value);
+        }
+        for value in [0, 100, SLICE_SOLVED] {
+            cube.set_slice(value);
+            assert_eq!(cube.slice(), value);
+        }
+        assert_eq!(SOLVED.slice(), SLICE_SOLVED);
+        let mut perm = [0; 8];
+        unrank(12345, &mut perm);
+        assert_eq!(rank(&perm), 12345);
+    }
+
+    #[test]
+    fn solves_a_random_scramble() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+        let mut cube = Cube::new();
+        cube.scramble_with(cube::ScrambleStyle::Standard, 25, &mut rng);
+        let solution = solve(&cube, 24).unwrap();
+        assert!(solution.len() <= 24);
+        cube.apply_sequence(&solution);
+        assert!(cube.is_solved());
+
+        assert_eq!(solve(&Cube::new(), 24), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn random_state_scramble_reaches_the_drawn_state() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        let state = random_cubie(&mut rng);
+        assert_eq!(state.verify(), Ok(()));
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        let scramble = random_state_scramble(&mut rng, 24);
+        assert!(scramble.len() <= 24);
+        let mut cube = Cube::new();
+        cube.apply_sequence(&scramble);
+        assert_eq!(cubie_state(&cube), Ok(state));
+    }
+
+    #[test]
+    fn random_last_layer_keeps_f2l() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        for _ in 0..3 {
+            let state = random_last_layer(&mut rng);
+            assert_eq!(state.verify(), Ok(()));
+            assert_eq!(state.cp[4..], SOLVED.cp[4..]);
+            assert_eq!(state.ep[4..], SOLVED.ep[4..]);
+        }
+        let mut cube = Cube::new();
+        cube.apply_sequence(&random_last_layer_scramble(&mut rng, 24));
+        assert!(!cube.is_solved());
+        assert!(cube.facelets_match_centers(|desc| desc.coord.y <= 0));
+    }
+}
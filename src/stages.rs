@@ -0,0 +1,384 @@
+use crate::cube::{
+    Cube, Face, FaceColor, FaceletDescriptor, LatticePoint, Move, facelet_descriptors,
+};
+
+/// The solving method whose stage the status line follows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Method {
+    Cfop,
+    Roux,
+}
+
+impl std::str::FromStr for Method {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cfop" => Ok(Method::Cfop),
+            "roux" => Ok(Method::Roux),
+            other => Err(format!("unknown method '{other}' (expected cfop or roux)")),
+        }
+    }
+}
+
+impl Method {
+    pub fn next(self) -> Self {
+        match self {
+            Method::Cfop => Method::Roux,
+            Method::Roux => Method::Cfop,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Method::Cfop => "CFOP",
+            Method::Roux => "Roux",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RouxStage {
+    FirstBlock,
+    SecondBlock,
+    Cmll,
+    Lse,
+    Solved,
+}
+
+impl RouxStage {
+    pub fn label(self) -> &'static str {
+        match self {
+            RouxStage::FirstBlock => "First block",
+            RouxStage::SecondBlock => "Second block",
+            RouxStage::Cmll => "CMLL",
+            RouxStage::Lse => "LSE",
+            RouxStage::Solved => "Solved",
+        }
+    }
+}
+
+/// Reports the Roux stage the solver is working on. Blocks are the 1x2x3
+/// regions on the L and R sides of the bottom two layers, so either side
+/// counts as a first block.
+pub fn roux_stage(cube: &Cube) -> RouxStage {
+    let left = block_solved(cube, -1);
+    let right = block_solved(cube, 1);
+    if !left && !right {
+        RouxStage::FirstBlock
+    } else if !(left && right) {
+        RouxStage::SecondBlock
+    } else if !cmll_solved(cube) {
+        RouxStage::Cmll
+    } else if !cube.is_solved() {
+        RouxStage::Lse
+    } else {
+        RouxStage::Solved
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CfopStage {
+    Cross,
+    /// Working on F2L with this many of the four slots done.
+    F2l(usize),
+    Oll,
+    Pll,
+    Auf,
+    Solved,
+}
+
+impl CfopStage {
+    pub fn label(self) -> String {
+        match self {
+            CfopStage::Cross => "Cross".to_string(),
+            CfopStage::F2l(done) => format!("F2L {done}/4"),
+            CfopStage::Oll => "OLL".to_string(),
+            CfopStage::Pll => "PLL".to_string(),
+            CfopStage::Auf => "AUF".to_string(),
+            CfopStage::Solved => "Solved".to_string(),
+        }
+    }
+}
+
+/// F2L slots by their (x, z) position: FR, FL, BR, BL.
+pub const F2L_SLOTS: [(i8, i8); 4] = [(1, 1), (-1, 1), (1, -1), (-1, -1)];
+
+/// Reports the CFOP stage the solver is working on, with the cross on D.
+pub fn cfop_stage(cube: &Cube) -> CfopStage {
+    let slots = F2L_SLOTS
+        .iter()
+        .filter(|(x, z)| f2l_slot_solved(cube, *x, *z))
+        .count();
+    if !cross_solved(cube) {
+        CfopStage::Cross
+    } else if slots < 4 {
+        CfopStage::F2l(slots)
+    } else if !oll_solved(cube) {
+        CfopStage::Oll
+    } else if !pll_solved(cube) {
+        CfopStage::Pll
+    } else if !cube.is_solved() {
+        CfopStage::Auf
+    } else {
+        CfopStage::Solved
+    }
+}
+
+/// The four D edges, both stickers matching their centers.
+pub fn cross_solved(cube: &Cube) -> bool {
+    cube.facelets_match_centers(|desc| {
+        desc.coord.y == -1 && (desc.coord.x == 0) != (desc.coord.z == 0)
+    })
+}
+
+/// The D corner and middle-layer edge at (`x`, `z`) both solved.
+pub fn f2l_slot_solved(cube: &Cube, x: i8, z: i8) -> bool {
+    cube.facelets_match_centers(|desc| desc.coord.x == x && desc.coord.z == z && desc.coord.y <= 0)
+}
+
+/// Stickers of the F2L pairs that are joined up outside their slot: the
+/// corner sits next to its edge with matching colors on both faces they
+/// share, ready to be inserted as one. The cross is on D as in `cfop_stage`.
+pub fn connected_f2l_pairs(cube: &Cube) -> Vec<usize> {
+    let colors = cube.face_colors();
+    let stickers = |coord: LatticePoint| -> Vec<(usize, Face)> {
+        facelet_descriptors()
+            .iter()
+            .enumerate()
+            .filter(|(_, desc)| desc.coord == coord)
+            .map(|(idx, desc)| (idx, desc.face))
+            .collect()
+    };
+    // The position of the piece carrying exactly `wanted`, with its stickers.
+    let find = |wanted: &[FaceColor]| -> Option<(LatticePoint, Vec<(usize, Face)>)> {
+        facelet_descriptors().iter().find_map(|desc| {
+            let piece = stickers(desc.coord);
+            (piece.len() == wanted.len()
+                && piece.iter().all(|(idx, _)| wanted.contains(&colors[*idx])))
+            .then_some((desc.coord, piece))
+        })
+    };
+    let mut highlighted = Vec::new();
+    for &(x, z) in &F2L_SLOTS {
+        if f2l_slot_solved(cube, x, z) {
+            continue;
+        }
+        let side_x = cube.center_color(if x == 1 { Face::Right } else { Face::Left });
+        let side_z = cube.center_color(if z == 1 { Face::Front } else { Face::Back });
+        let (Some((corner_at, corner)), Some((edge_at, edge))) = (
+            find(&[cube.center_color(Face::Down), side_x, side_z]),
+            find(&[side_x, side_z]),
+        ) else {
+            continue;
+        };
+        // Next to each other, the edge's two faces are both corner faces;
+        // each edge sticker must continue the corner sticker beside it.
+        let distance = (corner_at.x - edge_at.x).abs()
+            + (corner_at.y - edge_at.y).abs()
+            + (corner_at.z - edge_at.z).abs();
+        let matching = edge.iter().all(|(edge_idx, face)| {
+            corner.iter().any(|(corner_idx, corner_face)| {
+                corner_face == face && colors[*corner_idx] == colors[*edge_idx]
+            })
+        });
+        if distance == 1 && matching {
+            highlighted.extend(corner.iter().chain(&edge).map(|(idx, _)| *idx));
+        }
+    }
+    highlighted
+}
+
+/// Every U sticker matches the U center.
+pub fn oll_solved(cube: &Cube) -> bool {
+    cube.facelets_match_centers(|desc| desc.face == Face::Up)
+}
+
+/// Solved apart from a turn of U.
+pub fn pll_solved(cube: &Cube) -> bool {
+    let mut probe = *cube;
+    (0..4).any(|_| {
+        probe.apply_move(Move::U);
+        probe.facelets_match_centers(|_| true)
+    })
+}
+
+fn block_solved(cube: &Cube, side: i8) -> bool {
+    matches_roux_colors(cube, |desc| desc.coord.x == side && desc.coord.y <= 0)
+}
+
+fn cmll_solved(cube: &Cube) -> bool {
+    let mut probe = *cube;
+    (0..4).any(|_| {
+        probe.apply_move(Move::U);
+        matches_roux_colors(&probe, is_top_corner)
+    })
+}
+
+/// Like `Cube::facelets_match_centers`, but only the L and R centers are
+/// trusted: M moves carry the U, F, D and B centers around during LSE, so
+/// those faces are held to their colors in the solved scheme instead.
+fn matches_roux_colors(cube: &Cube, filter: impl Fn(&FaceletDescriptor) -> bool) -> bool {
+    let colors = cube.face_colors();
+    facelet_descriptors()
+        .iter()
+        .enumerate()
+        .filter(|(_, desc)| filter(desc))
+        .all(|(idx, desc)| {
+            let expected = match desc.face {
+                Face::Left | Face::Right => cube.center_color(desc.face),
+                face => face.default_color(),
+            };
+            colors[idx] == expected
+        })
+}
+
+fn is_top_corner(desc: &FaceletDescriptor) -> bool {
+    desc.coord.y == 1 && desc.coord.x != 0 && desc.coord.z != 0
+}
+
+/// Counts misoriented edges relative to the F/B axis, as used by ZZ: an
+/// edge's U/D sticker (or its F/B sticker if it has none) must face U/D,
+/// or F/B when the edge sits in the E slice.
+pub fn bad_edge_count(cube: &Cube) -> usize {
+    let colors = cube.face_colors();
+    let ud = [cube.center_color(Face::Up), cube.center_color(Face::Down)];
+    let fb = [
+        cube.center_color(Face::Front),
+        cube.center_color(Face::Back),
+    ];
+    edge_facelets()
+        .iter()
+        .filter(|(coord, stickers)| {
+            let primary = stickers
+                .iter()
+                .find(|(idx, _)| ud.contains(&colors[*idx]))
+                .or_else(|| stickers.iter().find(|(idx, _)| fb.contains(&colors[*idx])))
+                .map(|(_, face)| *face)
+                .expect("every edge carries a U/D or F/B color");
+            let good = if coord.y == 0 {
+                matches!(primary, Face::Front | Face::Back)
+            } else {
+                matches!(primary, Face::Up | Face::Down)
+            };
+            !good
+        })
+        .count()
+}
+
+/// EOLine: all edges oriented plus the DF and DB edges solved.
+pub fn eoline_solved(cube: &Cube) -> bool {
+    bad_edge_count(cube) == 0
+        && cube.facelets_match_centers(|desc| desc.coord.y == -1 && desc.coord.x == 0)
+}
+
+fn edge_facelets() -> Vec<(LatticePoint, [(usize, Face); 2])> {
+    let mut edges: Vec<(LatticePoint, Vec<(usize, Face)>)> = Vec::with_capacity(12);
+    for (idx, desc) in facelet_descriptors().iter().enumerate() {
+        let c = desc.coord;
+        if [c.x, c.y, c.z].iter().filter(|v| **v != 0).count() != 2 {
+            continue;
+        }
+        match edges.iter_mut().find(|(coord, _)| *coord == c) {
+            Some((_, stickers)) => stickers.push((idx, desc.face)),
+            None => edges.push((c, vec![(idx, desc.face)])),
+        }
+    }
+    edges
+        .into_iter()
+        .map(|(coord, stickers)| (coord, [stickers[0], stickers[1]]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn methods_parse_and_cycle() {
+        assert_eq!("roux".parse(), Ok(Method::Roux));
+        assert_eq!("cfop".parse(), Ok(Method::Cfop));
+        assert!("zz".parse::<Method>().is_err());
+        assert_eq!(Method::Cfop.next().next(), Method::Cfop);
+    }
+
+    #[test]
+    fn roux_stage_tracks_blocks() {
+        let mut cube = Cube::new();
+        assert_eq!(roux_stage(&cube), RouxStage::Solved);
+        cube.apply_move(Move::R);
+        assert_eq!(roux_stage(&cube), RouxStage::SecondBlock);
+        cube.apply_move(Move::L);
+        assert_eq!(roux_stage(&cube), RouxStage::FirstBlock);
+    }
+
+    #[test]
+    fn m_moves_leave_roux_in_lse() {
+        let mut cube = Cube::new();
+        cube.apply_move(Move::M);
+        assert_eq!(roux_stage(&cube), RouxStage::Lse);
+        cube.apply_sequence(&[Move::U, Move::M, Move::UPrime]);
+        assert_eq!(roux_stage(&cube), RouxStage::Lse);
+    }
+
+    #[test]
+    fn quarter_f_flips_four_edges() {
+        let mut cube = Cube::new();
+        cube.apply_move(Move::R);
+        assert_eq!(bad_edge_count(&cube), 0);
+        cube.apply_move(Move::F);
+        assert_eq!(bad_edge_count(&cube), 4);
+        assert!(!eoline_solved(&cube));
+        cube.apply_move(Move::FPrime);
+        assert!(eoline_solved(&cube));
+    }
+
+    #[test]
+    fn cfop_stage_walks_through_the_steps() {
+        let mut cube = Cube::new();
+        assert_eq!(cfop_stage(&cube), CfopStage::Solved);
+        cube.apply_move(Move::U);
+        assert_eq!(cfop_stage(&cube), CfopStage::Auf);
+        // Sune keeps F2L but twists three U corners.
+        cube.apply_sequence(&[
+            Move::R,
+            Move::U,
+            Move::RPrime,
+            Move::U,
+            Move::R,
+            Move::U2,
+            Move::RPrime,
+        ]);
+        assert_eq!(cfop_stage(&cube), CfopStage::Oll);
+        cube = Cube::new();
+        cube.apply_sequence(&[Move::R, Move::U, Move::RPrime]);
+        assert_eq!(cfop_stage(&cube), CfopStage::F2l(3));
+        assert!(!f2l_slot_solved(&cube, 1, 1));
+        cube.apply_move(Move::D);
+        assert_eq!(cfop_stage(&cube), CfopStage::Cross);
+    }
+
+    #[test]
+    fn auf_only_leaves_lse() {
+        let mut cube = Cube::new();
+        cube.apply_move(Move::U);
+        assert_eq!(roux_stage(&cube), RouxStage::Lse);
+    }
+    #[test]
+    fn finds_connected_f2l_pairs() {
+        let mut cube = Cube::new();
+        assert!(connected_f2l_pairs(&cube).is_empty());
+        // Lifts the FR pair out whole and parks it in the U layer.
+        cube.apply_sequence(&[Move::R, Move::U, Move::RPrime]);
+        assert_eq!(connected_f2l_pairs(&cube).len(), 5);
+        // Splits the FR pair, while the second R lifts the BR pair to DFR
+        // and DR.
+        cube.apply_sequence(&[Move::UPrime, Move::R, Move::U]);
+        let pairs = connected_f2l_pairs(&cube);
+        assert_eq!(pairs.len(), 5);
+        assert!(pairs.iter().all(|idx| {
+            let coord = facelet_descriptors()[*idx].coord;
+            coord.x == 1 && coord.y == -1
+        }));
+    }
+}
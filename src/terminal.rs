@@ -1,26 +1,39 @@
 use std::io;
 use std::io::{Stdout, Write, stdout};
+use std::path::PathBuf;
+use std::time::Duration;
 
 use crossterm::cursor;
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
 use crossterm::execute;
 use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
 
+use crate::config;
 use crate::raster::Frame;
+use crate::recording::Recorder;
 
 type TermResult<T> = io::Result<T>;
 
-pub struct TerminalGuard;
+pub struct TerminalGuard {
+    mouse: bool,
+}
 
 impl TerminalGuard {
-    pub fn new() -> TermResult<Self> {
+    pub fn new(mouse: bool) -> TermResult<Self> {
         terminal::enable_raw_mode()?;
         execute!(stdout(), EnterAlternateScreen, cursor::Hide)?;
-        Ok(Self)
+        if mouse {
+            execute!(stdout(), EnableMouseCapture)?;
+        }
+        Ok(Self { mouse })
     }
 }
 
 impl Drop for TerminalGuard {
     fn drop(&mut self) {
+        if self.mouse {
+            let _ = execute!(stdout(), DisableMouseCapture);
+        }
         let _ = terminal::disable_raw_mode();
         let _ = execute!(stdout(), LeaveAlternateScreen, cursor::Show);
     }
@@ -28,18 +41,216 @@ impl Drop for TerminalGuard {
 
 pub struct FrameWriter {
     stdout: Stdout,
+    recorder: Option<Recorder>,
 }
 
 impl FrameWriter {
     pub fn new() -> Self {
-        Self { stdout: stdout() }
+        Self {
+            stdout: stdout(),
+            recorder: None,
+        }
     }
 
     pub fn blit(&mut self, frame: &Frame) -> TermResult<()> {
         let ansi = frame.as_ansi_string();
         execute!(self.stdout, cursor::MoveTo(0, 0))?;
         self.stdout.write_all(ansi.as_bytes())?;
+        self.blit_images(frame)?;
         self.stdout.flush()?;
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.capture(frame, &ansi)?;
+        }
         Ok(())
     }
+
+    /// Draws the frame's bitmaps over the characters just written.
+    fn blit_images(&mut self, frame: &Frame) -> TermResult<()> {
+        for image in frame.images() {
+            execute!(self.stdout, cursor::MoveTo(image.x as u16, image.y as u16))?;
+            self.stdout.write_all(image.data.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Hands every frame blitted from now on to `recorder`.
+    pub fn start_recording(&mut self, recorder: Recorder) {
+        self.recorder = Some(recorder);
+    }
+
+    /// Finishes the recording, if one is running, and returns its path.
+    pub fn stop_recording(&mut self) -> TermResult<Option<PathBuf>> {
+        self.recorder.take().map(Recorder::finish).transpose()
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Asks the terminal to put `text` on the system clipboard (OSC 52).
+    /// Terminals without support silently ignore it.
+    pub fn copy_to_clipboard(&mut self, text: &str) -> TermResult<()> {
+        write!(self.stdout, "\x1b]52;c;{}\x07", base64(text.as_bytes()))?;
+        self.stdout.flush()
+    }
+
+    /// Text on the system clipboard, from the desktop's clipboard where
+    /// there is one, or else asked of the terminal (OSC 52), which many
+    /// terminals refuse. `None` if it is empty or can't be read.
+    pub fn read_clipboard(&mut self) -> TermResult<Option<String>> {
+        if let Ok(text) = arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text())
+            && !text.is_empty()
+        {
+            return Ok(Some(text));
+        }
+        write!(self.stdout, "\x1b]52;c;?\x07")?;
+        self.stdout.flush()?;
+        let reply = read_reply(Duration::from_millis(config::CLIPBOARD_REPLY_MILLIS))?;
+        Ok(clipboard_reply(&reply))
+    }
+}
+
+/// Bytes the terminal sends back within `timeout`, up to the end of its
+/// first string reply. Keys pressed meanwhile are swallowed.
+#[cfg(unix)]
+fn read_reply(timeout: Duration) -> TermResult<Vec<u8>> {
+    use std::fs::File;
+    use std::io::Read;
+    use std::os::fd::AsRawFd;
+    use std::time::Instant;
+
+    let mut tty = File::open("/dev/tty")?;
+    let deadline = Instant::now() + timeout;
+    let mut reply = Vec::new();
+    let mut buffer = [0; 1024];
+    while !reply.ends_with(b"\x07") && !reply.ends_with(b"\x1b\\") {
+        let left = deadline.saturating_duration_since(Instant::now());
+        let mut poll = libc::pollfd {
+            fd: tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: `poll` is one valid pollfd that outlives the call.
+        let ready = unsafe { libc::poll(&mut poll, 1, left.as_millis() as libc::c_int) };
+        if ready < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(err);
+        }
+        if ready == 0 {
+            break;
+        }
+        let read = tty.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        reply.extend_from_slice(&buffer[..read]);
+    }
+    Ok(reply)
+}
+
+#[cfg(not(unix))]
+fn read_reply(_timeout: Duration) -> TermResult<Vec<u8>> {
+    Ok(Vec::new())
+}
+
+/// The text in an OSC 52 reply, `ESC ] 52 ; c ; <base64>` ended by BEL or
+/// `ESC \`, if it holds any.
+fn clipboard_reply(reply: &[u8]) -> Option<String> {
+    let start = reply.windows(5).position(|window| window == b"\x1b]52;")? + 5;
+    let rest = &reply[start..];
+    let data = &rest[rest.iter().position(|&byte| byte == b';')? + 1..];
+    let end = data.iter().position(|&byte| byte == 0x07 || byte == 0x1b)?;
+    let text = String::from_utf8(unbase64(&data[..end])?).ok()?;
+    (!text.is_empty()).then_some(text)
+}
+
+/// Pixel size of one character cell, when the terminal reports its size in
+/// pixels.
+pub fn cell_pixel_size() -> Option<(usize, usize)> {
+    let size = terminal::window_size().ok()?;
+    if size.width == 0 || size.height == 0 || size.columns == 0 || size.rows == 0 {
+        return None;
+    }
+    Some((
+        usize::from(size.width / size.columns),
+        usize::from(size.height / size.rows),
+    ))
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let word = chunk.iter().enumerate().fold(0u32, |word, (i, byte)| {
+            word | (*byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(word >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Decodes standard base64, padded or not; `None` on any other byte.
+fn unbase64(encoded: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(encoded.len() / 4 * 3);
+    let mut word = 0u32;
+    let mut bits = 0;
+    for &byte in encoded.iter().take_while(|&&byte| byte != b'=') {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        word = word << 6 | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((word >> bits) as u8);
+            word &= (1 << bits) - 1;
+        }
+    }
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_pads_partial_chunks() {
+        assert_eq!(base64(b"UUF"), "VVVG");
+        assert_eq!(base64(b"UU"), "VVU=");
+        assert_eq!(base64(b"U"), "VQ==");
+        assert_eq!(base64(b""), "");
+    }
+
+    #[test]
+    fn clipboard_replies_decode() {
+        for text in ["R U R' U'", "R2", "F"] {
+            assert_eq!(
+                unbase64(base64(text.as_bytes()).as_bytes()),
+                Some(text.into())
+            );
+        }
+        let encoded = base64(b"R U2");
+        let bel = format!("\x1b]52;c;{encoded}\x07");
+        let st = format!("\x1b]52;;{encoded}\x1b\\");
+        assert_eq!(clipboard_reply(bel.as_bytes()).as_deref(), Some("R U2"));
+        assert_eq!(clipboard_reply(st.as_bytes()).as_deref(), Some("R U2"));
+        // Terminals that refuse answer with nothing or an empty reply.
+        assert_eq!(clipboard_reply(b""), None);
+        assert_eq!(clipboard_reply(b"\x1b]52;c;\x07"), None);
+        assert_eq!(clipboard_reply(b"\x1b]52;c;R!\x07"), None);
+    }
 }
@@ -0,0 +1,236 @@
+use std::fs;
+
+use crossterm::style::Color;
+use once_cell::sync::OnceCell;
+
+use crate::config;
+use crate::cube::FaceColor;
+
+/// Every color and shade decision the renderer and overlays make.
+#[derive(Clone, Debug)]
+pub struct Theme {
+    /// Sticker color per face color, indexed by `FaceColor::index`.
+    pub faces: [Color; 6],
+    /// Fill characters from darkest to brightest.
+    pub shades: Vec<char>,
+    /// Fills cells nothing was drawn into; `None` keeps the terminal's own.
+    pub background: Option<Color>,
+    pub hud: Option<Color>,
+    /// Background and text of tooltips and result panels.
+    pub highlight: Color,
+    pub highlight_text: Color,
+    pub outline: Color,
+    /// The cube body behind the stickers.
+    pub body: Color,
+    pub stars: Color,
+}
+
+static ACTIVE: OnceCell<Theme> = OnceCell::new();
+
+/// The theme installed at startup, or the classic one.
+pub fn active() -> &'static Theme {
+    ACTIVE.get_or_init(Theme::classic)
+}
+
+/// Makes `theme` the active theme; only the first call has any effect.
+pub fn install(theme: Theme) {
+    let _ = ACTIVE.set(theme);
+}
+
+pub const BUILT_IN: [&str; 3] = ["classic", "pastel", "solarized"];
+
+/// Loads a built-in theme by name, or otherwise a theme file from disk.
+pub fn load(spec: &str) -> Result<Theme, String> {
+    match spec {
+        "classic" => Ok(Theme::classic()),
+        "pastel" => parse(PASTEL),
+        "solarized" => parse(SOLARIZED),
+        path => {
+            let text = fs::read_to_string(path).map_err(|err| {
+                format!(
+                    "cannot read theme '{path}': {err} (built-in themes: {})",
+                    BUILT_IN.join(", ")
+                )
+            })?;
+            parse(&text).map_err(|err| format!("{path}: {err}"))
+        }
+    }
+}
+
+impl Theme {
+    pub fn classic() -> Self {
+        Self {
+            faces: [
+                Color::White,
+                Color::Yellow,
+                Color::Red,
+                Color::Rgb {
+                    r: 255,
+                    g: 140,
+                    b: 0,
+                },
+                Color::Blue,
+                Color::Green,
+            ],
+            shades: config::ASCII_SHADES.to_vec(),
+            background: None,
+            hud: None,
+            highlight: Color::White,
+            highlight_text: Color::Black,
+            outline: config::TOON_OUTLINE_COLOR,
+            body: Color::Rgb {
+                r: 24,
+                g: 24,
+                b: 24,
+            },
+            stars: Color::DarkGrey,
+        }
+    }
+
+    pub fn face(&self, color: FaceColor) -> Color {
+        self.faces[color.index()]
+    }
+}
+
+const PASTEL: &str = r##"
+background = "#1e1e28"
+hud = "#c8c8dc"
+highlight = "#f5c2e7"
+highlight_text = "#1e1e28"
+outline = "#585b70"
+body = "#11111b"
+stars = "#45475a"
+
+[faces]
+white = "#f5f5f5"
+yellow = "#f9e2af"
+red = "#f38ba8"
+orange = "#fab387"
+blue = "#89b4fa"
+green = "#a6e3a1"
+"##;
+
+const SOLARIZED: &str = r##"
+shades = " .-:=+*#%@"
+background = "#002b36"
+hud = "#93a1a1"
+highlight = "#eee8d5"
+highlight_text = "#073642"
+outline = "#586e75"
+body = "#001f27"
+stars = "#586e75"
+
+[faces]
+white = "#fdf6e3"
+yellow = "#b58900"
+red = "#dc322f"
+orange = "#cb4b16"
+blue = "#268bd2"
+green = "#859900"
+"##;
+
+/// Parses the TOML subset themes use: `key = "value"` pairs, with face
+/// colors under a `[faces]` table. Keys left out keep the classic value.
+pub fn parse(text: &str) -> Result<Theme, String> {
+    let mut theme = Theme::classic();
+    let mut section = String::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let at = |msg: String| format!("line {}: {msg}", number + 1);
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.trim().to_string();
+            if section != "faces" {
+                return Err(at(format!("unknown table [{section}]")));
+            }
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| at(format!("expected key = \"value\", got '{line}'")))?;
+        let key = key.trim();
+        let value = value
+            .trim()
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .ok_or_else(|| at(format!("value for '{key}' must be a quoted string")))?;
+        if section == "faces" {
+            let color = face_by_name(key).ok_or_else(|| at(format!("unknown face '{key}'")))?;
+            theme.faces[color.index()] = parse_color(value).map_err(at)?;
+            continue;
+        }
+        match key {
+            "shades" => {
+                if value.chars().count() < 2 {
+                    return Err(at("shades needs at least two characters".to_string()));
+                }
+                theme.shades = value.chars().collect();
+            }
+            "background" => theme.background = Some(parse_color(value).map_err(at)?),
+            "hud" => theme.hud = Some(parse_color(value).map_err(at)?),
+            "highlight" => theme.highlight = parse_color(value).map_err(at)?,
+            "highlight_text" => theme.highlight_text = parse_color(value).map_err(at)?,
+            "outline" => theme.outline = parse_color(value).map_err(at)?,
+            "body" => theme.body = parse_color(value).map_err(at)?,
+            "stars" => theme.stars = parse_color(value).map_err(at)?,
+            other => return Err(at(format!("unknown key '{other}'"))),
+        }
+    }
+    Ok(theme)
+}
+
+fn face_by_name(name: &str) -> Option<FaceColor> {
+    [
+        FaceColor::White,
+        FaceColor::Yellow,
+        FaceColor::Red,
+        FaceColor::Orange,
+        FaceColor::Blue,
+        FaceColor::Green,
+    ]
+    .into_iter()
+    .find(|color| color.name() == name)
+}
+
+/// Accepts `#rrggbb` or a terminal color name such as `dark_grey`.
+fn parse_color(value: &str) -> Result<Color, String> {
+    if let Some(hex) = value.strip_prefix('#') {
+        let channel = |range: std::ops::Range<usize>| {
+            hex.get(range)
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+        };
+        if hex.len() == 6
+            && let (Some(r), Some(g), Some(b)) = (channel(0..2), channel(2..4), channel(4..6))
+        {
+            return Ok(Color::Rgb { r, g, b });
+        }
+        return Err(format!("invalid hex color '{value}'"));
+    }
+    Color::try_from(value).map_err(|_| format!("unknown color '{value}'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_themes_parse() {
+        for name in BUILT_IN {
+            assert!(load(name).is_ok(), "{name}");
+        }
+    }
+
+    #[test]
+    fn parse_reads_keys_and_reports_line_numbers() {
+        let theme = parse("hud = \"grey\"\n[faces]\nred = \"#ff0000\"").unwrap();
+        assert_eq!(theme.hud, Some(Color::Grey));
+        assert_eq!(
+            theme.face(FaceColor::Red),
+            Color::Rgb { r: 255, g: 0, b: 0 }
+        );
+        let err = parse("hud = \"grey\"\nglow = \"red\"").unwrap_err();
+        assert!(err.starts_with("line 2:"), "{err}");
+    }
+}
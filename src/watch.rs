@@ -0,0 +1,82 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::cube::Cube;
+
+/// A file holding a facelet string, for `--watch`: other programs write
+/// states to it and cubex shows each one.
+pub struct StateWatch {
+    path: PathBuf,
+    /// Modification time at the last check; `None` if the file was missing.
+    modified: Option<SystemTime>,
+    checked: bool,
+}
+
+impl StateWatch {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            modified: None,
+            checked: false,
+        }
+    }
+
+    /// The file's state if it changed since the last call, or why it
+    /// can't be read. The first call always reads it.
+    pub fn poll(&mut self) -> Option<Result<Cube, String>> {
+        let modified = fs::metadata(&self.path)
+            .and_then(|meta| meta.modified())
+            .ok();
+        if self.checked && modified == self.modified {
+            return None;
+        }
+        self.checked = true;
+        self.modified = modified;
+        let path = self.path.display();
+        Some(
+            fs::read_to_string(&self.path)
+                .map_err(|err| format!("{path}: {err}"))
+                .and_then(|text| {
+                    Cube::from_facelet_string(text.trim()).map_err(|err| format!("{path}: {err}"))
+                }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::Move;
+    use std::fs::File;
+    use std::time::Duration;
+
+    #[test]
+    fn reloads_only_when_the_file_changes() {
+        let dir = std::env::temp_dir().join(format!("cubex-watch-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.txt");
+        let mut cube = Cube::new();
+        cube.apply_move(Move::R);
+        fs::write(&path, format!("{}\n", cube.to_facelet_string())).unwrap();
+
+        let mut watch = StateWatch::new(path.clone());
+        assert_eq!(watch.poll(), Some(Ok(cube)));
+        assert_eq!(watch.poll(), None);
+
+        fs::write(&path, "not a cube").unwrap();
+        // Filesystem timestamps can be coarse; make the change visible.
+        let later = SystemTime::now() + Duration::from_secs(5);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(matches!(watch.poll(), Some(Err(_))));
+        assert_eq!(watch.poll(), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(watch.poll(), Some(Err(_))));
+    }
+}